@@ -0,0 +1,2 @@
+DROP TABLE commands;
+ALTER TABLE devices DROP COLUMN identity_fingerprint;
@@ -0,0 +1,15 @@
+-- Devices authenticate via the identity certificate issued to them
+-- via SCEP. We persist its SHA-256 fingerprint upon enrollment.
+ALTER TABLE devices ADD COLUMN identity_fingerprint VARCHAR;
+
+CREATE TABLE commands (
+  command_uuid VARCHAR PRIMARY KEY NOT NULL,
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  request_type VARCHAR NOT NULL,
+  -- The full command as sent to the device, in XML property list form.
+  payload BLOB NOT NULL,
+  -- One of "Queued", "Acknowledged", "Error", "CommandFormatError" or "NotNow".
+  status VARCHAR NOT NULL,
+  creation_date DATETIME NOT NULL,
+  last_update DATETIME NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE activation_lock_bypass_codes;
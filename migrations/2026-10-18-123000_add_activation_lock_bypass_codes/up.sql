@@ -0,0 +1,12 @@
+CREATE TABLE activation_lock_bypass_codes (
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  -- Either "server" if we generated this code,
+  -- or "device" if reported via the ActivationLockBypassCode query.
+  source VARCHAR NOT NULL,
+  -- The bypass code itself, encrypted via our vault.
+  sealed_code BLOB NOT NULL,
+  -- The hash of this code, as sent to Apple.
+  code_hash VARCHAR NOT NULL,
+  creation_date DATETIME NOT NULL,
+  PRIMARY KEY (udid, source)
+);
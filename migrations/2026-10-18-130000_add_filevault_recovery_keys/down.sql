@@ -0,0 +1 @@
+DROP TABLE filevault_recovery_keys;
@@ -0,0 +1,9 @@
+CREATE TABLE filevault_recovery_keys (
+  udid VARCHAR PRIMARY KEY NOT NULL REFERENCES devices (udid),
+  -- The personal recovery key, encrypted via our vault.
+  sealed_key BLOB NOT NULL,
+  creation_date DATETIME NOT NULL,
+  -- When this key was last retrieved by an administrator.
+  -- Viewed keys are rotated.
+  viewed_date DATETIME
+);
@@ -0,0 +1 @@
+DROP TABLE bootstrap_tokens;
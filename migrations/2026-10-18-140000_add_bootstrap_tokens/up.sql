@@ -0,0 +1,9 @@
+CREATE TABLE bootstrap_tokens (
+  udid VARCHAR PRIMARY KEY NOT NULL REFERENCES devices (udid),
+  -- The bootstrap token, encrypted via our vault.
+  sealed_token BLOB NOT NULL,
+  -- The SHA-256 fingerprint of the identity certificate which escrowed this token.
+  -- It is only provided to this same identity.
+  identity_fingerprint VARCHAR NOT NULL,
+  creation_date DATETIME NOT NULL
+);
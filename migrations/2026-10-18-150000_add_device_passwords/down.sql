@@ -0,0 +1,2 @@
+DROP TABLE device_passwords;
+ALTER TABLE devices DROP COLUMN is_apple_silicon;
@@ -0,0 +1,21 @@
+-- Reported via the IsAppleSilicon query. Only applicable to Macs.
+ALTER TABLE devices ADD COLUMN is_apple_silicon BOOLEAN;
+
+CREATE TABLE device_passwords (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  -- The kind of password, such as "recovery_lock" or "firmware".
+  kind VARCHAR NOT NULL,
+  -- The password itself, encrypted via our vault.
+  sealed_password BLOB NOT NULL,
+  -- The command which sets this password on the device.
+  command_uuid VARCHAR NOT NULL REFERENCES commands (command_uuid),
+  -- One of "pending", "active", "superseded" or "failed".
+  -- Only one password of a kind should be active for a device.
+  status VARCHAR NOT NULL,
+  creation_date DATETIME NOT NULL,
+  -- When the device acknowledged this password.
+  activation_date DATETIME,
+  -- When the device last verified this password.
+  verification_date DATETIME
+);
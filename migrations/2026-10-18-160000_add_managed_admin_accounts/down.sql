@@ -0,0 +1,2 @@
+DROP TABLE secret_accesses;
+ALTER TABLE devices DROP COLUMN admin_account_guid;
@@ -0,0 +1,11 @@
+-- The GUID of the managed administrator account created via AccountConfiguration.
+-- Its password is tracked within device_passwords, as the "admin_account" kind.
+ALTER TABLE devices ADD COLUMN admin_account_guid VARCHAR;
+
+CREATE TABLE secret_accesses (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  -- The kind of secret accessed, such as "filevault_recovery_key" or "admin_account".
+  secret_kind VARCHAR NOT NULL,
+  access_date DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Reported via the IsSupervised query.
+ALTER TABLE devices ADD COLUMN is_supervised BOOLEAN;
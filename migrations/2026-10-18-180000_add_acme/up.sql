@@ -0,0 +1,43 @@
+-- One-time identifiers we provide to devices within ACME payloads.
+-- A device presents its identifier when placing an order, tying the order to it.
+CREATE TABLE acme_client_identifiers (
+  client_identifier VARCHAR PRIMARY KEY NOT NULL,
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  creation_date DATETIME NOT NULL,
+  -- Set once an order has been placed with this identifier.
+  use_date DATETIME
+);
+
+-- Anti-replay nonces handed out via the newNonce resource.
+-- Each is removed once used.
+CREATE TABLE acme_nonces (
+  nonce VARCHAR PRIMARY KEY NOT NULL,
+  creation_date DATETIME NOT NULL
+);
+
+CREATE TABLE acme_accounts (
+  account_id VARCHAR PRIMARY KEY NOT NULL,
+  -- The RFC 7638 thumbprint of the account's public key.
+  key_thumbprint VARCHAR NOT NULL UNIQUE,
+  -- The account's public key, as a JSON Web Key.
+  jwk VARCHAR NOT NULL,
+  creation_date DATETIME NOT NULL
+);
+
+-- Orders only ever contain a single identifier, and therefore a single
+-- authorization and device-attest-01 challenge. Both share the order's ID.
+CREATE TABLE acme_orders (
+  order_id VARCHAR PRIMARY KEY NOT NULL,
+  account_id VARCHAR NOT NULL REFERENCES acme_accounts (account_id),
+  client_identifier VARCHAR NOT NULL REFERENCES acme_client_identifiers (client_identifier),
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  -- One of "pending", "ready", "valid" or "invalid".
+  status VARCHAR NOT NULL,
+  challenge_token VARCHAR NOT NULL,
+  -- The public key attested to by the device, which the CSR must match.
+  attested_public_key BLOB,
+  -- The issued certificate, in DER form.
+  certificate BLOB,
+  creation_date DATETIME NOT NULL,
+  expiration_date DATETIME NOT NULL
+);
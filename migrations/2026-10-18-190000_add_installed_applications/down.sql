@@ -0,0 +1,2 @@
+DROP TABLE installed_applications;
+ALTER TABLE devices DROP COLUMN applications_report_date;
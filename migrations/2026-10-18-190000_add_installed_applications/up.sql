@@ -0,0 +1,14 @@
+-- When the device last reported its installed applications.
+-- If unset, we do not yet know which applications are installed.
+ALTER TABLE devices ADD COLUMN applications_report_date DATETIME;
+
+-- The applications a device reported via InstalledApplicationList.
+-- These are replaced in full upon every report.
+CREATE TABLE installed_applications (
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  bundle_identifier VARCHAR NOT NULL,
+  name VARCHAR,
+  version VARCHAR,
+  short_version VARCHAR,
+  PRIMARY KEY (udid, bundle_identifier)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE devices DROP COLUMN user_name;
+DROP TABLE users;
@@ -0,0 +1,11 @@
+-- Users whose attributes are used to personalize profiles,
+-- such as the email address within mail accounts.
+CREATE TABLE users (
+  user_name VARCHAR NOT NULL PRIMARY KEY,
+  full_name VARCHAR,
+  email_address VARCHAR,
+  creation_date DATETIME NOT NULL
+);
+
+-- The user a device is assigned to, if any.
+ALTER TABLE devices ADD COLUMN user_name VARCHAR REFERENCES users (user_name);
@@ -0,0 +1,2 @@
+ALTER TABLE devices DROP COLUMN asset_tag;
+ALTER TABLE devices DROP COLUMN device_name;
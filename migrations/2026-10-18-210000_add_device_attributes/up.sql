@@ -0,0 +1,4 @@
+-- The name of the device, as reported by the device itself.
+ALTER TABLE devices ADD COLUMN device_name VARCHAR;
+-- An asset tag, as assigned by administrators.
+ALTER TABLE devices ADD COLUMN asset_tag VARCHAR;
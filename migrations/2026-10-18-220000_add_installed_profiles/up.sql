@@ -0,0 +1,11 @@
+-- The latest revision of each profile we have installed on a device.
+-- Its version is incremented whenever the hash of its contents changes.
+CREATE TABLE installed_profiles (
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  identifier VARCHAR NOT NULL,
+  version INTEGER NOT NULL,
+  content_hash VARCHAR NOT NULL,
+  command_uuid VARCHAR NOT NULL REFERENCES commands (command_uuid),
+  update_date DATETIME NOT NULL,
+  PRIMARY KEY (udid, identifier)
+);
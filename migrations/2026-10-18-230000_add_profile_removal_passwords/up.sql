@@ -0,0 +1,8 @@
+-- Passwords required to remove profiles we serve or install, keyed by profile identifier.
+-- The same password is used across all devices a profile is installed on.
+CREATE TABLE profile_removal_passwords (
+  identifier VARCHAR PRIMARY KEY NOT NULL,
+  -- The removal password, encrypted via our vault.
+  sealed_password BLOB NOT NULL,
+  creation_date DATETIME NOT NULL
+);
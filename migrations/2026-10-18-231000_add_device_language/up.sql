@@ -0,0 +1,2 @@
+-- The language the device prefers, per the Accept-Language header of its check-ins.
+ALTER TABLE devices ADD COLUMN language VARCHAR;
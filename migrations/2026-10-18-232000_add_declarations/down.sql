@@ -0,0 +1,3 @@
+ALTER TABLE devices DROP COLUMN declarations_update_date;
+DROP TABLE device_declarations;
+DROP TABLE declarations;
@@ -0,0 +1,21 @@
+-- Declarations for Declarative Device Management, keyed by their identifier.
+CREATE TABLE declarations (
+  identifier VARCHAR PRIMARY KEY NOT NULL,
+  -- The type of declaration, such as "com.apple.configuration.passcode.settings".
+  declaration_type VARCHAR NOT NULL,
+  -- The declaration's payload, as JSON.
+  payload TEXT NOT NULL,
+  -- Derived from the declaration's contents, changing whenever they do.
+  server_token VARCHAR NOT NULL,
+  update_date DATETIME NOT NULL
+);
+
+-- The declarations each device is given.
+CREATE TABLE device_declarations (
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  identifier VARCHAR NOT NULL REFERENCES declarations (identifier),
+  PRIMARY KEY (udid, identifier)
+);
+
+-- When the set of declarations given to this device last changed.
+ALTER TABLE devices ADD COLUMN declarations_update_date DATETIME;
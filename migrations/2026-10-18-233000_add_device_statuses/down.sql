@@ -0,0 +1,2 @@
+DROP TABLE device_status_changes;
+DROP TABLE device_statuses;
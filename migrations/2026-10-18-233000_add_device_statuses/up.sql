@@ -0,0 +1,20 @@
+-- The latest value of each status item a device has reported via Declarative Device Management.
+CREATE TABLE device_statuses (
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  -- The status item's path, such as "softwareupdate.install-state".
+  status_item VARCHAR NOT NULL,
+  -- The reported value, as JSON.
+  value TEXT NOT NULL,
+  update_date DATETIME NOT NULL,
+  PRIMARY KEY (udid, status_item)
+);
+
+-- Every change to a status item, including its first report.
+CREATE TABLE device_status_changes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  udid VARCHAR NOT NULL REFERENCES devices (udid),
+  status_item VARCHAR NOT NULL,
+  -- The reported value, as JSON. Absent if the item was removed.
+  value TEXT,
+  report_date DATETIME NOT NULL
+);
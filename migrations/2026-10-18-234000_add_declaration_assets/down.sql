@@ -0,0 +1 @@
+DROP TABLE declaration_assets;
@@ -0,0 +1,12 @@
+-- The data of asset declarations, served to devices via signed URLs.
+-- Data is either read from our assets directory, or stored within our database.
+CREATE TABLE declaration_assets (
+  identifier VARCHAR PRIMARY KEY NOT NULL REFERENCES declarations (identifier),
+  -- For example, "application/pkcs12".
+  content_type VARCHAR NOT NULL,
+  -- A path relative to our assets directory.
+  file_name VARCHAR,
+  -- The asset's data, encrypted via our vault. Assets often contain credentials.
+  sealed_contents BLOB,
+  update_date DATETIME NOT NULL
+);
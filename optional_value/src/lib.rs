@@ -41,8 +41,11 @@ pub fn payload(_metadata: TokenStream, item: TokenStream) -> TokenStream {
 
         // Good - we have an optional value.
         // Let's tack on our own serde attributes.
+        // As deserialize_with is specified, serde no longer treats an absent key as None
+        // unless we explicitly provide a default. Device responses often omit keys.
         let serialize_attr: Attribute = parse_quote!(
             #[serde(
+                default,
                 deserialize_with = "crate::payloads::ser::deserialize_option_some",
                 serialize_with = "crate::payloads::ser::serialize_option_some",
                 skip_serializing_if = "Option::is_none"
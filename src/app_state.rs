@@ -1,23 +1,31 @@
+use crate::assets::MessageCatalog;
 use crate::certificates::Certificates;
 use crate::config::Config;
 use crate::database::Database;
+use crate::vault::Vault;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config,
     pub certificates: Certificates,
     pub database: Database,
+    pub vault: Vault,
+    pub messages: MessageCatalog,
 }
 
 impl AppState {
     pub fn with_config(config: Config) -> Self {
         let certificates = Certificates::load_certs(&config);
         let database = Database::open(&config.storage.database_path);
+        let vault = Vault::load_key(&config);
+        let messages = MessageCatalog::load(&config);
 
         AppState {
             config,
             certificates,
             database,
+            vault,
+            messages,
         }
     }
 }
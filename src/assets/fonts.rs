@@ -0,0 +1,175 @@
+use crate::config::Config;
+use crate::payloads::{BasePayload, FontPayload, Payload, PayloadType, Profile};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use ttf_parser::{PlatformId, name, name_id};
+
+/// The file extensions of fonts we're able to serve.
+const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+
+/// The size of a font's table directory header, and of each of its table records.
+/// https://learn.microsoft.com/en-us/typography/opentype/spec/otff#table-directory
+const TABLE_DIRECTORY_SIZE: usize = 12;
+const TABLE_RECORD_SIZE: usize = 16;
+
+#[derive(Clone, Serialize)]
+/// A font available within our assets directory.
+pub struct FontAsset {
+    pub postscript_name: String,
+    /// The path of this font, relative to our assets directory.
+    pub file_name: String,
+}
+
+/// Scans our assets directory (and its subdirectories) for TrueType and OpenType fonts.
+/// Fonts we're unable to parse are skipped.
+pub fn index_fonts(config: &Config) -> Vec<FontAsset> {
+    let mut fonts = vec![];
+    scan_fonts(config, |font, _| fonts.push(font));
+    fonts.sort_by(|left, right| left.postscript_name.cmp(&right.postscript_name));
+    fonts
+}
+
+/// Names every font within our assets directory, providing each alongside its path.
+/// Only the name table of each font is read. Fonts we're unable to read or parse are skipped.
+fn scan_fonts(config: &Config, mut visit: impl FnMut(FontAsset, PathBuf)) {
+    let assets_dir = Path::new(&config.storage.assets_dir);
+    let mut font_paths = vec![];
+    find_fonts(assets_dir, &mut font_paths);
+
+    for font_path in font_paths {
+        let Some(postscript_name) =
+            read_name_table(&font_path).and_then(|name_table| postscript_name(&name_table))
+        else {
+            println!("unable to determine name of font {}", font_path.display());
+            continue;
+        };
+        let file_name = font_path
+            .strip_prefix(assets_dir)
+            .expect("fonts should be within our assets directory")
+            .to_string_lossy()
+            .to_string();
+        visit(
+            FontAsset {
+                postscript_name,
+                file_name,
+            },
+            font_path,
+        );
+    }
+}
+
+/// Recursively collects the paths of all fonts within the given directory.
+fn find_fonts(directory: &Path, font_paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_fonts(&path, font_paths);
+            continue;
+        }
+
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| FONT_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+        if is_font {
+            font_paths.push(path);
+        }
+    }
+}
+
+/// Reads only the name table of the font at the given path, locating it via its table directory.
+fn read_name_table(font_path: &Path) -> Option<Vec<u8>> {
+    let mut file = File::open(font_path).ok()?;
+    let mut header = [0; TABLE_DIRECTORY_SIZE];
+    file.read_exact(&mut header).ok()?;
+    let table_count = u16::from_be_bytes([header[4], header[5]]);
+
+    let mut table_records = vec![0; usize::from(table_count) * TABLE_RECORD_SIZE];
+    file.read_exact(&mut table_records).ok()?;
+    let name_record = table_records
+        .chunks_exact(TABLE_RECORD_SIZE)
+        .find(|record| &record[..4] == b"name")?;
+    let offset = u32::from_be_bytes(name_record[8..12].try_into().ok()?);
+    let length = u32::from_be_bytes(name_record[12..16].try_into().ok()?);
+
+    // Avoid allocating for tables extending past the end of the file.
+    if u64::from(offset) + u64::from(length) > file.metadata().ok()?.len() {
+        return None;
+    }
+    let mut name_table = vec![0; length as usize];
+    file.seek(SeekFrom::Start(offset.into())).ok()?;
+    file.read_exact(&mut name_table).ok()?;
+    Some(name_table)
+}
+
+/// Reads the PostScript name from a font's name table.
+fn postscript_name(name_table: &[u8]) -> Option<String> {
+    name::Table::parse(name_table)?
+        .names
+        .into_iter()
+        .filter(|name| name.name_id == name_id::POST_SCRIPT_NAME)
+        .find_map(|name| {
+            if name.platform_id == PlatformId::Macintosh {
+                // PostScript names are limited to printable ASCII.
+                String::from_utf8(name.name.to_vec()).ok()
+            } else {
+                name.to_string()
+            }
+        })
+}
+
+/// Creates a profile installing the fonts with the given PostScript names.
+/// Returns the first name not present within our assets directory as an error.
+///
+/// Fonts are located as we scan for them, so that any removed since
+/// they were last listed are simply reported as missing.
+/// Only the requested fonts are read in full.
+pub fn font_profile(
+    config: &Config,
+    postscript_names: &[String],
+) -> Result<Profile<Payload>, String> {
+    let service_config = &config.service;
+    let mut requested_fonts = vec![];
+    scan_fonts(config, |font, font_path| {
+        if postscript_names.contains(&font.postscript_name) {
+            requested_fonts.push((font.postscript_name, font_path));
+        }
+    });
+
+    let mut payloads = vec![];
+    for postscript_name in postscript_names {
+        let Some(contents) = requested_fonts
+            .iter()
+            .find(|(name, _)| name == postscript_name)
+            .and_then(|(_, font_path)| fs::read(font_path).ok())
+        else {
+            return Err(postscript_name.clone());
+        };
+
+        payloads.push(Payload::Font(FontPayload {
+            base: BasePayload {
+                identifier: format!("{}.fonts.{postscript_name}", service_config.base_identifier),
+                payload_type: PayloadType::Font,
+                ..Default::default()
+            },
+            name: Some(postscript_name.clone()),
+            font: contents,
+        }));
+    }
+
+    Ok(Profile {
+        base: BasePayload {
+            identifier: format!("{}.fonts", service_config.base_identifier),
+            display_name: Some("Fonts".to_string()),
+            organization: Some(service_config.organization_name.clone()),
+            ..Default::default()
+        },
+        contents: payloads,
+        ..Default::default()
+    })
+}
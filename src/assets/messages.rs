@@ -0,0 +1,277 @@
+use crate::config::Config;
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The locale we fall back to if no preferred locale has a translation.
+const FALLBACK_LOCALE: &str = "en";
+
+/// Our built-in English strings, used if no catalog provides a string.
+/// Arguments are referenced by name within braces, such as `{organization}`.
+const ENGLISH: [(&str, &str); 7] = [
+    ("enrollment.display_name", "{organization} Enrollment"),
+    (
+        "enrollment.description",
+        "Install this profile to enroll into the MDM service for \"{organization}\".
+If you do not recognize this name, please remove this profile.",
+    ),
+    ("enrollment.scep.display_name", "SCEP Payload"),
+    (
+        "enrollment.mdm.display_name",
+        "Device Management for {organization}",
+    ),
+    ("trust.display_name", "Trust Profile for {organization}"),
+    (
+        "trust.description",
+        "Configures your device to securely connect to the MDM service for \"{organization}\".",
+    ),
+    (
+        "trust.root.display_name",
+        "Root Certificate for {organization}",
+    ),
+];
+
+#[derive(Clone, Default)]
+/// Translated strings, loaded from the `strings` directory within our assets directory.
+///
+/// Each locale has its own TOML file named after its language tag, such as `fr.toml`
+/// or `pt-BR.toml`, mapping string IDs (e.g. "trust.display_name") to their translation.
+pub struct MessageCatalog {
+    /// Strings keyed by their normalized locale, and then their ID.
+    locales: Arc<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl MessageCatalog {
+    /// Loads all catalogs within our assets directory.
+    /// Catalogs we're unable to parse are skipped.
+    pub fn load(config: &Config) -> Self {
+        let strings_dir = Path::new(&config.storage.assets_dir).join("strings");
+        let mut locales = BTreeMap::new();
+        let Ok(entries) = fs::read_dir(&strings_dir) else {
+            return MessageCatalog::default();
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("toml") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let strings = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| {
+                    toml::from_str::<BTreeMap<String, String>>(&contents).map_err(|e| e.to_string())
+                });
+            let strings = match strings {
+                Ok(strings) => strings,
+                Err(e) => {
+                    println!("unable to parse message catalog {}: {e}", path.display());
+                    continue;
+                }
+            };
+            locales.insert(normalize_locale(locale), strings);
+        }
+        MessageCatalog {
+            locales: Arc::new(locales),
+        }
+    }
+
+    /// Provides strings for the first of the given languages we have a catalog for,
+    /// in order of preference. Regional variants (e.g. "fr-CA") fall back to their
+    /// language ("fr"), and every language falls back to English.
+    pub fn localizer(&self, languages: &[String]) -> Localizer<'_> {
+        let mut candidates: Vec<String> = vec![];
+        for language in languages {
+            let language = normalize_locale(language);
+            // Progressively remove subtags, e.g. "zh-hant-tw" → "zh-hant" → "zh".
+            let mut locale = language.as_str();
+            loop {
+                if !candidates.iter().any(|candidate| candidate == locale) {
+                    candidates.push(locale.to_string());
+                }
+                match locale.rfind('-') {
+                    Some(index) => locale = &locale[..index],
+                    None => break,
+                }
+            }
+        }
+        // English is always available via our built-in strings,
+        // so any less preferred languages will never be used.
+        match candidates
+            .iter()
+            .position(|candidate| candidate == FALLBACK_LOCALE)
+        {
+            Some(position) => candidates.truncate(position + 1),
+            None => candidates.push(FALLBACK_LOCALE.to_string()),
+        }
+
+        let locales = candidates
+            .iter()
+            .filter_map(|locale| self.locales.get(locale))
+            .collect();
+        Localizer { locales }
+    }
+}
+
+/// Strings for a single request or device, in order of preference.
+pub struct Localizer<'a> {
+    locales: Vec<&'a BTreeMap<String, String>>,
+}
+
+impl Localizer<'_> {
+    /// The string with the given ID, with its arguments filled in.
+    /// If no catalog provides this string, our built-in English string is used.
+    /// Every ID we request must have a built-in string.
+    pub fn get(&self, id: &str, arguments: &[(&str, &str)]) -> String {
+        let translation = self
+            .locales
+            .iter()
+            .find_map(|strings| strings.get(id))
+            .map(String::as_str);
+        let built_in = ENGLISH
+            .iter()
+            .find(|(english_id, _)| *english_id == id)
+            .map(|(_, english)| *english);
+        let mut message = translation
+            .or(built_in)
+            .expect("messages should have a built-in English string")
+            .to_string();
+        for (name, value) in arguments {
+            message = message.replace(&format!("{{{name}}}"), value);
+        }
+        message
+    }
+}
+
+/// Language tags are compared irrespective of case, and with underscores as hyphens.
+/// For example, "en_US" and "en-us" are equivalent.
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_lowercase()
+}
+
+/// The languages a client prefers, per its Accept-Language header,
+/// ordered from most to least preferred.
+pub struct AcceptLanguage(pub Vec<String>);
+
+impl AcceptLanguage {
+    /// Parses a header such as "fr-CA, fr;q=0.9, en;q=0.8".
+    /// Wildcards and languages with a quality of zero are ignored.
+    pub fn parse(header: &str) -> Self {
+        let mut languages: Vec<(String, f32)> = header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parameters = entry.split(';');
+                let language = parameters.next()?.trim();
+                let quality = parameters
+                    .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .find_map(|quality| quality.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                if language.is_empty() || language == "*" || quality <= 0.0 {
+                    return None;
+                }
+                Some((language.to_string(), quality))
+            })
+            .collect();
+        // Sorting is stable, so languages of equal quality retain their order.
+        languages.sort_by(|left, right| right.1.total_cmp(&left.1));
+        AcceptLanguage(
+            languages
+                .into_iter()
+                .map(|(language, _)| language)
+                .collect(),
+        )
+    }
+
+    /// The client's most preferred language, if any.
+    pub fn preferred(&self) -> Option<&str> {
+        self.0.first().map(String::as_str)
+    }
+}
+
+impl<S> FromRequestParts<S> for AcceptLanguage
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let header = parts
+            .headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Ok(AcceptLanguage::parse(header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> MessageCatalog {
+        let strings = |display_name: &str| {
+            BTreeMap::from([("trust.display_name".to_string(), display_name.to_string())])
+        };
+        MessageCatalog {
+            locales: Arc::new(BTreeMap::from([
+                (
+                    "fr".to_string(),
+                    strings("Profil de confiance pour {organization}"),
+                ),
+                (
+                    "pt-br".to_string(),
+                    strings("Perfil de Confiança de {organization}"),
+                ),
+            ])),
+        }
+    }
+
+    fn languages(languages: &[&str]) -> Vec<String> {
+        languages
+            .iter()
+            .map(|language| language.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_accept_language_by_quality() {
+        let header = "de;q=0.5, fr-CA, *, en;q=0.8, es;q=0, it;q=0.8";
+        assert_eq!(AcceptLanguage::parse(header).0, ["fr-CA", "en", "it", "de"]);
+        assert_eq!(AcceptLanguage::parse("").preferred(), None);
+    }
+
+    #[test]
+    fn localizes_via_regional_and_language_fallback() {
+        let catalog = catalog();
+        let arguments = [("organization", "Contoso")];
+        let get = |preferred: &[&str]| {
+            catalog
+                .localizer(&languages(preferred))
+                .get("trust.display_name", &arguments)
+        };
+        assert_eq!(get(&["fr-CA"]), "Profil de confiance pour Contoso");
+        assert_eq!(get(&["pt_BR"]), "Perfil de Confiança de Contoso");
+        assert_eq!(get(&["de", "fr"]), "Profil de confiance pour Contoso");
+        // English is preferred over French, so French is never used.
+        assert_eq!(get(&["en-GB", "fr"]), "Trust Profile for Contoso");
+        assert_eq!(get(&["de"]), "Trust Profile for Contoso");
+        assert_eq!(get(&[]), "Trust Profile for Contoso");
+    }
+
+    #[test]
+    fn falls_back_to_english_for_untranslated_messages() {
+        let catalog = catalog();
+        let localizer = catalog.localizer(&languages(&["fr"]));
+        assert_eq!(
+            localizer.get("trust.root.display_name", &[("organization", "Contoso")]),
+            "Root Certificate for Contoso"
+        );
+    }
+}
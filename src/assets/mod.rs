@@ -0,0 +1,24 @@
+mod fonts;
+mod messages;
+mod web_clips;
+
+pub use fonts::*;
+pub use messages::*;
+pub use web_clips::*;
+
+use crate::config::Config;
+use std::fs;
+use std::path::{Component, Path};
+
+/// Reads a file at the given path, relative to our assets directory.
+/// Paths which would escape our assets directory are refused.
+pub fn read_asset(config: &Config, relative_path: &str) -> Option<Vec<u8>> {
+    let relative_path = Path::new(relative_path);
+    let is_contained = relative_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_contained {
+        return None;
+    }
+    fs::read(Path::new(&config.storage.assets_dir).join(relative_path)).ok()
+}
@@ -0,0 +1,63 @@
+use crate::config::Config;
+use crate::payloads::{BasePayload, Payload, Profile, WebClipPayload};
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+use super::read_asset;
+
+#[derive(Deserialize)]
+/// A web clip to create, with its icon read from our assets directory.
+pub struct WebClipAsset {
+    pub url: String,
+    pub label: String,
+    /// The path of this web clip's icon, relative to our assets directory.
+    pub icon: Option<String>,
+    pub full_screen: Option<bool>,
+    pub is_removable: Option<bool>,
+    pub target_application_bundle_identifier: Option<String>,
+}
+
+/// Creates a profile containing the given web clips.
+/// Returns the first icon not present within our assets directory as an error.
+pub fn web_clip_profile(
+    config: &Config,
+    web_clips: Vec<WebClipAsset>,
+) -> Result<Profile<Payload>, String> {
+    let service_config = &config.service;
+
+    let mut payloads = vec![];
+    for (index, web_clip) in web_clips.into_iter().enumerate() {
+        let icon = match web_clip.icon {
+            Some(icon_path) => match read_asset(config, &icon_path) {
+                Some(contents) => Some(ByteBuf::from(contents)),
+                None => return Err(icon_path),
+            },
+            None => None,
+        };
+
+        payloads.push(Payload::WebClip(WebClipPayload {
+            base: BasePayload {
+                identifier: format!("{}.web-clips.{index}", service_config.base_identifier),
+                ..WebClipPayload::default().base
+            },
+            url: web_clip.url,
+            label: web_clip.label,
+            icon,
+            full_screen: web_clip.full_screen,
+            is_removable: web_clip.is_removable,
+            target_application_bundle_identifier: web_clip.target_application_bundle_identifier,
+            ..Default::default()
+        }));
+    }
+
+    Ok(Profile {
+        base: BasePayload {
+            identifier: format!("{}.web-clips", service_config.base_identifier),
+            display_name: Some("Web Clips".to_string()),
+            organization: Some(service_config.organization_name.clone()),
+            ..Default::default()
+        },
+        contents: payloads,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Apple's extensions within attestation leaf certificates.
+/// https://developer.apple.com/documentation/devicemanagement/devicedeviceattestation
+const OID_SERIAL_NUMBER: &str = "1.2.840.113635.100.8.9.1";
+const OID_UDID: &str = "1.2.840.113635.100.8.9.2";
+const OID_FRESHNESS_CODE: &str = "1.2.840.113635.100.8.11.1";
+
+#[derive(Deserialize)]
+/// A WebAuthn-style attestation object, as sent within device-attest-01 challenges.
+/// https://datatracker.ietf.org/doc/html/draft-ietf-acme-device-attest
+struct AttestationObject {
+    fmt: String,
+    #[serde(rename = "attStmt")]
+    att_stmt: AttestationStatement,
+}
+
+#[derive(Deserialize)]
+struct AttestationStatement {
+    /// The attestation certificate, followed by its intermediates.
+    x5c: Vec<ByteBuf>,
+}
+
+/// The properties of a device Apple has attested to.
+pub struct DeviceAttestation {
+    pub serial_number: Option<String>,
+    pub udid: Option<String>,
+    /// The attested key, as the contents of its subjectPublicKey.
+    pub public_key: Vec<u8>,
+}
+
+/// Verifies a device-attest-01 attestation object in the "apple" format.
+///
+/// Its certificate chain must be issued by the given root, and its freshness code
+/// must be the SHA-256 digest of the challenge token. Verifying which device
+/// this attestation is for is left to the caller.
+pub fn verify_device_attestation(
+    attestation_root: &[u8],
+    attestation_object: &[u8],
+    challenge_token: &str,
+) -> Result<DeviceAttestation, String> {
+    let attestation: AttestationObject = ciborium::from_reader(attestation_object)
+        .map_err(|err| format!("unable to parse attestation object: {err}"))?;
+    if attestation.fmt != "apple" {
+        return Err(format!(
+            "unsupported attestation format {}",
+            attestation.fmt
+        ));
+    }
+
+    // Parse our chain, ending with our trusted root.
+    let mut chain = vec![];
+    for contents in attestation
+        .att_stmt
+        .x5c
+        .iter()
+        .map(|cert| cert.as_slice())
+        .chain([attestation_root])
+    {
+        let (_, certificate) = X509Certificate::from_der(contents)
+            .map_err(|_| "unable to parse attestation certificate".to_string())?;
+        chain.push(certificate);
+    }
+    if chain.len() < 2 {
+        return Err("attestation statement has no certificates".to_string());
+    }
+
+    // Every certificate must be issued by its successor,
+    // which itself must be a CA permitted to sign certificates.
+    for pair in chain.windows(2) {
+        let (certificate, issuer) = (&pair[0], &pair[1]);
+        if !certificate.validity().is_valid() {
+            return Err("attestation certificate has expired".to_string());
+        }
+        if !may_issue_certificates(issuer) || certificate.issuer() != issuer.subject() {
+            return Err("attestation certificate chain is invalid".to_string());
+        }
+        certificate
+            .verify_signature(Some(issuer.public_key()))
+            .map_err(|_| "attestation certificate chain is invalid".to_string())?;
+    }
+    // Our root is not checked above, as it issues itself.
+    let root = &chain[chain.len() - 1];
+    if !root.validity().is_valid() {
+        return Err("attestation root has expired".to_string());
+    }
+
+    let leaf = &chain[0];
+    let mut attestation = DeviceAttestation {
+        serial_number: None,
+        udid: None,
+        public_key: leaf.public_key().subject_public_key.data.to_vec(),
+    };
+    let mut freshness_code = None;
+    for extension in leaf.extensions() {
+        // Apple's extensions are raw values, not further DER-encoded.
+        let value = extension.value;
+        match extension.oid.to_id_string().as_str() {
+            OID_SERIAL_NUMBER => {
+                attestation.serial_number = Some(String::from_utf8_lossy(value).to_string())
+            }
+            OID_UDID => attestation.udid = Some(String::from_utf8_lossy(value).to_string()),
+            OID_FRESHNESS_CODE => freshness_code = Some(value),
+            _ => {}
+        }
+    }
+
+    let expected_code = Sha256::digest(challenge_token.as_bytes());
+    if freshness_code != Some(expected_code.as_slice()) {
+        return Err("attestation freshness code does not match challenge".to_string());
+    }
+
+    Ok(attestation)
+}
+
+/// Whether this certificate is a CA whose key may be used to sign certificates.
+fn may_issue_certificates(certificate: &X509Certificate) -> bool {
+    if !certificate.is_ca() {
+        return false;
+    }
+    // Without key usage, its key is not restricted.
+    match certificate.key_usage() {
+        Ok(Some(key_usage)) => key_usage.value.key_cert_sign(),
+        Ok(None) => true,
+        Err(_) => false,
+    }
+}
@@ -3,12 +3,16 @@ use crate::AppState;
 use axum::extract::FromRef;
 use cms::{
     cert::CertificateChoices,
+    content_info::ContentInfo,
     signed_data::{SignedData, SignerIdentifier},
 };
 use der::{
-    DecodePem, Encode,
+    Decode, DecodePem, Encode,
     asn1::OctetStringRef,
-    oid::{ObjectIdentifier, db::rfc5912},
+    oid::{
+        ObjectIdentifier,
+        db::{rfc5911, rfc5912},
+    },
     referenced::OwnedToRef,
 };
 use rsa::{
@@ -17,7 +21,8 @@ use rsa::{
     signature::Verifier,
 };
 use sha1::Sha1;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
 use x509_cert::Certificate;
 
 /// Apple writes:
@@ -84,17 +89,20 @@ fn verify_signature(
                 .verify(message, &given_signature)
                 .ok()?
         }
-        _ => panic!(
-            "Unknown signature algorithm encountered: {}!",
-            signature.algorithm
-        ),
+        _ => {
+            println!(
+                "Unknown signature algorithm encountered: {}!",
+                signature.algorithm
+            );
+            return None;
+        }
     }
 
     Some(())
 }
 
 /// Extracts the signing certificate from a CMS envelope.
-fn extract_signing_cert(envelope: &SignedData) -> Option<Certificate> {
+pub fn extract_signing_cert(envelope: &SignedData) -> Option<Certificate> {
     // Assume we only have one signer.
     let signer_info = envelope.signer_infos.0.get(0)?;
 
@@ -147,10 +155,16 @@ fn verify_cert_signature(verifying_cert: &Certificate, potential_cert: &Certific
     Some(())
 }
 
-/// Attempts to verify an X.509 certificate against our CMS SignedInfo.
-/// It returns the signee and contents within the envelope.
-fn verify_signing_cert(verifying_cert: Certificate, envelope: &SignedData) -> Option<Vec<u8>> {
-    // Before anything else, obtain our envelope's contents.
+/// Whether the current time is within this certificate's validity period.
+fn is_within_validity(certificate: &Certificate) -> bool {
+    let validity = &certificate.tbs_certificate.validity;
+    let current_time = SystemTime::now();
+    validity.not_before.to_system_time() <= current_time
+        && current_time <= validity.not_after.to_system_time()
+}
+
+/// Obtains the contents encapsulated within our CMS envelope.
+pub fn encapsulated_contents(envelope: &SignedData) -> Option<Vec<u8>> {
     let encap_contents = envelope
         .encap_content_info
         .clone()
@@ -159,9 +173,12 @@ fn verify_signing_cert(verifying_cert: Certificate, envelope: &SignedData) -> Op
         .ok()?
         .as_bytes()
         .to_vec();
-    // We're going to assume we only have one signer.
-    let signer_info = &envelope.signer_infos.0.get(0)?;
+    Some(encap_contents)
+}
 
+/// Attempts to verify an X.509 certificate against our CMS SignedInfo.
+/// It returns the signee and contents within the envelope.
+fn verify_signing_cert(verifying_cert: Certificate, envelope: &SignedData) -> Option<Vec<u8>> {
     // Obtain the signer of this envelope via our SignerInfo.
     let signing_certificate = extract_signing_cert(envelope)?;
 
@@ -170,7 +187,23 @@ fn verify_signing_cert(verifying_cert: Certificate, envelope: &SignedData) -> Op
     // (Here, our potential certificate is the extracted signing certificate.)
     verify_cert_signature(&verifying_cert, &signing_certificate)?;
 
-    // If successful, obtain the public key of the signing certificate
+    verify_envelope_signature(&signing_certificate, envelope)
+}
+
+/// Verifies that our envelope was signed by the given signing certificate,
+/// returning the contents within the envelope.
+///
+/// This does not consider who issued the signing certificate.
+pub fn verify_envelope_signature(
+    signing_certificate: &Certificate,
+    envelope: &SignedData,
+) -> Option<Vec<u8>> {
+    // Before anything else, obtain our envelope's contents.
+    let encap_contents = encapsulated_contents(envelope)?;
+    // We're going to assume we only have one signer.
+    let signer_info = &envelope.signer_infos.0.get(0)?;
+
+    // Obtain the public key of the signing certificate
     // to use whilst verifying our envelope.
     let signing_subject = signing_certificate
         .tbs_certificate
@@ -195,9 +228,19 @@ fn verify_signing_cert(verifying_cert: Certificate, envelope: &SignedData) -> Op
         }
     };
 
+    // Some signers (such as OpenSSL) only specify rsaEncryption,
+    // leaving the digest algorithm to be specified separately.
+    let algorithm = match (
+        signer_info.signature_algorithm.oid,
+        signer_info.digest_alg.oid,
+    ) {
+        (rfc5912::RSA_ENCRYPTION, rfc5912::ID_SHA_1) => rfc5912::SHA_1_WITH_RSA_ENCRYPTION,
+        (rfc5912::RSA_ENCRYPTION, rfc5912::ID_SHA_256) => rfc5912::SHA_256_WITH_RSA_ENCRYPTION,
+        (algorithm, _) => algorithm,
+    };
     let envelope_metadata = SignatureMetadata {
         contents: signer_info.signature.as_bytes().to_vec(),
-        algorithm: signer_info.signature_algorithm.oid,
+        algorithm,
     };
     verify_signature(signing_public_key, envelope_metadata, &digest_contents)?;
 
@@ -232,3 +275,70 @@ where
     // Beyond that, we have no idea who the signee is here.
     None
 }
+
+/// Verifies a detached CMS signature over the given contents.
+///
+/// Managed devices sign every check-in and command response with their
+/// identity certificate, providing the signature via the Mdm-Signature header.
+/// If this identity was issued by our device CA, it is returned.
+pub fn verify_detached_signature<S>(
+    state: &S,
+    signature: &[u8],
+    contents: &[u8],
+) -> Option<Certificate>
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    let state = AppState::from_ref(state);
+    let envelope = ContentInfo::from_der(signature)
+        .ok()?
+        .content
+        .decode_as::<SignedData>()
+        .ok()?;
+    let signer_info = envelope.signer_infos.0.get(0)?;
+
+    // Ensure the device's identity was issued by our device CA, and remains valid.
+    let signing_certificate = extract_signing_cert(&envelope)?;
+    verify_cert_signature(&state.certificates.device_ca_cert, &signing_certificate)?;
+    if !is_within_validity(&signing_certificate) {
+        return None;
+    }
+
+    // As our contents are not within the envelope, we must have signed
+    // attributes present: they contain the digest of our contents.
+    let signed_attributes = signer_info.signed_attrs.as_ref()?;
+    let message_digest = signed_attributes
+        .iter()
+        .find(|attribute| attribute.oid == rfc5911::ID_MESSAGE_DIGEST)?
+        .values
+        .get(0)?
+        .decode_as::<OctetStringRef>()
+        .ok()?;
+    let contents_digest = match signer_info.digest_alg.oid {
+        rfc5912::ID_SHA_1 => Sha1::digest(contents).to_vec(),
+        rfc5912::ID_SHA_256 => Sha256::digest(contents).to_vec(),
+        _ => return None,
+    };
+    if message_digest.as_bytes() != contents_digest.as_slice() {
+        return None;
+    }
+
+    // Lastly, verify the signature over our signed attributes.
+    let signing_subject = signing_certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .owned_to_ref();
+    let signing_public_key = RsaPublicKey::try_from(signing_subject).ok()?;
+    let envelope_metadata = SignatureMetadata {
+        contents: signer_info.signature.as_bytes().to_vec(),
+        algorithm: signer_info.signature_algorithm.oid,
+    };
+    verify_signature(
+        signing_public_key,
+        envelope_metadata,
+        &signed_attributes.to_der().ok()?,
+    )?;
+
+    Some(signing_certificate)
+}
@@ -1,3 +1,4 @@
+use crate::payloads::version_profile;
 use crate::plist::Plist;
 use crate::{app_state::AppState, config::Config};
 use axum::{
@@ -14,23 +15,36 @@ use der::{
     asn1::OctetStringRef,
     oid::db::{rfc5911, rfc5912},
 };
-use rsa::{RsaPrivateKey, pkcs1v15::SigningKey, pkcs8::DecodePrivateKey};
+use rcgen::{
+    CertificateParams, CertificateSigningRequestParams, DistinguishedName, DnType,
+    ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair, KeyUsagePurpose, SerialNumber,
+};
+use rsa::{
+    RsaPrivateKey,
+    pkcs1v15::SigningKey,
+    pkcs8::{DecodePrivateKey, EncodePrivateKey},
+};
 use serde::Serialize;
 use sha1::Sha1;
 use std::fs;
 use std::path::Path;
 use x509_cert::{Certificate, spki::AlgorithmIdentifierOwned};
 
-use super::generator;
+use super::generator::{self, CertificateParamsHelper};
 
 /// Manages certificate generation and signing.
 #[derive(Clone, Debug)]
 pub struct Certificates {
     pub root_ca_cert: Certificate,
     pub device_ca_cert: Certificate,
-    pub device_ca_key: RsaPrivateKey,
+    pub acme_ca_cert: Certificate,
+    pub acme_ca_key: RsaPrivateKey,
     pub ssl_cert: Certificate,
     pub ssl_key: RsaPrivateKey,
+    pub escrow_cert: Certificate,
+    pub escrow_key: RsaPrivateKey,
+    /// The root device attestations must chain to, if ACME is configured.
+    pub apple_attestation_root: Option<Certificate>,
 }
 
 impl Certificates {
@@ -38,22 +52,45 @@ impl Certificates {
         let root_ca_cert_path = config.certificate_path("root_ca_cert.pem");
         let root_ca_key_path = config.certificate_path("root_ca_key.pem");
         let device_ca_cert_path = config.certificate_path("device_ca_cert.pem");
-        let device_ca_key_path = config.certificate_path("device_ca_key.pem");
+        let acme_ca_cert_path = config.certificate_path("acme_ca_cert.pem");
+        let acme_ca_key_path = config.certificate_path("acme_ca_key.pem");
         let ssl_cert_path = config.certificate_path("ssl_cert.pem");
         let ssl_key_path = config.certificate_path("ssl_key.pem");
+        let escrow_cert_path = config.certificate_path("escrow_cert.pem");
+        let escrow_key_path = config.certificate_path("escrow_key.pem");
 
-        if !root_ca_key_path.exists() || !root_ca_cert_path.exists() {
+        let regenerate_ca = !root_ca_key_path.exists() || !root_ca_cert_path.exists();
+        if regenerate_ca {
             // Regenerate all of our CA certificates.
             generator::issue_ca_certificates(config);
         }
 
+        // Our ACME CA is issued separately, as it may be introduced after our root CA.
+        if regenerate_ca || !acme_ca_key_path.exists() || !acme_ca_cert_path.exists() {
+            generator::issue_acme_ca_certificate(config);
+        }
+
+        // Our escrow certificate is independent of our CA certificates.
+        // Note that regenerating it will prevent decrypting anything escrowed prior.
+        if !escrow_key_path.exists() || !escrow_cert_path.exists() {
+            generator::issue_escrow_certificate(config);
+        }
+
         // Load our certificates, and then we're all set!
         Certificates {
             root_ca_cert: read_cert_pem(&root_ca_cert_path),
             device_ca_cert: read_cert_pem(&device_ca_cert_path),
-            device_ca_key: read_key_pem(&device_ca_key_path),
+            acme_ca_cert: read_cert_pem(&acme_ca_cert_path),
+            acme_ca_key: read_key_pem(&acme_ca_key_path),
             ssl_cert: read_cert_pem(&ssl_cert_path),
             ssl_key: read_key_pem(&ssl_key_path),
+            escrow_cert: read_cert_pem(&escrow_cert_path),
+            escrow_key: read_key_pem(&escrow_key_path),
+            apple_attestation_root: config
+                .acme
+                .apple_attestation_root_path
+                .as_ref()
+                .map(|root_path| read_cert_pem(Path::new(root_path))),
         }
     }
 
@@ -110,9 +147,56 @@ impl Certificates {
             .expect("should be able to convert CMS container to DER form")
     }
 
+    /// Issues a client certificate from our ACME CA for the given request.
+    /// As it is not issued by our device CA, it cannot be used to authenticate via MDM.
+    ///
+    /// Only the request's key is certified. Its subject is replaced by the given
+    /// (attested) device identifier, and any requested alternative names
+    /// or extensions are discarded.
+    pub fn issue_acme_certificate(
+        &self,
+        mut request: CertificateSigningRequestParams,
+        device_identifier: &str,
+        validity_days: i64,
+    ) -> Result<rcgen::Certificate, rcgen::Error> {
+        let ca_key = self
+            .acme_ca_key
+            .to_pkcs8_der()
+            .expect("should be able to encode ACME CA key");
+        let ca_key = KeyPair::try_from(ca_key.as_bytes())?;
+        let ca_cert = self
+            .acme_ca_cert
+            .to_der()
+            .expect("should be able to encode ACME CA certificate");
+        let issuer = Issuer::from_ca_cert_der(&ca_cert.into(), ca_key)?;
+
+        // Serial numbers must be positive.
+        let mut serial_number = rand::random::<[u8; 16]>();
+        serial_number[0] &= 0x7f;
+
+        let mut subject = DistinguishedName::new();
+        subject.push(DnType::CommonName, device_identifier);
+        request.params = CertificateParams::default();
+        let params = &mut request.params;
+        params.distinguished_name = subject;
+        params.set_days_valid(validity_days);
+        params.serial_number = Some(SerialNumber::from(serial_number.to_vec()));
+        params.is_ca = IsCa::ExplicitNoCa;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        params.use_authority_key_identifier_extension = true;
+        request.signed_by(&issuer)
+    }
+
+    /// Serializes and signs the given profile, i.e. for use within InstallProfile.
+    pub fn signed_profile_data<T: Serialize>(&self, profile: T) -> Result<Vec<u8>, plist::Error> {
+        let profile_xml = Plist(profile).to_xml()?;
+        Ok(self.sign_contents(profile_xml))
+    }
+
     pub fn sign_profile<T: Serialize>(&self, profile: T) -> Response {
-        // Let's get our payload contents.
-        let profile_xml = match Plist(profile).to_xml() {
+        // Let's get our signed payload contents.
+        let signed_profile = match self.signed_profile_data(profile) {
             Ok(body) => body,
             Err(err) => {
                 // We should not expose this exact error for safety reasons.
@@ -122,9 +206,6 @@ impl Certificates {
             }
         };
 
-        // We need to sign this profile.
-        let signed_profile = self.sign_contents(profile_xml);
-
         let headers = [(header::CONTENT_TYPE, "application/x-apple-aspen-config")];
         (headers, signed_profile).into_response()
     }
@@ -132,8 +213,16 @@ impl Certificates {
 
 impl AppState {
     // Signs a profile with the current SSL certificate.
+    // Its payload UUIDs are derived from its contents, so unchanged profiles are identical.
     pub fn serve_profile<T: Serialize>(&self, profile: T) -> Response {
-        self.certificates.sign_profile(profile)
+        let namespace = self.config.service.uuid_namespace();
+        match version_profile(&profile, &namespace) {
+            Ok(versioned) => self.certificates.sign_profile(versioned.contents),
+            Err(err) => {
+                println!("error within plist serialization: {err}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+            }
+        }
     }
 }
 
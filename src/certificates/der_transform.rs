@@ -79,10 +79,7 @@ fn encode_as_der(ber_contents: Vec<u8>) -> Option<Vec<u8>> {
     // inner string is at an offset 50 of bytes.
     // TODO(spotlightishere): We perform zero sanity checks.
     const STRING_EXPECTED_TAG: [u8; 2] = [0x04, 0x82];
-    let string_read_tag = [
-        ber_contents[INNER_STRING_OFFSET],
-        ber_contents[INNER_STRING_OFFSET + 1],
-    ];
+    let string_read_tag = ber_contents.get(INNER_STRING_OFFSET..INNER_STRING_OFFSET + 2)?;
 
     if string_read_tag != STRING_EXPECTED_TAG {
         // This likely means that we do not need to fix up
@@ -223,14 +220,10 @@ pub fn parse_der(ber_contents: Vec<u8>) -> Option<SignedData> {
 
     // We're done hacking together a fully DER-encoded object.
     // We can finally use the Rust cms crate to extract its contents.
-    let parsed_content =
-        ContentInfo::from_der(&result).expect("should be able to parse body as ContentInfo");
+    let parsed_content = ContentInfo::from_der(&result).ok()?;
 
     // Our contents should be pkcs7-data.
-    let envelope = parsed_content
-        .content
-        .decode_as::<SignedData>()
-        .expect("should be able to parse contents as SignedData");
+    let envelope = parsed_content.content.decode_as::<SignedData>().ok()?;
 
     Some(envelope)
 }
@@ -0,0 +1,99 @@
+use aes::{Aes128, Aes192, Aes256};
+use cbc::{
+    Decryptor,
+    cipher::{BlockCipher, BlockDecryptMut, KeyInit, KeyIvInit, block_padding::Pkcs7},
+};
+use cms::{
+    content_info::ContentInfo,
+    enveloped_data::{EnvelopedData, RecipientIdentifier, RecipientInfo},
+};
+use der::{Decode, asn1::OctetStringRef, oid::db::rfc5911};
+use des::TdesEde3;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use x509_cert::Certificate;
+
+use super::Certificates;
+
+impl Certificates {
+    /// Decrypts a CMS envelope encrypted against our escrow certificate,
+    /// such as a FileVault personal recovery key.
+    pub fn decrypt_escrowed(&self, envelope: &[u8]) -> Option<Vec<u8>> {
+        decrypt_enveloped_data(&self.escrow_cert, &self.escrow_key, envelope)
+    }
+}
+
+/// Decrypts the contents of a CMS EnvelopedData structure, in DER form,
+/// using the given certificate and its private key.
+///
+/// Apple devices encrypt their contents with a single key transport recipient,
+/// using RSA PKCS#1 v1.5 alongside AES or Triple DES in CBC mode.
+/// We do not support any other recipient types or algorithms.
+pub fn decrypt_enveloped_data(
+    certificate: &Certificate,
+    private_key: &RsaPrivateKey,
+    envelope: &[u8],
+) -> Option<Vec<u8>> {
+    let enveloped_data = ContentInfo::from_der(envelope)
+        .ok()?
+        .content
+        .decode_as::<EnvelopedData>()
+        .ok()?;
+
+    // Find the recipient matching our certificate.
+    let tbs_certificate = &certificate.tbs_certificate;
+    let recipient = enveloped_data
+        .recip_infos
+        .0
+        .iter()
+        .find_map(|recipient_info| {
+            let RecipientInfo::Ktri(recipient) = recipient_info else {
+                return None;
+            };
+            let RecipientIdentifier::IssuerAndSerialNumber(issuer) = &recipient.rid else {
+                return None;
+            };
+
+            if issuer.issuer == tbs_certificate.issuer
+                && issuer.serial_number == tbs_certificate.serial_number
+            {
+                Some(recipient)
+            } else {
+                None
+            }
+        })?;
+
+    // With that, we can decrypt the content encryption key.
+    let content_key = private_key
+        .decrypt(Pkcs1v15Encrypt, recipient.enc_key.as_bytes())
+        .ok()?;
+
+    // Our initialization vector is the sole parameter to our content encryption algorithm.
+    let encrypted_content = &enveloped_data.encrypted_content;
+    let iv = encrypted_content
+        .content_enc_alg
+        .parameters
+        .as_ref()?
+        .decode_as::<OctetStringRef>()
+        .ok()?
+        .as_bytes();
+    let ciphertext = encrypted_content.encrypted_content.as_ref()?.as_bytes();
+
+    match encrypted_content.content_enc_alg.oid {
+        rfc5911::ID_AES_128_CBC => decrypt_cbc::<Aes128>(&content_key, iv, ciphertext),
+        rfc5911::ID_AES_192_CBC => decrypt_cbc::<Aes192>(&content_key, iv, ciphertext),
+        rfc5911::ID_AES_256_CBC => decrypt_cbc::<Aes256>(&content_key, iv, ciphertext),
+        rfc5911::DES_EDE_3_CBC => decrypt_cbc::<TdesEde3>(&content_key, iv, ciphertext),
+        _ => None,
+    }
+}
+
+/// Decrypts the given ciphertext in CBC mode, removing its PKCS#7 padding.
+fn decrypt_cbc<C>(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockDecryptMut + KeyInit,
+{
+    Decryptor::<C>::new_from_slices(key, iv)
+        .ok()?
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .ok()
+}
@@ -61,6 +61,35 @@ fn create_device_cert_params(config: &Config) -> CertificateParams {
     cert_params
 }
 
+/// Generates CA parameters suitable for signing certificates requested via ACME.
+///
+/// This is separate from our device CA, as only identities issued by our device CA
+/// may authenticate as a device via MDM.
+fn create_acme_cert_params(config: &Config) -> CertificateParams {
+    let mut cert_params = CertificateParams::default();
+
+    // Similar to our device CA, a validity of 10 years suits our needs.
+    cert_params.set_days_valid(3650);
+
+    let mut cert_name = DistinguishedName::new();
+    cert_name.push(
+        DnType::CommonName,
+        format!("{} ACME CA", config.service.organization_name),
+    );
+    cert_name.push(DnType::OrganizationName, &config.service.organization_name);
+    cert_params.distinguished_name = cert_name;
+
+    // We do not want any intermediate certificates underneath us.
+    cert_params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    cert_params.key_usages = vec![
+        KeyUsagePurpose::DigitalSignature,
+        KeyUsagePurpose::KeyCertSign,
+        KeyUsagePurpose::CrlSign,
+    ];
+    cert_params.use_authority_key_identifier_extension = true;
+    cert_params
+}
+
 /// Generates a general SSL certificate for the configured base domain.
 fn create_ssl_cert_params(config: &Config) -> CertificateParams {
     // We should only have our base domain as a Subject Alternative Name.
@@ -90,6 +119,31 @@ fn create_ssl_cert_params(config: &Config) -> CertificateParams {
     cert_params
 }
 
+/// Generates a self-signed certificate for devices to encrypt escrowed secrets against,
+/// such as FileVault personal recovery keys.
+fn create_escrow_cert_params(config: &Config) -> CertificateParams {
+    let mut cert_params = CertificateParams::default();
+
+    // This is never presented beyond our own devices, so a validity of 10 years suits our needs.
+    cert_params.set_days_valid(3650);
+
+    let mut cert_name = DistinguishedName::new();
+    cert_name.push(
+        DnType::CommonName,
+        format!("{} Escrow Encryption", config.service.organization_name),
+    );
+    cert_name.push(DnType::OrganizationName, &config.service.organization_name);
+    cert_params.distinguished_name = cert_name;
+
+    // This certificate is only used to encrypt keys for us.
+    cert_params.is_ca = IsCa::ExplicitNoCa;
+    cert_params.key_usages = vec![
+        KeyUsagePurpose::KeyEncipherment,
+        KeyUsagePurpose::DataEncipherment,
+    ];
+    cert_params
+}
+
 pub fn issue_ca_certificates(config: &Config) {
     // TODO(spotlightishere): All of these paths are within Certificates::load_certs as well.
     // Can we somehow consolidate the two?
@@ -140,6 +194,43 @@ pub fn issue_ca_certificates(config: &Config) {
     write_key_pem(&ssl_key, &ssl_key_path);
 }
 
+/// Issues the CA for certificates requested via ACME, from our existing root CA.
+pub fn issue_acme_ca_certificate(config: &Config) {
+    let root_ca_cert_path = config.certificate_path("root_ca_cert.pem");
+    let root_ca_key_path = config.certificate_path("root_ca_key.pem");
+    let acme_ca_cert_path = config.certificate_path("acme_ca_cert.pem");
+    let acme_ca_key_path = config.certificate_path("acme_ca_key.pem");
+
+    let root_ca_cert =
+        fs::read_to_string(&root_ca_cert_path).expect("should be able to read root CA certificate");
+    let root_ca_key =
+        fs::read_to_string(&root_ca_key_path).expect("should be able to read root CA key");
+    let root_ca_key = KeyPair::from_pem(&root_ca_key).expect("should be able to parse root CA key");
+    let root_issuer = Issuer::from_ca_cert_pem(&root_ca_cert, root_ca_key)
+        .expect("should be able to parse root CA certificate");
+
+    let acme_ca_key = create_rsa_keypair();
+    let acme_ca_cert = create_acme_cert_params(config)
+        .signed_by(&acme_ca_key, &root_issuer)
+        .expect("should be able to issue ACME CA certificate");
+    write_ca_pem(acme_ca_cert, &acme_ca_cert_path);
+    write_key_pem(&acme_ca_key, &acme_ca_key_path);
+}
+
+/// Issues our escrow encryption certificate.
+/// Unlike our CA certificates, this is self-signed.
+pub fn issue_escrow_certificate(config: &Config) {
+    let escrow_cert_path = config.certificate_path("escrow_cert.pem");
+    let escrow_key_path = config.certificate_path("escrow_key.pem");
+
+    let escrow_key = create_rsa_keypair();
+    let escrow_cert = create_escrow_cert_params(config)
+        .self_signed(&escrow_key)
+        .expect("should be able to issue escrow certificate");
+    write_ca_pem(escrow_cert, &escrow_cert_path);
+    write_key_pem(&escrow_key, &escrow_key_path);
+}
+
 /// Serializes this certificate to the given path in PEM format.
 pub fn write_ca_pem(ca: rcgen::Certificate, key_path: &Path) {
     let cert_contents = ca.pem();
@@ -0,0 +1,59 @@
+use super::cert_verify::verify_detached_signature;
+use crate::app_state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{FromRef, FromRequest, Request},
+    http::StatusCode,
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use der::Encode;
+use sha2::{Digest, Sha256};
+
+/// A message sent by an enrolled device to our check-in or command endpoints.
+///
+/// Its contents are signed by the identity certificate the device obtained
+/// via SCEP, as specified within the Mdm-Signature header.
+/// This requires SignMessage to be set within the MDM payload.
+pub struct MdmRequest {
+    /// The SHA-256 fingerprint of the device's identity certificate, in hexadecimal form.
+    pub fingerprint: String,
+    pub contents: Vec<u8>,
+}
+
+impl<S> FromRequest<S> for MdmRequest
+where
+    Bytes: FromRequest<S>,
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // Our signature is a base64-encoded, detached CMS signature.
+        let Some(signature_header) = req.headers().get("Mdm-Signature") else {
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+        let Ok(signature) = BASE64_STANDARD.decode(signature_header.as_bytes()) else {
+            return Err(StatusCode::BAD_REQUEST);
+        };
+
+        let Ok(body_bytes) = Bytes::from_request(req, state).await else {
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        let contents = body_bytes.to_vec();
+
+        // Similar to Pkcs7Body, do not hint at why verification failed.
+        let Some(certificate) = verify_detached_signature(state, &signature, &contents) else {
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+        let Ok(certificate_der) = certificate.to_der() else {
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+        let fingerprint = hex::encode(Sha256::digest(certificate_der));
+
+        Ok(Self {
+            fingerprint,
+            contents,
+        })
+    }
+}
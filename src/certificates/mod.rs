@@ -1,8 +1,15 @@
+mod attestation;
 mod cert_verify;
 mod certs;
 mod der_transform;
+mod enveloped_data;
 mod generator;
+mod mdm_signature;
 mod pkcs7_body;
+mod signed_profile;
 
+pub use attestation::verify_device_attestation;
 pub use certs::Certificates;
+pub use mdm_signature::MdmRequest;
 pub use pkcs7_body::{Pkcs7Body, Pkcs7Signer};
+pub use signed_profile::{ProfileSigner, open_signed_profile};
@@ -0,0 +1,43 @@
+use super::cert_verify::{encapsulated_contents, extract_signing_cert, verify_envelope_signature};
+use super::der_transform::parse_der;
+use x509_cert::Certificate;
+
+/// The signer of a profile created outside of this server.
+pub struct ProfileSigner {
+    pub certificate: Certificate,
+    /// Whether the profile's signature is valid for this certificate.
+    /// Its issuer is not considered, as profiles may be signed by anyone.
+    pub verified: bool,
+}
+
+impl ProfileSigner {
+    /// Describes this signer, such as "CN=Example (issued by CN=Example CA, verified)".
+    pub fn describe(&self) -> String {
+        let tbs_certificate = &self.certificate.tbs_certificate;
+        let verification = if self.verified {
+            "verified"
+        } else {
+            "unable to verify signature"
+        };
+        format!(
+            "{} (issued by {}, {verification})",
+            tbs_certificate.subject, tbs_certificate.issuer
+        )
+    }
+}
+
+/// Extracts the contents of a signed profile, alongside its signer.
+///
+/// Profiles signed by other tools (such as Apple Configurator) are DER-encoded
+/// CMS envelopes, whose contents are the profile's plist.
+/// Returns None if the given contents are not a CMS envelope.
+pub fn open_signed_profile(contents: Vec<u8>) -> Option<(Vec<u8>, Option<ProfileSigner>)> {
+    let envelope = parse_der(contents)?;
+    let profile_contents = encapsulated_contents(&envelope)?;
+
+    let signer = extract_signing_cert(&envelope).map(|certificate| ProfileSigner {
+        verified: verify_envelope_signature(&certificate, &envelope).is_some(),
+        certificate,
+    });
+    Some((profile_contents, signer))
+}
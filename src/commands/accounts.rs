@@ -0,0 +1,73 @@
+use diesel::SqliteConnection;
+use optional_value::payload;
+
+use crate::app_state::AppState;
+use crate::database::QueuedCommand;
+use crate::escrow;
+
+#[payload]
+/// Configures accounts created by Setup Assistant on Macs enrolled via Automated Device Enrollment.
+/// This is only accepted while the device is awaiting configuration.
+/// https://developer.apple.com/documentation/devicemanagement/accountconfigurationcommand/command
+pub struct AccountConfiguration {
+    #[serde(rename = "AutoSetupAdminAccounts")]
+    /// Administrator accounts to create. Only one is currently supported by macOS.
+    pub auto_setup_admin_accounts: Option<Vec<AutoSetupAdminAccount>>,
+    #[serde(rename = "SkipPrimarySetupAccountCreation")]
+    /// Whether to skip creating the user's own account.
+    pub skip_primary_setup_account_creation: Option<bool>,
+    #[serde(rename = "SetPrimarySetupAccountAsRegularUser")]
+    /// Whether the user's own account should be a standard (non-admin) account.
+    pub set_primary_setup_account_as_regular_user: Option<bool>,
+}
+
+#[payload]
+/// https://developer.apple.com/documentation/devicemanagement/accountconfigurationcommand/command/autosetupadminaccountsitem
+pub struct AutoSetupAdminAccount {
+    #[serde(rename = "shortName")]
+    pub short_name: String,
+    #[serde(rename = "fullName")]
+    pub full_name: Option<String>,
+    #[serde(rename = "passwordHash", with = "serde_bytes")]
+    /// A property list containing a SALTED-SHA512-PBKDF2 dictionary.
+    pub password_hash: Vec<u8>,
+    #[serde(rename = "hidden")]
+    /// Whether to hide this account within the login window and Users & Groups.
+    pub hidden: Option<bool>,
+}
+
+#[payload]
+/// Changes the password of the administrator account created via AccountConfiguration.
+/// https://developer.apple.com/documentation/devicemanagement/setautoadminpasswordcommand/command
+pub struct SetAutoAdminPassword {
+    #[serde(rename = "GUID")]
+    /// The account's GUID, as reported by the AutoSetupAdminAccounts query.
+    pub guid: String,
+    #[serde(rename = "passwordHash", with = "serde_bytes")]
+    pub password_hash: Vec<u8>,
+}
+
+#[payload]
+#[derive(Default)]
+/// Allows a device awaiting configuration to continue through Setup Assistant.
+/// https://developer.apple.com/documentation/devicemanagement/deviceconfiguredcommand/command
+pub struct DeviceConfigured {}
+
+/// Handles the response to an AccountConfiguration command.
+/// Once acknowledged, we'll need our account's GUID in order to rotate its password.
+pub fn handle_account_configuration(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+) {
+    escrow::password_acknowledged(connection, &queued_command.command_uuid);
+    escrow::request_admin_account_guid(state, connection, &queued_command.udid);
+}
+
+/// Handles the response to a SetAutoAdminPassword command.
+pub fn handle_set_auto_admin_password(
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+) {
+    escrow::password_acknowledged(connection, &queued_command.command_uuid);
+}
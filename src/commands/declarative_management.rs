@@ -0,0 +1,12 @@
+use optional_value::payload;
+use serde_bytes::ByteBuf;
+
+#[payload]
+/// Enables Declarative Device Management, and asks the device to synchronize its declarations.
+/// https://developer.apple.com/documentation/devicemanagement/declarativemanagementcommand/command
+pub struct DeclarativeManagement {
+    #[serde(rename = "Data")]
+    /// Our synchronization tokens, as JSON.
+    /// The device only synchronizes if they differ from its own.
+    pub data: Option<ByteBuf>,
+}
@@ -0,0 +1,120 @@
+use diesel::prelude::*;
+use optional_value::payload;
+
+use crate::app_state::AppState;
+use crate::database::devices;
+use crate::escrow::{self, BypassCode, PasswordKind};
+use crate::plist::Plist;
+
+#[payload]
+/// Queries information about a device.
+/// https://developer.apple.com/documentation/devicemanagement/deviceinformationcommand/command
+pub struct DeviceInformation {
+    #[serde(rename = "Queries")]
+    /// The keys to query for, such as "SerialNumber" or "ActivationLockBypassCode".
+    pub queries: Vec<String>,
+}
+
+#[payload]
+/// The device's response to a DeviceInformation command.
+/// Only queried keys available to us are present.
+/// https://developer.apple.com/documentation/devicemanagement/deviceinformationresponse
+pub struct DeviceInformationResponse {
+    #[serde(rename = "QueryResponses")]
+    pub query_responses: QueryResponses,
+}
+
+#[payload]
+/// The subset of query responses we currently handle.
+pub struct QueryResponses {
+    #[serde(rename = "ActivationLockBypassCode")]
+    /// Only available on supervised devices with Activation Lock enabled.
+    pub activation_lock_bypass_code: Option<String>,
+    #[serde(rename = "IsSupervised")]
+    pub is_supervised: Option<bool>,
+    #[serde(rename = "DeviceName")]
+    /// The user-visible name of the device, such as "John's iPhone".
+    pub device_name: Option<String>,
+    #[serde(rename = "IsAppleSilicon")]
+    /// Only available on macOS.
+    pub is_apple_silicon: Option<bool>,
+    #[serde(rename = "AutoSetupAdminAccounts")]
+    /// Accounts created via AccountConfiguration. Only available on macOS.
+    pub auto_setup_admin_accounts: Option<Vec<AutoSetupAdminAccountInfo>>,
+}
+
+#[payload]
+/// https://developer.apple.com/documentation/devicemanagement/deviceinformationresponse/queryresponses/autosetupadminaccountsitem
+pub struct AutoSetupAdminAccountInfo {
+    #[serde(rename = "GUID")]
+    pub guid: String,
+    #[serde(rename = "shortName")]
+    pub short_name: String,
+}
+
+/// Handles the response to a DeviceInformation command.
+pub fn handle_response(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    contents: Vec<u8>,
+) {
+    let Ok(response) = Plist::<DeviceInformationResponse>::from_xml(contents) else {
+        println!("unable to parse DeviceInformation response from {udid}");
+        return;
+    };
+    let query_responses = response.query_responses;
+
+    // If the device has generated its own bypass code, escrow it.
+    if let Some(reported_code) = query_responses.activation_lock_bypass_code {
+        match BypassCode::parse(&reported_code) {
+            Some(code) => {
+                escrow::escrow_code(state, connection, udid, escrow::SOURCE_DEVICE, &code)
+            }
+            None => println!("device {udid} reported a malformed bypass code"),
+        }
+    }
+
+    if let Some(is_supervised) = query_responses.is_supervised {
+        diesel::update(devices::table.find(udid))
+            .set(devices::is_supervised.eq(is_supervised))
+            .execute(connection)
+            .expect("error updating device");
+    }
+
+    if let Some(device_name) = query_responses.device_name {
+        diesel::update(devices::table.find(udid))
+            .set(devices::device_name.eq(device_name))
+            .execute(connection)
+            .expect("error updating device");
+    }
+
+    // We need our administrator account's GUID to later change its password.
+    if let Some(admin_accounts) = query_responses.auto_setup_admin_accounts
+        && let Some(admin_name) = &state.config.passwords.admin_account_name
+        && let Some(admin_account) = admin_accounts
+            .into_iter()
+            .find(|account| &account.short_name == admin_name)
+    {
+        diesel::update(devices::table.find(udid))
+            .set(devices::admin_account_guid.eq(admin_account.guid))
+            .execute(connection)
+            .expect("error updating device");
+    }
+
+    // With a Mac's architecture known, we can set its pre-boot password.
+    if let Some(is_apple_silicon) = query_responses.is_apple_silicon {
+        diesel::update(devices::table.find(udid))
+            .set(devices::is_apple_silicon.eq(is_apple_silicon))
+            .execute(connection)
+            .expect("error updating device");
+
+        let password_kind = PasswordKind::for_architecture(is_apple_silicon);
+        if state.config.passwords.manage_mac_passwords
+            && escrow::active_password(connection, udid, password_kind).is_none()
+            && !escrow::has_pending_password(connection, udid, password_kind)
+        {
+            escrow::rotate_password(state, connection, udid, password_kind);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use diesel::SqliteConnection;
+use optional_value::payload;
+use serde_bytes::ByteBuf;
+
+use crate::app_state::AppState;
+use crate::escrow;
+use crate::plist::Plist;
+
+#[payload]
+/// Rotates the FileVault recovery key of a Mac.
+/// https://developer.apple.com/documentation/devicemanagement/rotatefilevaultkeycommand/command
+pub struct RotateFileVaultKey {
+    #[serde(rename = "KeyType")]
+    /// Either "personal" or "institutional". We only handle personal recovery keys.
+    pub key_type: String,
+    #[serde(rename = "FileVaultUnlock")]
+    pub unlock: FileVaultUnlock,
+    #[serde(rename = "ReplyEncryptionCertificate", with = "serde_bytes")]
+    /// The DER-encoded certificate to encrypt the new recovery key against.
+    pub reply_encryption_certificate: Vec<u8>,
+}
+
+#[payload]
+/// Credentials used to unlock FileVault prior to rotation.
+pub struct FileVaultUnlock {
+    #[serde(rename = "Password")]
+    /// A local user's password, or the current personal recovery key.
+    pub password: Option<String>,
+}
+
+#[payload]
+/// The device's response to a RotateFileVaultKey command.
+pub struct RotateFileVaultKeyResponse {
+    #[serde(rename = "RotateResult")]
+    pub rotate_result: Option<RotateResult>,
+}
+
+#[payload]
+pub struct RotateResult {
+    #[serde(rename = "EncryptedNewRecoveryKey")]
+    /// The new recovery key, encrypted against our reply encryption certificate.
+    pub encrypted_new_recovery_key: Option<ByteBuf>,
+}
+
+/// Handles the response to a RotateFileVaultKey command.
+pub fn handle_response(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    contents: Vec<u8>,
+) {
+    let Ok(response) = Plist::<RotateFileVaultKeyResponse>::from_xml(contents) else {
+        println!("unable to parse RotateFileVaultKey response from {udid}");
+        return;
+    };
+
+    let envelope = response
+        .rotate_result
+        .and_then(|result| result.encrypted_new_recovery_key);
+    match envelope {
+        Some(envelope) => escrow::escrow_recovery_key(state, connection, udid, &envelope),
+        None => println!("device {udid} did not return a rotated recovery key"),
+    }
+}
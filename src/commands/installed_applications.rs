@@ -0,0 +1,120 @@
+use diesel::prelude::*;
+use optional_value::payload;
+use time::{Duration, OffsetDateTime};
+
+use crate::app_state::AppState;
+use crate::database::{Device, InstalledApplication, devices, installed_applications};
+use crate::plist::Plist;
+
+use super::{Command, is_pending};
+
+/// How long a device's reported applications are considered current.
+const REPORT_MAX_AGE: Duration = Duration::days(1);
+
+#[payload]
+#[derive(Default)]
+/// Queries the applications installed on a device.
+/// https://developer.apple.com/documentation/devicemanagement/installedapplicationlistcommand/command
+pub struct InstalledApplicationList {
+    #[serde(rename = "Identifiers")]
+    /// If specified, only these bundle identifiers are reported.
+    pub identifiers: Option<Vec<String>>,
+    #[serde(rename = "ManagedAppsOnly")]
+    pub managed_apps_only: Option<bool>,
+}
+
+#[payload]
+/// The device's response to an InstalledApplicationList command.
+/// https://developer.apple.com/documentation/devicemanagement/installedapplicationlistresponse
+pub struct InstalledApplicationListResponse {
+    #[serde(rename = "InstalledApplicationList")]
+    pub installed_application_list: Vec<InstalledApplicationInfo>,
+}
+
+#[payload]
+/// The subset of application information we currently handle.
+/// https://developer.apple.com/documentation/devicemanagement/installedapplicationlistresponse/installedapplicationlistitem
+pub struct InstalledApplicationInfo {
+    #[serde(rename = "Identifier")]
+    /// Not present for some system applications on older versions.
+    pub identifier: Option<String>,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Version")]
+    pub version: Option<String>,
+    #[serde(rename = "ShortVersion")]
+    pub short_version: Option<String>,
+}
+
+/// Handles the response to an InstalledApplicationList command.
+/// The device's inventory is replaced in full.
+pub fn handle_response(connection: &mut SqliteConnection, udid: &str, contents: Vec<u8>) {
+    let Ok(response) = Plist::<InstalledApplicationListResponse>::from_xml(contents) else {
+        println!("unable to parse InstalledApplicationList response from {udid}");
+        return;
+    };
+
+    let applications: Vec<InstalledApplication> = response
+        .installed_application_list
+        .into_iter()
+        .filter_map(|application| {
+            Some(InstalledApplication {
+                udid: udid.to_string(),
+                bundle_identifier: application.identifier?,
+                name: application.name,
+                version: application.version,
+                short_version: application.short_version,
+            })
+        })
+        .collect();
+
+    connection
+        .transaction(|connection| {
+            diesel::delete(
+                installed_applications::table.filter(installed_applications::udid.eq(udid)),
+            )
+            .execute(connection)?;
+            // Identifiers may be reported more than once, such as for App Clips.
+            diesel::insert_or_ignore_into(installed_applications::table)
+                .values(&applications)
+                .execute(connection)?;
+            diesel::update(devices::table.find(udid))
+                .set(devices::applications_report_date.eq(OffsetDateTime::now_utc()))
+                .execute(connection)
+        })
+        .expect("error persisting installed applications");
+}
+
+/// The bundle identifiers installed on this device.
+/// Returns None if the device has yet to report its applications.
+pub fn installed_bundle_identifiers(
+    connection: &mut SqliteConnection,
+    device: &Device,
+) -> Option<Vec<String>> {
+    device.applications_report_date?;
+
+    let bundle_identifiers = installed_applications::table
+        .filter(installed_applications::udid.eq(&device.udid))
+        .select(installed_applications::bundle_identifier)
+        .load::<String>(connection)
+        .expect("can query installed applications");
+    Some(bundle_identifiers)
+}
+
+/// Queries the applications installed on this device, unless it has reported them
+/// recently or a query is already pending.
+/// Profiles referencing applications are validated against this inventory.
+pub fn refresh_applications(state: &AppState, connection: &mut SqliteConnection, device: &Device) {
+    let is_current = device
+        .applications_report_date
+        .is_some_and(|report_date| OffsetDateTime::now_utc() - report_date < REPORT_MAX_AGE);
+    if is_current || is_pending(connection, &device.udid, "InstalledApplicationList") {
+        return;
+    }
+
+    Command::InstalledApplicationList(InstalledApplicationList::default()).enqueue(
+        state,
+        connection,
+        &device.udid,
+    );
+}
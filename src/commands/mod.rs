@@ -0,0 +1,171 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::app_state::AppState;
+use crate::database::{QueuedCommand, commands};
+use crate::plist::Plist;
+
+pub mod accounts;
+pub mod declarative_management;
+pub mod device_information;
+pub mod filevault;
+pub mod installed_applications;
+pub mod passwords;
+mod profiles;
+pub mod security_info;
+
+pub use accounts::{
+    AccountConfiguration, AutoSetupAdminAccount, DeviceConfigured, SetAutoAdminPassword,
+};
+pub use declarative_management::DeclarativeManagement;
+pub use device_information::DeviceInformation;
+pub use filevault::{FileVaultUnlock, RotateFileVaultKey};
+pub use installed_applications::{InstalledApplicationList, refresh_applications};
+pub use passwords::{
+    SetFirmwarePassword, SetRecoveryLock, VerifyFirmwarePassword, VerifyRecoveryLock,
+};
+pub use profiles::{InstallProfile, install_profile};
+pub use security_info::SecurityInfo;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "RequestType")]
+/// All MDM commands we are able to send to devices.
+/// https://developer.apple.com/documentation/devicemanagement/commands_and_queries
+pub enum Command {
+    AccountConfiguration(AccountConfiguration),
+    DeclarativeManagement(DeclarativeManagement),
+    DeviceConfigured(DeviceConfigured),
+    DeviceInformation(DeviceInformation),
+    InstallProfile(InstallProfile),
+    InstalledApplicationList(InstalledApplicationList),
+    RotateFileVaultKey(RotateFileVaultKey),
+    SecurityInfo(SecurityInfo),
+    SetAutoAdminPassword(SetAutoAdminPassword),
+    SetFirmwarePassword(SetFirmwarePassword),
+    SetRecoveryLock(SetRecoveryLock),
+    VerifyFirmwarePassword(VerifyFirmwarePassword),
+    VerifyRecoveryLock(VerifyRecoveryLock),
+}
+
+impl Command {
+    /// The value of RequestType for this command.
+    pub fn request_type(&self) -> &'static str {
+        match self {
+            Command::AccountConfiguration(_) => "AccountConfiguration",
+            Command::DeclarativeManagement(_) => "DeclarativeManagement",
+            Command::DeviceConfigured(_) => "DeviceConfigured",
+            Command::DeviceInformation(_) => "DeviceInformation",
+            Command::InstallProfile(_) => "InstallProfile",
+            Command::InstalledApplicationList(_) => "InstalledApplicationList",
+            Command::RotateFileVaultKey(_) => "RotateFileVaultKey",
+            Command::SecurityInfo(_) => "SecurityInfo",
+            Command::SetAutoAdminPassword(_) => "SetAutoAdminPassword",
+            Command::SetFirmwarePassword(_) => "SetFirmwarePassword",
+            Command::SetRecoveryLock(_) => "SetRecoveryLock",
+            Command::VerifyFirmwarePassword(_) => "VerifyFirmwarePassword",
+            Command::VerifyRecoveryLock(_) => "VerifyRecoveryLock",
+        }
+    }
+
+    /// Queues this command for the given device.
+    /// It will be sent the next time the device contacts us.
+    ///
+    /// As commands may contain secrets (such as passwords),
+    /// their payloads are encrypted via our vault while queued.
+    pub fn enqueue(self, state: &AppState, connection: &mut SqliteConnection, udid: &str) -> Uuid {
+        let command_uuid = Uuid::new_v4();
+        let request_type = self.request_type();
+        let request = CommandRequest {
+            command_uuid,
+            command: self,
+        };
+        let payload = Plist(request)
+            .to_xml()
+            .expect("should be able to serialize command");
+        let sealed_payload = state.vault.seal(&vault_context(&command_uuid), &payload);
+
+        let current_time = OffsetDateTime::now_utc();
+        let queued_command = QueuedCommand {
+            command_uuid: command_uuid.to_string(),
+            udid: udid.to_string(),
+            request_type: request_type.to_string(),
+            payload: sealed_payload,
+            status: CommandStatus::Queued.as_str().to_string(),
+            creation_date: current_time,
+            last_update: current_time,
+        };
+        diesel::insert_into(commands::table)
+            .values(&queued_command)
+            .execute(connection)
+            .expect("error persisting command");
+
+        command_uuid
+    }
+}
+
+/// The context command payloads are sealed under within our vault.
+fn vault_context(command_uuid: &Uuid) -> String {
+    format!("command:{command_uuid}")
+}
+
+/// Decrypts the payload of a queued command, in order to send it.
+pub fn open_payload(state: &AppState, queued_command: &QueuedCommand) -> Option<Vec<u8>> {
+    let command_uuid = Uuid::parse_str(&queued_command.command_uuid).ok()?;
+    state
+        .vault
+        .open(&vault_context(&command_uuid), &queued_command.payload)
+}
+
+/// Whether a command of the given type is waiting to be sent to this device.
+/// This can be used to avoid queuing duplicate commands.
+pub fn is_pending(connection: &mut SqliteConnection, udid: &str, request_type: &str) -> bool {
+    let pending_count = commands::table
+        .filter(commands::udid.eq(udid))
+        .filter(commands::request_type.eq(request_type))
+        .filter(
+            commands::status
+                .eq(CommandStatus::Queued.as_str())
+                .or(commands::status.eq(CommandStatus::NotNow.as_str())),
+        )
+        .count()
+        .get_result::<i64>(connection)
+        .expect("can query commands");
+    pending_count > 0
+}
+
+#[derive(Serialize)]
+/// The full format of a command, as sent to a device.
+pub struct CommandRequest {
+    #[serde(rename = "CommandUUID")]
+    pub command_uuid: Uuid,
+    #[serde(rename = "Command")]
+    pub command: Command,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+/// The status a device reports for its current command.
+/// We additionally use this to track the state of queued commands.
+pub enum CommandStatus {
+    /// Only used by us: this command has not yet been sent.
+    Queued,
+    Idle,
+    Acknowledged,
+    Error,
+    CommandFormatError,
+    NotNow,
+}
+
+impl CommandStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandStatus::Queued => "Queued",
+            CommandStatus::Idle => "Idle",
+            CommandStatus::Acknowledged => "Acknowledged",
+            CommandStatus::Error => "Error",
+            CommandStatus::CommandFormatError => "CommandFormatError",
+            CommandStatus::NotNow => "NotNow",
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use diesel::SqliteConnection;
+use optional_value::payload;
+
+use crate::database::QueuedCommand;
+use crate::escrow::{self, PasswordKind};
+use crate::plist::Plist;
+
+#[payload]
+/// Sets (or clears) the Recovery Lock password on a Mac with Apple silicon.
+/// https://developer.apple.com/documentation/devicemanagement/setrecoverylockcommand/command
+pub struct SetRecoveryLock {
+    #[serde(rename = "CurrentPassword")]
+    /// Required if a password is currently set.
+    pub current_password: Option<String>,
+    #[serde(rename = "NewPassword")]
+    pub new_password: String,
+}
+
+#[payload]
+/// Verifies the Recovery Lock password on a Mac with Apple silicon.
+/// https://developer.apple.com/documentation/devicemanagement/verifyrecoverylockcommand/command
+pub struct VerifyRecoveryLock {
+    #[serde(rename = "Password")]
+    pub password: String,
+}
+
+#[payload]
+/// The device's response to a VerifyRecoveryLock command.
+pub struct VerifyRecoveryLockResponse {
+    #[serde(rename = "PasswordVerified")]
+    pub password_verified: bool,
+}
+
+#[payload]
+/// Sets the firmware password on an Intel-based Mac.
+/// It only takes effect once the device restarts.
+/// https://developer.apple.com/documentation/devicemanagement/setfirmwarepasswordcommand/command
+pub struct SetFirmwarePassword {
+    #[serde(rename = "CurrentPassword")]
+    /// Required if a password is currently set.
+    pub current_password: Option<String>,
+    #[serde(rename = "NewPassword")]
+    pub new_password: String,
+    #[serde(rename = "AllowOroms")]
+    /// Whether to allow option ROMs to load.
+    pub allow_oroms: Option<bool>,
+}
+
+#[payload]
+/// The device's response to a SetFirmwarePassword command.
+pub struct SetFirmwarePasswordResponse {
+    #[serde(rename = "SetFirmwarePassword")]
+    pub result: SetFirmwarePasswordResult,
+}
+
+#[payload]
+pub struct SetFirmwarePasswordResult {
+    #[serde(rename = "PasswordChanged")]
+    pub password_changed: bool,
+}
+
+#[payload]
+/// Verifies the firmware password on an Intel-based Mac.
+/// https://developer.apple.com/documentation/devicemanagement/verifyfirmwarepasswordcommand/command
+pub struct VerifyFirmwarePassword {
+    #[serde(rename = "Password")]
+    pub password: String,
+}
+
+#[payload]
+/// The device's response to a VerifyFirmwarePassword command.
+pub struct VerifyFirmwarePasswordResponse {
+    #[serde(rename = "VerifyFirmwarePassword")]
+    pub result: VerifyFirmwarePasswordResult,
+}
+
+#[payload]
+pub struct VerifyFirmwarePasswordResult {
+    #[serde(rename = "PasswordVerified")]
+    pub password_verified: bool,
+}
+
+/// Handles the response to a SetRecoveryLock command.
+/// An acknowledgement means our new password is now in effect.
+pub fn handle_set_recovery_lock(connection: &mut SqliteConnection, queued_command: &QueuedCommand) {
+    escrow::password_acknowledged(connection, &queued_command.command_uuid);
+}
+
+/// Handles the response to a SetFirmwarePassword command.
+pub fn handle_set_firmware_password(
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+    contents: Vec<u8>,
+) {
+    let command_uuid = &queued_command.command_uuid;
+    let Ok(response) = Plist::<SetFirmwarePasswordResponse>::from_xml(contents) else {
+        println!("unable to parse SetFirmwarePassword response for {command_uuid}");
+        return;
+    };
+
+    if response.result.password_changed {
+        escrow::password_acknowledged(connection, command_uuid);
+    } else {
+        escrow::password_failed(connection, command_uuid);
+    }
+}
+
+/// Handles the response to a VerifyRecoveryLock command.
+pub fn handle_verify_recovery_lock(
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+    contents: Vec<u8>,
+) {
+    let udid = &queued_command.udid;
+    let Ok(response) = Plist::<VerifyRecoveryLockResponse>::from_xml(contents) else {
+        println!("unable to parse VerifyRecoveryLock response from {udid}");
+        return;
+    };
+
+    if response.password_verified {
+        escrow::password_verified(connection, udid, PasswordKind::RecoveryLock);
+    } else {
+        println!("device {udid} did not verify its Recovery Lock password");
+    }
+}
+
+/// Handles the response to a VerifyFirmwarePassword command.
+pub fn handle_verify_firmware_password(
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+    contents: Vec<u8>,
+) {
+    let udid = &queued_command.udid;
+    let Ok(response) = Plist::<VerifyFirmwarePasswordResponse>::from_xml(contents) else {
+        println!("unable to parse VerifyFirmwarePassword response from {udid}");
+        return;
+    };
+
+    if response.result.password_verified {
+        escrow::password_verified(connection, udid, PasswordKind::Firmware);
+    } else {
+        println!("device {udid} did not verify its firmware password");
+    }
+}
@@ -0,0 +1,102 @@
+use diesel::prelude::*;
+use optional_value::payload;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::app_state::AppState;
+use crate::database::{Device, InstalledProfile, installed_profiles};
+use crate::escrow;
+use crate::payloads::{Payload, Profile, version_profile};
+
+use super::Command;
+use super::installed_applications::{installed_bundle_identifiers, refresh_applications};
+
+#[payload]
+/// Installs a configuration profile.
+/// https://developer.apple.com/documentation/devicemanagement/installprofilecommand/command
+pub struct InstallProfile {
+    #[serde(rename = "Payload", with = "serde_bytes")]
+    /// The profile to install, typically signed.
+    pub payload: Vec<u8>,
+}
+
+/// Signs and queues the given profile for installation on a device.
+///
+/// Profiles referencing certificates they do not contain are rejected by devices,
+/// so we warn about them here, alongside policies such as malformed code requirements.
+/// Likewise, if the device is known to be unsupervised,
+/// we warn about any keys within the profile which it will ignore.
+/// Apps the profile refers to are checked against the device's inventory, once known.
+/// The profile is still installed in any case.
+///
+/// Payload UUIDs are derived from the profile's contents. If the same contents were
+/// already installed on this device, its version is kept, so that the device sees an
+/// identical profile and reinstalling it is a no-op. (It is still queued, as the device
+/// may have removed it since.) Otherwise, the device's revision of this profile is replaced,
+/// and its version incremented.
+///
+/// Removal password payloads without a password are given the one we store for this profile.
+pub fn install_profile(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device: &Device,
+    mut profile: Profile<Payload>,
+) -> Result<Uuid, plist::Error> {
+    let identifier = profile.base.identifier.clone();
+    profile.fill_removal_password(|| escrow::removal_password(state, connection, &identifier));
+    for warning in profile.unresolved_references() {
+        println!("profile {} is invalid - {warning}", profile.base.identifier);
+    }
+    for warning in profile.policy_warnings() {
+        println!("profile {} is invalid - {warning}", profile.base.identifier);
+    }
+    if device.is_supervised == Some(false) {
+        for warning in profile.supervision_warnings() {
+            println!("device {} is unsupervised - {warning}", device.udid);
+        }
+    }
+    // A stale inventory is still used, but we'll ask for a current one for next time.
+    refresh_applications(state, connection, device);
+    if let Some(installed) = installed_bundle_identifiers(connection, device) {
+        for warning in profile.missing_applications(&installed) {
+            println!("device {} is missing an app - {warning}", device.udid);
+        }
+    }
+
+    let namespace = state.config.service.uuid_namespace();
+    let versioned = version_profile(&profile, &namespace)?;
+    let previous = installed_profiles::table
+        .find((&device.udid, &versioned.identifier))
+        .first::<InstalledProfile>(connection)
+        .optional()
+        .expect("can query installed profiles");
+
+    let version = match &previous {
+        Some(previous) if previous.content_hash == versioned.content_hash => previous.version,
+        Some(previous) => previous.version + 1,
+        None => 1,
+    };
+
+    let profile_data = state.certificates.signed_profile_data(versioned.contents)?;
+    let command_uuid = Command::InstallProfile(InstallProfile {
+        payload: profile_data,
+    })
+    .enqueue(state, connection, &device.udid);
+
+    let installed_profile = InstalledProfile {
+        udid: device.udid.clone(),
+        identifier: versioned.identifier,
+        version,
+        content_hash: versioned.content_hash,
+        command_uuid: command_uuid.to_string(),
+        update_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(installed_profiles::table)
+        .values(&installed_profile)
+        .on_conflict((installed_profiles::udid, installed_profiles::identifier))
+        .do_update()
+        .set(&installed_profile)
+        .execute(connection)
+        .expect("error persisting installed profile");
+    Ok(command_uuid)
+}
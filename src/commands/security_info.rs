@@ -0,0 +1,52 @@
+use diesel::SqliteConnection;
+use optional_value::payload;
+use serde_bytes::ByteBuf;
+
+use crate::app_state::AppState;
+use crate::escrow;
+use crate::plist::Plist;
+
+#[payload]
+#[derive(Default)]
+/// Queries security-related information about a device.
+/// https://developer.apple.com/documentation/devicemanagement/securityinfocommand/command
+pub struct SecurityInfo {}
+
+#[payload]
+/// The device's response to a SecurityInfo command.
+/// https://developer.apple.com/documentation/devicemanagement/securityinforesponse
+pub struct SecurityInfoResponse {
+    #[serde(rename = "SecurityInfo")]
+    pub security_info: SecurityInfoDetails,
+}
+
+#[payload]
+/// The subset of security information we currently handle.
+pub struct SecurityInfoDetails {
+    #[serde(rename = "FDE_Enabled")]
+    /// Whether FileVault is enabled. Only available on macOS.
+    pub fde_enabled: Option<bool>,
+    #[serde(rename = "FDE_HasPersonalRecoveryKey")]
+    pub fde_has_personal_recovery_key: Option<bool>,
+    #[serde(rename = "FDE_PersonalRecoveryKeyCMS")]
+    /// The personal recovery key, encrypted against our escrow certificate.
+    /// Only present if our FileVault escrow payload is installed.
+    pub fde_personal_recovery_key_cms: Option<ByteBuf>,
+}
+
+/// Handles the response to a SecurityInfo command.
+pub fn handle_response(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    contents: Vec<u8>,
+) {
+    let Ok(response) = Plist::<SecurityInfoResponse>::from_xml(contents) else {
+        println!("unable to parse SecurityInfo response from {udid}");
+        return;
+    };
+
+    if let Some(envelope) = response.security_info.fde_personal_recovery_key_cms {
+        escrow::escrow_recovery_key(state, connection, udid, &envelope);
+    }
+}
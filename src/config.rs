@@ -1,10 +1,28 @@
+use crate::payloads::{ProfileOptions, TargetDeviceType, name_based_uuid};
+use plist::Date;
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
 use std::{fs, net::IpAddr};
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
     pub service: ServiceConfig,
     pub storage: StorageConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub push: PushConfig,
+    #[serde(default)]
+    pub passwords: PasswordConfig,
+    #[serde(default)]
+    pub acme: AcmeConfig,
+    #[serde(default)]
+    pub accounts: AccountsConfig,
+    #[serde(default)]
+    pub profiles: ProfilesConfig,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -32,6 +50,14 @@ pub struct ServiceConfig {
     pub device_ca_name: String,
 }
 
+impl ServiceConfig {
+    /// The namespace our deterministic payload UUIDs are created under.
+    /// It is derived from our base identifier, and thus unique to this server.
+    pub fn uuid_namespace(&self) -> Uuid {
+        name_based_uuid(&Uuid::NAMESPACE_DNS, &self.base_identifier)
+    }
+}
+
 /// Used to allow manipulating CA names to include the organizational name.
 impl From<RawServiceConfig> for ServiceConfig {
     fn from(value: RawServiceConfig) -> Self {
@@ -58,6 +84,129 @@ pub struct StorageConfig {
     pub assets_dir: String,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AdminConfig {
+    /// The token required to access administrative endpoints.
+    /// If not specified, they are disabled entirely.
+    pub api_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PushConfig {
+    /// The topic of our APNs push certificate, such as "com.apple.mgmt.External.<UUID>".
+    /// If not specified, devices are unable to complete enrollment.
+    pub topic: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PasswordConfig {
+    /// Whether to set a Recovery Lock (Apple silicon) or firmware (Intel)
+    /// password on Macs once they enroll.
+    #[serde(default)]
+    pub manage_mac_passwords: bool,
+    /// How many days a password remains in effect before it is rotated.
+    /// If not specified, passwords are only rotated upon request.
+    pub rotation_days: Option<i64>,
+    /// The short name of a hidden administrator account to create on Macs
+    /// enrolled via Automated Device Enrollment.
+    /// If not specified, no account is created.
+    pub admin_account_name: Option<String>,
+    /// The full name of this administrator account.
+    pub admin_account_full_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AcmeConfig {
+    /// The path to the Apple Enterprise Attestation Root CA, in PEM format.
+    /// Device attestations must chain up to this certificate.
+    /// If not specified, our ACME server is disabled entirely.
+    pub apple_attestation_root_path: Option<String>,
+    /// How many days certificates issued via ACME are valid for.
+    /// If not specified, defaults to 365.
+    pub certificate_validity_days: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+/// Servers to configure accounts against.
+/// Each user's own details are filled in when their profile is created.
+pub struct AccountsConfig {
+    /// Whether users sign in with their email address rather than their user name.
+    #[serde(default)]
+    pub sign_in_with_email_address: bool,
+    pub mail: Option<MailAccountConfig>,
+    pub exchange: Option<ExchangeAccountConfig>,
+    pub caldav: Option<DavAccountConfig>,
+    pub carddav: Option<DavAccountConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+/// An IMAP account, sending mail via SMTP.
+pub struct MailAccountConfig {
+    pub incoming_host: String,
+    pub incoming_port: Option<u16>,
+    pub outgoing_host: String,
+    pub outgoing_port: Option<u16>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ExchangeAccountConfig {
+    pub host: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DavAccountConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    /// If not specified, the principal is discovered via the host.
+    pub principal_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+/// Options for the profiles we serve prior to enrollment.
+pub struct ProfilesConfig {
+    #[serde(default)]
+    pub enrollment: ProfileOptionsConfig,
+    #[serde(default)]
+    pub trust: ProfileOptionsConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// As these profiles are served without authentication, removal passwords are unavailable.
+/// They may only be used within profiles installed via InstallProfile.
+/// Unknown options (such as `removal_password`) are rejected, rather than silently ignored.
+pub struct ProfileOptionsConfig {
+    /// Whether users are prevented from removing this profile.
+    #[serde(default)]
+    pub removal_disallowed: bool,
+    /// Text shown prior to installation, keyed by language code.
+    /// The "default" key is shown if no other language matches.
+    pub consent_text: Option<BTreeMap<String, String>>,
+    /// When this profile is automatically removed, in RFC 3339 format.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub removal_date: Option<OffsetDateTime>,
+    /// How many seconds after installation this profile is automatically removed.
+    pub duration_until_removal: Option<f64>,
+    /// The type of device this profile may be installed on, as its numeric value.
+    pub target_device_type: Option<TargetDeviceType>,
+}
+
+impl ProfileOptionsConfig {
+    /// The top-level keys to apply to this profile.
+    pub fn options(&self) -> ProfileOptions {
+        ProfileOptions {
+            removal_disallowed: self.removal_disallowed.then_some(true),
+            consent_text: self.consent_text.clone(),
+            removal_date: self
+                .removal_date
+                .map(|removal_date| Date::from(SystemTime::from(removal_date))),
+            duration_until_removal: self.duration_until_removal,
+            target_device_type: self.target_device_type,
+            has_removal_passcode: None,
+        }
+    }
+}
+
 /// Used to access options within configuration.
 impl Config {
     /// Loads the configuration from the specified path to our shared OnceCell.
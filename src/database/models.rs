@@ -1,4 +1,10 @@
-use super::schema::pending_enrollments;
+use super::schema::{
+    acme_accounts, acme_client_identifiers, acme_nonces, acme_orders, activation_lock_bypass_codes,
+    bootstrap_tokens, commands, declaration_assets, declarations, device_declarations,
+    device_passwords, device_status_changes, device_statuses, devices, filevault_recovery_keys,
+    installed_applications, installed_profiles, pending_enrollments, profile_removal_passwords,
+    secret_accesses, users,
+};
 use diesel::prelude::*;
 use time::OffsetDateTime;
 
@@ -8,7 +14,7 @@ pub struct PendingEnrollment {
     pub creation_date: OffsetDateTime,
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
 pub struct Device {
     pub udid: String,
     pub device_version: String,
@@ -16,4 +22,224 @@ pub struct Device {
     pub serial_number: String,
     pub imei: Option<String>,
     pub last_contact: OffsetDateTime,
+    pub identity_fingerprint: Option<String>,
+    pub is_apple_silicon: Option<bool>,
+    pub admin_account_guid: Option<String>,
+    pub is_supervised: Option<bool>,
+    pub applications_report_date: Option<OffsetDateTime>,
+    pub user_name: Option<String>,
+    pub device_name: Option<String>,
+    pub asset_tag: Option<String>,
+    /// The device's preferred language, such as "fr-CA".
+    pub language: Option<String>,
+    pub declarations_update_date: Option<OffsetDateTime>,
+}
+
+/// Prefixes of the product names Macs report, such as "MacBookPro18,3", "Macmini9,1",
+/// "iMac21,1", "Mac14,2" or "VirtualMac2,1". Other devices report names such as "iPad13,1".
+const MAC_PRODUCT_PREFIXES: [&str; 3] = ["Mac", "iMac", "VirtualMac"];
+
+impl Device {
+    /// Whether this device is a Mac, based on its product name.
+    pub fn is_mac(&self) -> bool {
+        MAC_PRODUCT_PREFIXES
+            .iter()
+            .any(|prefix| self.product.starts_with(prefix))
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = commands)]
+/// An MDM command queued for, or already sent to, a device.
+pub struct QueuedCommand {
+    pub command_uuid: String,
+    pub udid: String,
+    pub request_type: String,
+    /// The full command, encrypted via our vault.
+    pub payload: Vec<u8>,
+    pub status: String,
+    pub creation_date: OffsetDateTime,
+    pub last_update: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+pub struct ActivationLockBypassCode {
+    pub udid: String,
+    pub source: String,
+    pub sealed_code: Vec<u8>,
+    pub code_hash: String,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+pub struct FilevaultRecoveryKey {
+    pub udid: String,
+    pub sealed_key: Vec<u8>,
+    pub creation_date: OffsetDateTime,
+    pub viewed_date: Option<OffsetDateTime>,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct ProfileRemovalPassword {
+    pub identifier: String,
+    pub sealed_password: Vec<u8>,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct BootstrapToken {
+    pub udid: String,
+    pub sealed_token: Vec<u8>,
+    /// The identity certificate the device escrowed this token with.
+    pub identity_fingerprint: String,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable)]
+pub struct DevicePassword {
+    pub id: i32,
+    pub udid: String,
+    pub kind: String,
+    pub sealed_password: Vec<u8>,
+    pub command_uuid: String,
+    pub status: String,
+    pub creation_date: OffsetDateTime,
+    pub activation_date: Option<OffsetDateTime>,
+    pub verification_date: Option<OffsetDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = device_passwords)]
+pub struct NewDevicePassword {
+    pub udid: String,
+    pub kind: String,
+    pub sealed_password: Vec<u8>,
+    pub command_uuid: String,
+    pub status: String,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = secret_accesses)]
+/// A record of an administrator retrieving an escrowed secret.
+pub struct NewSecretAccess {
+    pub udid: String,
+    pub secret_kind: String,
+    pub access_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+/// A one-time identifier provided to a device within an ACME payload.
+pub struct AcmeClientIdentifier {
+    pub client_identifier: String,
+    pub udid: String,
+    pub creation_date: OffsetDateTime,
+    pub use_date: Option<OffsetDateTime>,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct AcmeNonce {
+    pub nonce: String,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct AcmeAccount {
+    pub account_id: String,
+    pub key_thumbprint: String,
+    pub jwk: String,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct AcmeOrder {
+    pub order_id: String,
+    pub account_id: String,
+    pub client_identifier: String,
+    pub udid: String,
+    pub status: String,
+    pub challenge_token: String,
+    pub attested_public_key: Option<Vec<u8>>,
+    pub certificate: Option<Vec<u8>>,
+    pub creation_date: OffsetDateTime,
+    pub expiration_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+/// An application a device has reported as installed.
+pub struct InstalledApplication {
+    pub udid: String,
+    pub bundle_identifier: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub short_version: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+/// The latest revision of a profile we have installed on a device.
+pub struct InstalledProfile {
+    pub udid: String,
+    pub identifier: String,
+    /// Incremented whenever the profile's contents change.
+    pub version: i32,
+    /// The SHA-256 hash of the profile's contents, in hexadecimal form.
+    pub content_hash: String,
+    /// The InstallProfile command which installed this revision.
+    pub command_uuid: String,
+    pub update_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+/// A user devices may be assigned to.
+/// Their attributes are used to personalize profiles.
+pub struct User {
+    pub user_name: String,
+    pub full_name: Option<String>,
+    pub email_address: Option<String>,
+    pub creation_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = declarations)]
+pub struct StoredDeclaration {
+    pub identifier: String,
+    pub declaration_type: String,
+    pub payload: String,
+    pub server_token: String,
+    pub update_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable)]
+pub struct DeviceDeclaration {
+    pub udid: String,
+    pub identifier: String,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = device_statuses)]
+pub struct DeviceStatus {
+    pub udid: String,
+    pub status_item: String,
+    pub value: String,
+    pub update_date: OffsetDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = device_status_changes)]
+pub struct NewDeviceStatusChange {
+    pub udid: String,
+    pub status_item: String,
+    pub value: Option<String>,
+    pub report_date: OffsetDateTime,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = declaration_assets, treat_none_as_null = true)]
+/// The data of an asset declaration, either within our assets directory or our database.
+pub struct DeclarationAsset {
+    pub identifier: String,
+    pub content_type: String,
+    pub file_name: Option<String>,
+    pub sealed_contents: Option<Vec<u8>>,
+    pub update_date: OffsetDateTime,
 }
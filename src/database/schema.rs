@@ -1,5 +1,136 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    acme_accounts (account_id) {
+        account_id -> Text,
+        key_thumbprint -> Text,
+        jwk -> Text,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    acme_client_identifiers (client_identifier) {
+        client_identifier -> Text,
+        udid -> Text,
+        creation_date -> TimestamptzSqlite,
+        use_date -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    acme_nonces (nonce) {
+        nonce -> Text,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    acme_orders (order_id) {
+        order_id -> Text,
+        account_id -> Text,
+        client_identifier -> Text,
+        udid -> Text,
+        status -> Text,
+        challenge_token -> Text,
+        attested_public_key -> Nullable<Binary>,
+        certificate -> Nullable<Binary>,
+        creation_date -> TimestamptzSqlite,
+        expiration_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    activation_lock_bypass_codes (udid, source) {
+        udid -> Text,
+        source -> Text,
+        sealed_code -> Binary,
+        code_hash -> Text,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    bootstrap_tokens (udid) {
+        udid -> Text,
+        sealed_token -> Binary,
+        identity_fingerprint -> Text,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    commands (command_uuid) {
+        command_uuid -> Text,
+        udid -> Text,
+        request_type -> Text,
+        payload -> Binary,
+        status -> Text,
+        creation_date -> TimestamptzSqlite,
+        last_update -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    declaration_assets (identifier) {
+        identifier -> Text,
+        content_type -> Text,
+        file_name -> Nullable<Text>,
+        sealed_contents -> Nullable<Binary>,
+        update_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    declarations (identifier) {
+        identifier -> Text,
+        declaration_type -> Text,
+        payload -> Text,
+        server_token -> Text,
+        update_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    device_declarations (udid, identifier) {
+        udid -> Text,
+        identifier -> Text,
+    }
+}
+
+diesel::table! {
+    device_passwords (id) {
+        id -> Integer,
+        udid -> Text,
+        kind -> Text,
+        sealed_password -> Binary,
+        command_uuid -> Text,
+        status -> Text,
+        creation_date -> TimestamptzSqlite,
+        activation_date -> Nullable<TimestamptzSqlite>,
+        verification_date -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    device_status_changes (id) {
+        id -> Integer,
+        udid -> Text,
+        status_item -> Text,
+        value -> Nullable<Text>,
+        report_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    device_statuses (udid, status_item) {
+        udid -> Text,
+        status_item -> Text,
+        value -> Text,
+        update_date -> TimestamptzSqlite,
+    }
+}
+
 diesel::table! {
     devices (udid) {
         udid -> Text,
@@ -8,6 +139,46 @@ diesel::table! {
         serial_number -> Text,
         imei -> Nullable<Text>,
         last_contact -> TimestamptzSqlite,
+        identity_fingerprint -> Nullable<Text>,
+        is_apple_silicon -> Nullable<Bool>,
+        admin_account_guid -> Nullable<Text>,
+        is_supervised -> Nullable<Bool>,
+        applications_report_date -> Nullable<TimestamptzSqlite>,
+        user_name -> Nullable<Text>,
+        device_name -> Nullable<Text>,
+        asset_tag -> Nullable<Text>,
+        language -> Nullable<Text>,
+        declarations_update_date -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    filevault_recovery_keys (udid) {
+        udid -> Text,
+        sealed_key -> Binary,
+        creation_date -> TimestamptzSqlite,
+        viewed_date -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    installed_applications (udid, bundle_identifier) {
+        udid -> Text,
+        bundle_identifier -> Text,
+        name -> Nullable<Text>,
+        version -> Nullable<Text>,
+        short_version -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    installed_profiles (udid, identifier) {
+        udid -> Text,
+        identifier -> Text,
+        version -> Integer,
+        content_hash -> Text,
+        command_uuid -> Text,
+        update_date -> TimestamptzSqlite,
     }
 }
 
@@ -18,4 +189,73 @@ diesel::table! {
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(devices, pending_enrollments,);
+diesel::table! {
+    profile_removal_passwords (identifier) {
+        identifier -> Text,
+        sealed_password -> Binary,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    secret_accesses (id) {
+        id -> Integer,
+        udid -> Text,
+        secret_kind -> Text,
+        access_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    users (user_name) {
+        user_name -> Text,
+        full_name -> Nullable<Text>,
+        email_address -> Nullable<Text>,
+        creation_date -> TimestamptzSqlite,
+    }
+}
+
+diesel::joinable!(acme_client_identifiers -> devices (udid));
+diesel::joinable!(acme_orders -> acme_accounts (account_id));
+diesel::joinable!(acme_orders -> acme_client_identifiers (client_identifier));
+diesel::joinable!(acme_orders -> devices (udid));
+diesel::joinable!(activation_lock_bypass_codes -> devices (udid));
+diesel::joinable!(bootstrap_tokens -> devices (udid));
+diesel::joinable!(commands -> devices (udid));
+diesel::joinable!(declaration_assets -> declarations (identifier));
+diesel::joinable!(device_declarations -> declarations (identifier));
+diesel::joinable!(device_declarations -> devices (udid));
+diesel::joinable!(device_passwords -> commands (command_uuid));
+diesel::joinable!(device_passwords -> devices (udid));
+diesel::joinable!(device_status_changes -> devices (udid));
+diesel::joinable!(device_statuses -> devices (udid));
+diesel::joinable!(devices -> users (user_name));
+diesel::joinable!(filevault_recovery_keys -> devices (udid));
+diesel::joinable!(installed_applications -> devices (udid));
+diesel::joinable!(installed_profiles -> commands (command_uuid));
+diesel::joinable!(installed_profiles -> devices (udid));
+diesel::joinable!(secret_accesses -> devices (udid));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    acme_accounts,
+    acme_client_identifiers,
+    acme_nonces,
+    acme_orders,
+    activation_lock_bypass_codes,
+    bootstrap_tokens,
+    commands,
+    declaration_assets,
+    declarations,
+    device_declarations,
+    device_passwords,
+    device_status_changes,
+    device_statuses,
+    devices,
+    filevault_recovery_keys,
+    installed_applications,
+    installed_profiles,
+    pending_enrollments,
+    profile_removal_passwords,
+    secret_accesses,
+    users,
+);
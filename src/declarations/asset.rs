@@ -0,0 +1,370 @@
+use diesel::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
+
+use crate::app_state::AppState;
+use crate::assets::read_asset;
+use crate::database::{DeclarationAsset, StoredDeclaration, declaration_assets, declarations};
+use crate::vault::Vault;
+
+use super::{Declaration, store_declaration};
+
+/// How long the signed URL of an asset remains valid.
+const ASSET_URL_LIFETIME: Duration = Duration::days(30);
+/// How long before expiring an asset's URL is replaced, giving devices time to sync.
+const ASSET_URL_RENEWAL: Duration = Duration::days(7);
+/// How often we check for asset URLs due to be renewed.
+const RENEWAL_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Clone, Copy, Deserialize)]
+/// The kinds of asset declarations we're able to serve.
+/// https://developer.apple.com/documentation/devicemanagement/assets
+pub enum AssetType {
+    #[serde(rename = "credential.acme")]
+    AcmeCredential,
+    #[serde(rename = "credential.certificate")]
+    CertificateCredential,
+    #[serde(rename = "credential.identity")]
+    IdentityCredential,
+    #[serde(rename = "credential.scep")]
+    ScepCredential,
+    #[serde(rename = "credential.userpassword")]
+    UserPasswordCredential,
+    #[serde(rename = "data")]
+    Data,
+}
+
+impl AssetType {
+    pub fn declaration_type(&self) -> &'static str {
+        match self {
+            AssetType::AcmeCredential => "com.apple.asset.credential.acme",
+            AssetType::CertificateCredential => "com.apple.asset.credential.certificate",
+            AssetType::IdentityCredential => "com.apple.asset.credential.identity",
+            AssetType::ScepCredential => "com.apple.asset.credential.scep",
+            AssetType::UserPasswordCredential => "com.apple.asset.credential.userpassword",
+            AssetType::Data => "com.apple.asset.data",
+        }
+    }
+
+    /// The content type devices expect for this kind of asset, if any.
+    /// Data assets may have any content type.
+    pub fn content_type(&self) -> Option<&'static str> {
+        match self {
+            AssetType::AcmeCredential => Some("application/json"),
+            AssetType::CertificateCredential => Some("application/pkix-cert"),
+            AssetType::IdentityCredential => Some("application/pkcs12"),
+            AssetType::ScepCredential => Some("application/json"),
+            AssetType::UserPasswordCredential => Some("application/json"),
+            AssetType::Data => None,
+        }
+    }
+}
+
+/// Where the data of an asset comes from.
+pub enum AssetSource {
+    /// A path relative to our assets directory.
+    /// Its contents are read whenever a device requests it.
+    File(String),
+    /// Data stored (sealed) within our database.
+    Contents(Vec<u8>),
+}
+
+/// Creates or replaces an asset declaration, and stores (or references) its data.
+///
+/// Its reference includes the data's hash, so devices only fetch it again once it changes.
+/// Assets within our assets directory must be stored again after they are modified,
+/// as their prior URL is no longer valid.
+pub fn store_asset(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    identifier: String,
+    asset_type: AssetType,
+    content_type: Option<String>,
+    source: AssetSource,
+) -> Result<Declaration, String> {
+    // Identifiers are placed within our asset URLs as-is.
+    let is_valid_identifier = !identifier.is_empty()
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !is_valid_identifier {
+        return Err(
+            "asset identifiers may only contain letters, digits, '.', '-' and '_'".to_string(),
+        );
+    }
+    let content_type = match (asset_type.content_type(), content_type) {
+        (Some(expected), Some(content_type)) if expected != content_type => {
+            return Err(format!(
+                "{identifier} must have the content type {expected}"
+            ));
+        }
+        (Some(expected), _) => expected.to_string(),
+        (None, content_type) => {
+            content_type.unwrap_or_else(|| "application/octet-stream".to_string())
+        }
+    };
+
+    let (contents, file_name, sealed_contents) = match source {
+        AssetSource::File(file_name) => {
+            let contents = read_asset(&state.config, &file_name)
+                .ok_or_else(|| format!("unable to read asset {file_name}"))?;
+            (contents, Some(file_name), None)
+        }
+        AssetSource::Contents(contents) => {
+            let sealed = state.vault.seal(&vault_context(&identifier), &contents);
+            (contents, None, Some(sealed))
+        }
+    };
+    let asset = DeclarationAsset {
+        identifier: identifier.clone(),
+        content_type: content_type.clone(),
+        file_name,
+        sealed_contents,
+        update_date: OffsetDateTime::now_utc(),
+    };
+
+    let content_hash = hex::encode(Sha256::digest(&contents));
+    let payload = json!({
+        "Reference": {
+            "DataURL": asset_url(state, &identifier, &content_hash, asset.update_date),
+            "ContentType": content_type,
+            "Size": contents.len(),
+            "Hash-SHA-256": content_hash,
+        },
+        // Our signed URL is the sole means of authentication.
+        "Authentication": {
+            "Type": "None",
+        },
+    });
+    let declaration = Declaration::new(
+        identifier,
+        asset_type.declaration_type().to_string(),
+        payload,
+    );
+
+    // Our declaration must exist before its data may reference it.
+    store_declaration(state, connection, &declaration);
+    diesel::insert_into(declaration_assets::table)
+        .values(&asset)
+        .on_conflict(declaration_assets::identifier)
+        .do_update()
+        .set(&asset)
+        .execute(connection)
+        .expect("error persisting declaration asset");
+    Ok(declaration)
+}
+
+/// Creates a declaration describing the user of a device.
+/// Unlike other assets, its contents are within the declaration itself.
+/// https://developer.apple.com/documentation/devicemanagement/assetuseridentity
+pub fn user_identity(
+    identifier: String,
+    full_name: Option<String>,
+    email_address: Option<String>,
+) -> Declaration {
+    let mut payload = json!({});
+    if let Some(full_name) = full_name {
+        payload["FullName"] = json!(full_name);
+    }
+    if let Some(email_address) = email_address {
+        payload["EmailAddress"] = json!(email_address);
+    }
+    Declaration::new(
+        identifier,
+        "com.apple.asset.useridentity".to_string(),
+        payload,
+    )
+}
+
+/// The content type and current data of the given asset.
+pub fn asset_data(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    identifier: &str,
+) -> Option<(String, Vec<u8>)> {
+    let asset = declaration_assets::table
+        .find(identifier)
+        .first::<DeclarationAsset>(connection)
+        .optional()
+        .expect("can query declaration assets")?;
+    let contents = match (asset.file_name, asset.sealed_contents) {
+        (Some(file_name), _) => read_asset(&state.config, &file_name),
+        (None, Some(sealed)) => state.vault.open(&vault_context(identifier), &sealed),
+        (None, None) => None,
+    }?;
+    Some((asset.content_type, contents))
+}
+
+/// Whether the given URL signature is valid for this asset's current data,
+/// and has yet to expire.
+/// Signatures are no longer valid once an asset's data changes.
+pub fn verify_asset_signature(
+    vault: &Vault,
+    identifier: &str,
+    contents: &[u8],
+    expiry: i64,
+    signature: &str,
+) -> bool {
+    if OffsetDateTime::now_utc().unix_timestamp() >= expiry {
+        return false;
+    }
+    let content_hash = hex::encode(Sha256::digest(contents));
+    vault.verify(
+        &signed_asset_value(identifier, &content_hash, expiry),
+        signature,
+    )
+}
+
+/// Removes the data of an asset, if any.
+pub fn remove_asset_data(connection: &mut SqliteConnection, identifier: &str) {
+    diesel::delete(declaration_assets::table.find(identifier))
+        .execute(connection)
+        .expect("error removing declaration asset");
+}
+
+/// Replaces the URLs of assets whose URL is close to expiring.
+/// Devices assigned these assets are asked to synchronize, fetching their new URL.
+pub fn renew_asset_urls(state: &AppState) {
+    let connection = &mut state.database.connection();
+    let renewal_cutoff = OffsetDateTime::now_utc() - (ASSET_URL_LIFETIME - ASSET_URL_RENEWAL);
+    let expiring_assets = declaration_assets::table
+        .filter(declaration_assets::update_date.lt(renewal_cutoff))
+        .inner_join(declarations::table)
+        .select(StoredDeclaration::as_select())
+        .load::<StoredDeclaration>(connection)
+        .expect("can query declaration assets");
+
+    for stored in expiring_assets {
+        let mut declaration = Declaration::from_stored(&stored);
+        let Some(content_hash) = declaration.payload["Reference"]["Hash-SHA-256"]
+            .as_str()
+            .map(str::to_string)
+        else {
+            continue;
+        };
+
+        let update_date = OffsetDateTime::now_utc();
+        declaration.payload["Reference"]["DataURL"] = json!(asset_url(
+            state,
+            &stored.identifier,
+            &content_hash,
+            update_date
+        ));
+        let declaration = Declaration::new(
+            declaration.identifier,
+            declaration.declaration_type,
+            declaration.payload,
+        );
+        println!("renewing URL of asset {}", declaration.identifier);
+        store_declaration(state, connection, &declaration);
+        diesel::update(declaration_assets::table.find(&declaration.identifier))
+            .set(declaration_assets::update_date.eq(update_date))
+            .execute(connection)
+            .expect("error updating declaration asset");
+    }
+}
+
+/// Periodically renews expiring asset URLs for as long as we're running.
+pub async fn schedule_asset_renewal(state: AppState) {
+    let mut interval = tokio::time::interval(RENEWAL_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || renew_asset_urls(&state))
+            .await
+            .expect("asset renewal should not panic");
+    }
+}
+
+/// The URL devices fetch an asset's data from, valid for a limited time after it was issued.
+///
+/// Requests for this URL are otherwise unauthenticated, so it is the only secret
+/// protecting an asset's data. It cannot be guessed, and is no longer valid once it expires.
+fn asset_url(
+    state: &AppState,
+    identifier: &str,
+    content_hash: &str,
+    issue_date: OffsetDateTime,
+) -> String {
+    let expiry = (issue_date + ASSET_URL_LIFETIME).unix_timestamp();
+    let signature = state
+        .vault
+        .sign(&signed_asset_value(identifier, content_hash, expiry));
+    format!(
+        "https://{}/mdm/assets/{identifier}/{expiry}/{signature}",
+        state.config.service.base_domain
+    )
+}
+
+fn signed_asset_value(identifier: &str, content_hash: &str, expiry: i64) -> String {
+    format!("asset-url:{identifier}:{content_hash}:{expiry}")
+}
+
+fn vault_context(identifier: &str) -> String {
+    format!("declaration-asset:{identifier}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENTS: &[u8] = b"-----BEGIN CERTIFICATE-----";
+
+    fn signature(vault: &Vault, identifier: &str, contents: &[u8], expiry: i64) -> String {
+        let content_hash = hex::encode(Sha256::digest(contents));
+        vault.sign(&signed_asset_value(identifier, &content_hash, expiry))
+    }
+
+    #[test]
+    fn accepts_unexpired_signatures() {
+        let vault = Vault::from_key(&[7; 32]);
+        let expiry = (OffsetDateTime::now_utc() + ASSET_URL_LIFETIME).unix_timestamp();
+        let signature = signature(&vault, "com.example.root", CONTENTS, expiry);
+        assert!(verify_asset_signature(
+            &vault,
+            "com.example.root",
+            CONTENTS,
+            expiry,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn rejects_expired_signatures() {
+        let vault = Vault::from_key(&[7; 32]);
+        let expiry = OffsetDateTime::now_utc().unix_timestamp() - 1;
+        let signature = signature(&vault, "com.example.root", CONTENTS, expiry);
+        assert!(!verify_asset_signature(
+            &vault,
+            "com.example.root",
+            CONTENTS,
+            expiry,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn rejects_signatures_for_other_values() {
+        let vault = Vault::from_key(&[7; 32]);
+        let expiry = (OffsetDateTime::now_utc() + ASSET_URL_LIFETIME).unix_timestamp();
+        let signature = signature(&vault, "com.example.root", CONTENTS, expiry);
+        // Other assets, changed data, and extended expiry dates are all rejected.
+        let verify = |identifier: &str, contents: &[u8], expiry: i64| {
+            verify_asset_signature(&vault, identifier, contents, expiry, &signature)
+        };
+        assert!(!verify("com.example.other", CONTENTS, expiry));
+        assert!(!verify("com.example.root", b"modified", expiry));
+        assert!(!verify("com.example.root", CONTENTS, expiry + 1));
+        // As are signatures from another key.
+        let other_vault = Vault::from_key(&[8; 32]);
+        assert!(!verify_asset_signature(
+            &other_vault,
+            "com.example.root",
+            CONTENTS,
+            expiry,
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::database::StoredDeclaration;
+
+#[derive(Clone, Copy, PartialEq)]
+/// The kinds of declarations, as determined by their type.
+/// https://developer.apple.com/documentation/devicemanagement/declarations
+pub enum DeclarationCategory {
+    /// Applies configurations and assets to the device, such as upon a predicate.
+    Activation,
+    /// Data referenced by configurations, such as credentials.
+    Asset,
+    /// Policy for the device, akin to a payload within a profile.
+    Configuration,
+    /// Information about the management server itself.
+    Management,
+}
+
+impl DeclarationCategory {
+    const ALL: [DeclarationCategory; 4] = [
+        DeclarationCategory::Activation,
+        DeclarationCategory::Asset,
+        DeclarationCategory::Configuration,
+        DeclarationCategory::Management,
+    ];
+
+    /// How this category is referred to within declaration endpoints and types.
+    /// For example, the "configuration" within "com.apple.configuration.passcode.settings".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeclarationCategory::Activation => "activation",
+            DeclarationCategory::Asset => "asset",
+            DeclarationCategory::Configuration => "configuration",
+            DeclarationCategory::Management => "management",
+        }
+    }
+
+    pub fn parse(category: &str) -> Option<Self> {
+        DeclarationCategory::ALL
+            .into_iter()
+            .find(|known| known.as_str() == category)
+    }
+
+    /// The category of the given declaration type.
+    pub fn for_type(declaration_type: &str) -> Option<Self> {
+        let category = declaration_type
+            .strip_prefix("com.apple.")?
+            .split('.')
+            .next()?;
+        DeclarationCategory::parse(category)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+/// A declaration, as served to devices.
+/// https://developer.apple.com/documentation/devicemanagement/declarationbase
+pub struct Declaration {
+    #[serde(rename = "Type")]
+    /// For example, "com.apple.configuration.passcode.settings".
+    pub declaration_type: String,
+    #[serde(rename = "Identifier")]
+    pub identifier: String,
+    #[serde(rename = "ServerToken")]
+    /// Changes whenever this declaration's contents do, prompting devices to fetch it again.
+    pub server_token: String,
+    #[serde(rename = "Payload")]
+    pub payload: Value,
+}
+
+impl Declaration {
+    /// Creates a declaration whose server token is derived from its contents.
+    pub fn new(identifier: String, declaration_type: String, payload: Value) -> Self {
+        // serde_json orders object keys, so equal contents are always hashed identically.
+        let contents = json!({
+            "Identifier": identifier,
+            "Payload": payload,
+            "Type": declaration_type,
+        });
+        let server_token = hex::encode(Sha256::digest(contents.to_string()));
+        Declaration {
+            declaration_type,
+            identifier,
+            server_token,
+            payload,
+        }
+    }
+
+    /// Creates an activation unconditionally applying the given configurations.
+    /// https://developer.apple.com/documentation/devicemanagement/activationsimple
+    pub fn simple_activation(identifier: String, configurations: Vec<String>) -> Self {
+        Declaration::new(
+            identifier,
+            "com.apple.activation.simple".to_string(),
+            json!({ "StandardConfigurations": configurations }),
+        )
+    }
+
+    pub fn from_stored(stored: &StoredDeclaration) -> Self {
+        Declaration {
+            declaration_type: stored.declaration_type.clone(),
+            identifier: stored.identifier.clone(),
+            server_token: stored.server_token.clone(),
+            payload: serde_json::from_str(&stored.payload)
+                .expect("stored declaration payloads should be valid JSON"),
+        }
+    }
+
+    pub fn category(&self) -> Option<DeclarationCategory> {
+        DeclarationCategory::for_type(&self.declaration_type)
+    }
+}
+
+/// Describes every reference within the given declarations to a declaration not among them,
+/// such as an activation's configurations or a configuration's assets.
+/// Devices consider declarations with unresolved references to be invalid.
+pub fn unresolved_references(declarations: &[Declaration]) -> Vec<String> {
+    let mut warnings = vec![];
+    for declaration in declarations {
+        let mut references = vec![];
+        collect_references(&declaration.payload, &mut references);
+        for reference in references {
+            if !declarations
+                .iter()
+                .any(|other| other.identifier == reference)
+            {
+                warnings.push(format!(
+                    "{}: declaration {reference} is not present",
+                    declaration.identifier
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Collects the identifiers referenced within a payload.
+/// Activations list configurations within StandardConfigurations,
+/// whereas configurations name assets via keys such as "AuthenticationCredentialsAssetReference".
+fn collect_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(reference) if key.ends_with("AssetReference") => {
+                        references.push(reference.clone())
+                    }
+                    Value::Array(items) if key == "StandardConfigurations" => references
+                        .extend(items.iter().filter_map(Value::as_str).map(str::to_string)),
+                    _ => collect_references(value, references),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_references(item, references);
+            }
+        }
+        _ => {}
+    }
+}
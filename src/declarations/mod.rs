@@ -0,0 +1,11 @@
+mod asset;
+mod declaration;
+mod software_update;
+mod status;
+mod sync;
+
+pub use asset::*;
+pub use declaration::*;
+pub use software_update::*;
+pub use status::*;
+pub use sync::*;
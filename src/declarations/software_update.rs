@@ -0,0 +1,215 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use time::{Duration, OffsetDateTime, PrimitiveDateTime, format_description};
+
+use crate::database::{Device, StoredDeclaration, declarations, device_statuses, devices};
+
+use super::{Declaration, assigned_devices};
+
+pub const ENFORCEMENT_TYPE: &str = "com.apple.configuration.softwareupdate.enforcement.specific";
+pub const SETTINGS_TYPE: &str = "com.apple.configuration.softwareupdate.settings";
+
+/// Status items describing a device's software update progress.
+/// Devices must be subscribed to these for their compliance to be known.
+pub const UPDATE_STATUS_ITEMS: [&str; 5] = [
+    "device.operating-system.build-version",
+    "device.operating-system.version",
+    "softwareupdate.failure-reason",
+    "softwareupdate.install-state",
+    "softwareupdate.pending-version",
+];
+
+#[derive(Serialize, Deserialize)]
+/// Requires a specific OS version to be installed by a deadline.
+/// https://developer.apple.com/documentation/devicemanagement/softwareupdateenforcementspecific
+pub struct SoftwareUpdateEnforcement {
+    #[serde(rename = "TargetOSVersion")]
+    /// For example, "18.1".
+    pub target_os_version: String,
+    #[serde(rename = "TargetBuildVersion", skip_serializing_if = "Option::is_none")]
+    /// For example, "22B83". Required to target Rapid Security Responses.
+    pub target_build_version: Option<String>,
+    #[serde(rename = "TargetLocalDateTime")]
+    /// The deadline, within the device's local time zone.
+    /// For example, "2026-11-01T18:00:00".
+    pub target_local_date_time: String,
+    #[serde(rename = "DetailsURL", skip_serializing_if = "Option::is_none")]
+    /// Shown to users alongside the update.
+    pub details_url: Option<String>,
+}
+
+impl SoftwareUpdateEnforcement {
+    /// The deadline for this update, as a local date and time.
+    pub fn deadline(&self) -> Result<PrimitiveDateTime, String> {
+        let format = format_description::parse("[year]-[month]-[day]T[hour]:[minute]:[second]")
+            .expect("deadline format should be valid");
+        PrimitiveDateTime::parse(&self.target_local_date_time, &format)
+            .map_err(|e| format!("invalid target date {}: {e}", self.target_local_date_time))
+    }
+
+    pub fn into_declaration(self, identifier: String) -> Result<Declaration, String> {
+        self.deadline()?;
+        if parse_version(&self.target_os_version).is_none() {
+            return Err(format!("invalid OS version {}", self.target_os_version));
+        }
+        let payload = serde_json::to_value(self).expect("enforcement should serialize");
+        Ok(Declaration::new(
+            identifier,
+            ENFORCEMENT_TYPE.to_string(),
+            payload,
+        ))
+    }
+}
+
+#[derive(Serialize)]
+/// How software updates are presented to users, and how long they may be deferred.
+/// https://developer.apple.com/documentation/devicemanagement/softwareupdatesettings
+pub struct SoftwareUpdateSettings {
+    #[serde(rename = "Notifications", skip_serializing_if = "Option::is_none")]
+    /// Whether users are notified of upcoming enforced updates.
+    pub notifications: Option<bool>,
+    #[serde(rename = "Deferrals", skip_serializing_if = "Option::is_none")]
+    pub deferrals: Option<SoftwareUpdateDeferrals>,
+}
+
+#[derive(Serialize)]
+/// The number of days updates are hidden from users once released, between 1 and 90.
+/// Combined deferrals apply to all updates on devices without separate deferrals.
+pub struct SoftwareUpdateDeferrals {
+    #[serde(
+        rename = "CombinedPeriodInDays",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub combined_period_in_days: Option<u8>,
+    #[serde(rename = "MajorPeriodInDays", skip_serializing_if = "Option::is_none")]
+    pub major_period_in_days: Option<u8>,
+    #[serde(rename = "MinorPeriodInDays", skip_serializing_if = "Option::is_none")]
+    pub minor_period_in_days: Option<u8>,
+    #[serde(rename = "SystemPeriodInDays", skip_serializing_if = "Option::is_none")]
+    pub system_period_in_days: Option<u8>,
+}
+
+impl SoftwareUpdateSettings {
+    pub fn into_declaration(self, identifier: String) -> Result<Declaration, String> {
+        if let Some(deferrals) = &self.deferrals {
+            let periods = [
+                deferrals.combined_period_in_days,
+                deferrals.major_period_in_days,
+                deferrals.minor_period_in_days,
+                deferrals.system_period_in_days,
+            ];
+            if periods
+                .into_iter()
+                .flatten()
+                .any(|days| !(1..=90).contains(&days))
+            {
+                return Err("deferrals must be between 1 and 90 days".to_string());
+            }
+        }
+        let payload = serde_json::to_value(self).expect("settings should serialize");
+        Ok(Declaration::new(
+            identifier,
+            SETTINGS_TYPE.to_string(),
+            payload,
+        ))
+    }
+}
+
+#[derive(Serialize)]
+/// A device's progress towards an enforced update, per its latest status reports.
+pub struct UpdateCompliance {
+    pub udid: String,
+    pub os_version: String,
+    pub build_version: Option<String>,
+    /// For example, "downloading" or "installing".
+    pub install_state: Option<String>,
+    pub pending_version: Option<Value>,
+    pub failure_reason: Option<Value>,
+    /// Whether the device runs the targeted version or later.
+    pub up_to_date: bool,
+    /// Whether the deadline has passed without the device updating.
+    pub missed_deadline: bool,
+}
+
+/// Describes the compliance of every device assigned the given enforcement declaration.
+/// Returns None if no such valid declaration exists.
+pub fn update_compliance(
+    connection: &mut SqliteConnection,
+    identifier: &str,
+) -> Option<Vec<UpdateCompliance>> {
+    let stored = declarations::table
+        .find(identifier)
+        .first::<StoredDeclaration>(connection)
+        .optional()
+        .expect("can query declarations")
+        .filter(|stored| stored.declaration_type == ENFORCEMENT_TYPE)?;
+    // Declarations may also be created directly, so their contents may not be valid.
+    let enforcement: SoftwareUpdateEnforcement = serde_json::from_str(&stored.payload).ok()?;
+    let target_version = parse_version(&enforcement.target_os_version)?;
+
+    // The deadline is within each device's own time zone, which we do not know.
+    // Devices are only considered late once it has passed in every time zone (UTC-12).
+    let deadline = enforcement.deadline().ok()?.assume_utc() + Duration::hours(12);
+    let deadline_passed = OffsetDateTime::now_utc() > deadline;
+
+    let mut compliance = vec![];
+    for udid in assigned_devices(connection, identifier) {
+        let device = devices::table
+            .find(&udid)
+            .first::<Device>(connection)
+            .expect("can query devices");
+        let statuses: Vec<(String, String)> = device_statuses::table
+            .filter(device_statuses::udid.eq(&udid))
+            .filter(device_statuses::status_item.eq_any(UPDATE_STATUS_ITEMS))
+            .select((device_statuses::status_item, device_statuses::value))
+            .load(connection)
+            .expect("can query device statuses");
+        let status = |name: &str| {
+            statuses
+                .iter()
+                .find(|(status_item, _)| status_item == name)
+                .and_then(|(_, value)| serde_json::from_str::<Value>(value).ok())
+                .filter(|value| !value.is_null())
+        };
+        let status_string =
+            |name: &str| status(name).and_then(|value| value.as_str().map(str::to_string));
+
+        // Our inventory is updated from status reports, but may also be more recent.
+        let os_version =
+            status_string("device.operating-system.version").unwrap_or(device.device_version);
+        // Build versions cannot be meaningfully ordered, so only OS versions are compared.
+        let up_to_date = parse_version(&os_version)
+            .is_some_and(|version| compare_versions(&version, &target_version) != Ordering::Less);
+        compliance.push(UpdateCompliance {
+            udid,
+            build_version: status_string("device.operating-system.build-version"),
+            install_state: status_string("softwareupdate.install-state"),
+            pending_version: status("softwareupdate.pending-version"),
+            failure_reason: status("softwareupdate.failure-reason"),
+            os_version,
+            up_to_date,
+            missed_deadline: !up_to_date && deadline_passed,
+        });
+    }
+    Some(compliance)
+}
+
+/// Parses a version such as "18.1.2" into its components.
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version
+        .split('.')
+        .map(|component| component.parse().ok())
+        .collect()
+}
+
+/// Compares versions, treating absent components as zero (e.g. "18.1" is equal to "18.1.0").
+fn compare_versions(left: &[u32], right: &[u32]) -> Ordering {
+    let length = left.len().max(right.len());
+    let component = |version: &[u32], index: usize| version.get(index).copied().unwrap_or(0);
+    (0..length)
+        .map(|index| component(left, index).cmp(&component(right, index)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
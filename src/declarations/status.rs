@@ -0,0 +1,271 @@
+use diesel::prelude::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use time::OffsetDateTime;
+
+use crate::database::{
+    DeviceStatus, NewDeviceStatusChange, device_status_changes, device_statuses, devices,
+};
+
+use super::Declaration;
+
+/// Status items devices may report, and which may be subscribed to.
+/// https://developer.apple.com/documentation/devicemanagement/status-reports
+///
+/// Some items are objects themselves (such as "softwareupdate.pending-version"),
+/// so these are kept as a single value rather than separated into their keys.
+pub const STATUS_ITEMS: [&str; 30] = [
+    "account.list.caldav",
+    "account.list.carddav",
+    "account.list.exchange",
+    "account.list.google",
+    "account.list.ldap",
+    "account.list.mail.incoming",
+    "account.list.mail.outgoing",
+    "app.managed.list",
+    "device.identifier.serial-number",
+    "device.identifier.udid",
+    "device.model.family",
+    "device.model.identifier",
+    "device.model.marketing-name",
+    "device.model.number",
+    "device.operating-system.build-version",
+    "device.operating-system.family",
+    "device.operating-system.marketing-name",
+    "device.operating-system.supplemental.build-version",
+    "device.operating-system.supplemental.extra-version",
+    "device.operating-system.version",
+    "diskmanagement.filevault.enabled",
+    "management.client-capabilities",
+    "management.declarations",
+    "passcode.is-compliant",
+    "passcode.is-present",
+    "security.certificate.list",
+    "softwareupdate.failure-reason",
+    "softwareupdate.install-reason",
+    "softwareupdate.install-state",
+    "softwareupdate.pending-version",
+];
+
+#[derive(Deserialize)]
+/// A status report sent by a device, either in full or containing only changed items.
+/// https://developer.apple.com/documentation/devicemanagement/statusreport
+pub struct StatusReport {
+    #[serde(rename = "StatusItems")]
+    /// Status items, nested by their path. For example, "passcode.is-compliant"
+    /// is reported as `{"passcode": {"is-compliant": true}}`.
+    pub status_items: Value,
+    #[serde(rename = "Errors", default)]
+    pub errors: Vec<StatusError>,
+    #[serde(rename = "FullReport", default)]
+    pub full_report: bool,
+}
+
+#[derive(Deserialize)]
+/// A status item the device was unable to report.
+pub struct StatusError {
+    #[serde(rename = "StatusItem")]
+    pub status_item: String,
+    #[serde(rename = "Reasons", default)]
+    pub reasons: Vec<StatusReason>,
+}
+
+#[derive(Deserialize)]
+/// https://developer.apple.com/documentation/devicemanagement/statusreason
+pub struct StatusReason {
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Description")]
+    pub description: Option<String>,
+}
+
+impl StatusReason {
+    fn describe(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{} ({description})", self.code),
+            None => self.code.clone(),
+        }
+    }
+}
+
+/// Persists every status item within a report, alongside a history of their changes.
+pub fn record_status_report(connection: &mut SqliteConnection, udid: &str, report: StatusReport) {
+    for error in &report.errors {
+        let reasons: Vec<String> = error.reasons.iter().map(StatusReason::describe).collect();
+        println!(
+            "device {udid} is unable to report {} - {}",
+            error.status_item,
+            reasons.join(", ")
+        );
+    }
+
+    let mut items = vec![];
+    flatten_status_items("", report.status_items, &mut items);
+    let report_date = OffsetDateTime::now_utc();
+
+    // Full reports contain every item the device is subscribed to,
+    // so any items not present are no longer reported.
+    if report.full_report {
+        let stored_items = device_statuses::table
+            .filter(device_statuses::udid.eq(udid))
+            .select(device_statuses::status_item)
+            .load::<String>(connection)
+            .expect("can query device statuses");
+        for status_item in stored_items {
+            if items.iter().any(|(reported, _)| reported == &status_item) {
+                continue;
+            }
+            diesel::delete(device_statuses::table.find((udid, &status_item)))
+                .execute(connection)
+                .expect("error removing device status");
+            diesel::insert_into(device_status_changes::table)
+                .values(&NewDeviceStatusChange {
+                    udid: udid.to_string(),
+                    status_item,
+                    value: None,
+                    report_date,
+                })
+                .execute(connection)
+                .expect("error persisting device status change");
+        }
+    }
+
+    for (status_item, value) in items {
+        let previous = device_statuses::table
+            .find((udid, &status_item))
+            .first::<DeviceStatus>(connection)
+            .optional()
+            .expect("can query device statuses")
+            .and_then(|previous| serde_json::from_str::<Value>(&previous.value).ok());
+
+        // Lists (such as "app.managed.list") only contain changed entries,
+        // unless this is a full report.
+        let value = match (previous.as_ref(), value) {
+            (Some(Value::Array(previous)), Value::Array(changes)) if !report.full_report => {
+                Value::Array(merge_list_changes(previous, changes))
+            }
+            (_, value) => value,
+        };
+        if previous.as_ref() == Some(&value) {
+            continue;
+        }
+
+        handle_status_change(connection, udid, &status_item, &value);
+        let status = DeviceStatus {
+            udid: udid.to_string(),
+            status_item: status_item.clone(),
+            value: value.to_string(),
+            update_date: report_date,
+        };
+        diesel::insert_into(device_statuses::table)
+            .values(&status)
+            .on_conflict((device_statuses::udid, device_statuses::status_item))
+            .do_update()
+            .set(&status)
+            .execute(connection)
+            .expect("error persisting device status");
+        diesel::insert_into(device_status_changes::table)
+            .values(&NewDeviceStatusChange {
+                udid: udid.to_string(),
+                status_item,
+                value: Some(status.value),
+                report_date,
+            })
+            .execute(connection)
+            .expect("error persisting device status change");
+    }
+}
+
+/// Separates nested status items into their full path and value.
+fn flatten_status_items(path: &str, value: Value, items: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(object) if !STATUS_ITEMS.contains(&path) => {
+            for (key, value) in object {
+                let item_path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten_status_items(&item_path, value, items);
+            }
+        }
+        value => items.push((path.to_string(), value)),
+    }
+}
+
+/// Applies changes to a list, whose entries are keyed by their "identifier".
+/// Removed entries are marked via "_removed".
+fn merge_list_changes(previous: &[Value], changes: Vec<Value>) -> Vec<Value> {
+    let identifier = |entry: &Value| entry.get("identifier").cloned();
+    let mut merged = previous.to_vec();
+    for change in changes {
+        merged.retain(|entry| {
+            identifier(entry).is_none() || identifier(entry) != identifier(&change)
+        });
+        if change.get("_removed") != Some(&Value::Bool(true)) {
+            merged.push(change);
+        }
+    }
+    merged
+}
+
+/// Reflects changed status items within our device inventory,
+/// and notes declarations the device considers invalid.
+fn handle_status_change(
+    connection: &mut SqliteConnection,
+    udid: &str,
+    status_item: &str,
+    value: &Value,
+) {
+    match (status_item, value) {
+        ("device.operating-system.version", Value::String(version)) => {
+            diesel::update(devices::table.find(udid))
+                .set(devices::device_version.eq(version))
+                .execute(connection)
+                .expect("error updating device");
+        }
+        ("management.declarations", Value::Object(declarations)) => {
+            let statuses = declarations.values().filter_map(Value::as_array).flatten();
+            for status in statuses {
+                if status.get("valid").and_then(Value::as_str) != Some("invalid") {
+                    continue;
+                }
+                let identifier = status
+                    .get("identifier")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let reasons = status.get("reasons").cloned().unwrap_or_default();
+                println!("device {udid} considers declaration {identifier} invalid - {reasons}");
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Creates a configuration subscribing devices to the given status items.
+/// https://developer.apple.com/documentation/devicemanagement/managementstatussubscriptions
+///
+/// Returns an error naming any unknown status items.
+pub fn status_subscriptions(
+    identifier: String,
+    status_items: &[String],
+) -> Result<Declaration, String> {
+    let unknown: Vec<&str> = status_items
+        .iter()
+        .map(String::as_str)
+        .filter(|status_item| !STATUS_ITEMS.contains(status_item))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("unknown status items: {}", unknown.join(", ")));
+    }
+
+    let subscriptions: Vec<Value> = status_items
+        .iter()
+        .map(|status_item| json!({ "Name": status_item }))
+        .collect();
+    Ok(Declaration::new(
+        identifier,
+        "com.apple.configuration.management.status-subscriptions".to_string(),
+        json!({ "StatusItems": subscriptions }),
+    ))
+}
@@ -0,0 +1,256 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::app_state::AppState;
+use crate::commands::{Command, DeclarativeManagement};
+use crate::database::{
+    Device, DeviceDeclaration, StoredDeclaration, declarations, device_declarations, devices,
+};
+
+use super::{Declaration, DeclarationCategory, remove_asset_data};
+
+#[derive(Serialize, Deserialize)]
+/// Identifies the current set of declarations for a device.
+/// https://developer.apple.com/documentation/devicemanagement/synchronizationtokens
+pub struct SyncTokens {
+    #[serde(rename = "DeclarationsToken")]
+    /// Changes whenever any of the device's declarations do.
+    pub declarations_token: String,
+    #[serde(rename = "Timestamp", with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+/// Our response to the "tokens" endpoint.
+/// https://developer.apple.com/documentation/devicemanagement/tokensresponse
+pub struct TokensResponse {
+    #[serde(rename = "SyncTokens")]
+    pub sync_tokens: SyncTokens,
+}
+
+#[derive(Serialize)]
+/// Our response to the "declaration-items" endpoint, listing all of a device's declarations.
+/// https://developer.apple.com/documentation/devicemanagement/declarationitemsresponse
+pub struct DeclarationItems {
+    #[serde(rename = "Declarations")]
+    pub declarations: ManifestDeclarations,
+    #[serde(rename = "DeclarationsToken")]
+    pub declarations_token: String,
+}
+
+#[derive(Default, Serialize)]
+pub struct ManifestDeclarations {
+    #[serde(rename = "Activations")]
+    pub activations: Vec<ManifestItem>,
+    #[serde(rename = "Assets")]
+    pub assets: Vec<ManifestItem>,
+    #[serde(rename = "Configurations")]
+    pub configurations: Vec<ManifestItem>,
+    #[serde(rename = "Management")]
+    pub management: Vec<ManifestItem>,
+}
+
+#[derive(Serialize)]
+pub struct ManifestItem {
+    #[serde(rename = "Identifier")]
+    pub identifier: String,
+    #[serde(rename = "ServerToken")]
+    pub server_token: String,
+}
+
+/// Creates or replaces a declaration, synchronizing devices it is assigned to
+/// if its contents have changed.
+pub fn store_declaration(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    declaration: &Declaration,
+) {
+    let previous_token = declarations::table
+        .find(&declaration.identifier)
+        .select(declarations::server_token)
+        .first::<String>(connection)
+        .optional()
+        .expect("can query declarations");
+    if previous_token.as_ref() == Some(&declaration.server_token) {
+        return;
+    }
+
+    let stored = StoredDeclaration {
+        identifier: declaration.identifier.clone(),
+        declaration_type: declaration.declaration_type.clone(),
+        payload: declaration.payload.to_string(),
+        server_token: declaration.server_token.clone(),
+        update_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(declarations::table)
+        .values(&stored)
+        .on_conflict(declarations::identifier)
+        .do_update()
+        .set(&stored)
+        .execute(connection)
+        .expect("error persisting declaration");
+
+    for udid in assigned_devices(connection, &declaration.identifier) {
+        request_sync(state, connection, &udid);
+    }
+}
+
+/// Removes a declaration from all devices, and then entirely.
+/// Returns false if no such declaration exists.
+pub fn remove_declaration(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    identifier: &str,
+) -> bool {
+    let udids = assigned_devices(connection, identifier);
+    diesel::delete(
+        device_declarations::table.filter(device_declarations::identifier.eq(identifier)),
+    )
+    .execute(connection)
+    .expect("error removing device declarations");
+    remove_asset_data(connection, identifier);
+    let removed_count = diesel::delete(declarations::table.find(identifier))
+        .execute(connection)
+        .expect("error removing declaration");
+
+    for udid in udids {
+        touch_device(connection, &udid);
+        request_sync(state, connection, &udid);
+    }
+    removed_count > 0
+}
+
+/// Replaces the declarations assigned to a device, and asks it to synchronize.
+/// This also enables Declarative Device Management on the device, if it was not already.
+pub fn assign_declarations(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    identifiers: &[String],
+) -> Uuid {
+    diesel::delete(device_declarations::table.filter(device_declarations::udid.eq(udid)))
+        .execute(connection)
+        .expect("error removing device declarations");
+    let assignments: Vec<DeviceDeclaration> = identifiers
+        .iter()
+        .map(|identifier| DeviceDeclaration {
+            udid: udid.to_string(),
+            identifier: identifier.clone(),
+        })
+        .collect();
+    diesel::insert_into(device_declarations::table)
+        .values(&assignments)
+        .execute(connection)
+        .expect("error persisting device declarations");
+
+    touch_device(connection, udid);
+    request_sync(state, connection, udid)
+}
+
+/// Notes that the set of declarations assigned to this device has changed.
+fn touch_device(connection: &mut SqliteConnection, udid: &str) {
+    diesel::update(devices::table.find(udid))
+        .set(devices::declarations_update_date.eq(OffsetDateTime::now_utc()))
+        .execute(connection)
+        .expect("error updating device");
+}
+
+/// The UDIDs of all devices the given declaration is assigned to.
+pub fn assigned_devices(connection: &mut SqliteConnection, identifier: &str) -> Vec<String> {
+    device_declarations::table
+        .filter(device_declarations::identifier.eq(identifier))
+        .select(device_declarations::udid)
+        .load::<String>(connection)
+        .expect("can query device declarations")
+}
+
+/// All declarations assigned to the given device, ordered by their identifier.
+pub fn device_declarations(
+    connection: &mut SqliteConnection,
+    udid: &str,
+) -> Vec<StoredDeclaration> {
+    device_declarations::table
+        .inner_join(declarations::table)
+        .filter(device_declarations::udid.eq(udid))
+        .select(StoredDeclaration::as_select())
+        .order(declarations::identifier.asc())
+        .load::<StoredDeclaration>(connection)
+        .expect("can query device declarations")
+}
+
+/// The synchronization tokens for a device's current declarations.
+pub fn sync_tokens(connection: &mut SqliteConnection, device: &Device) -> SyncTokens {
+    let declarations = device_declarations(connection, &device.udid);
+    let timestamp = declarations
+        .iter()
+        .map(|declaration| declaration.update_date)
+        .chain(device.declarations_update_date)
+        .max()
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+        .replace_nanosecond(0)
+        .expect("zero is a valid nanosecond");
+    SyncTokens {
+        declarations_token: declarations_token(&declarations),
+        timestamp,
+    }
+}
+
+/// A device's declarations, by category.
+pub fn declaration_items(connection: &mut SqliteConnection, udid: &str) -> DeclarationItems {
+    let declarations = device_declarations(connection, udid);
+    let mut manifest = ManifestDeclarations::default();
+    for declaration in &declarations {
+        let item = ManifestItem {
+            identifier: declaration.identifier.clone(),
+            server_token: declaration.server_token.clone(),
+        };
+        match DeclarationCategory::for_type(&declaration.declaration_type) {
+            Some(DeclarationCategory::Activation) => manifest.activations.push(item),
+            Some(DeclarationCategory::Asset) => manifest.assets.push(item),
+            Some(DeclarationCategory::Configuration) => manifest.configurations.push(item),
+            Some(DeclarationCategory::Management) => manifest.management.push(item),
+            None => println!(
+                "declaration {} has unknown type {}",
+                declaration.identifier, declaration.declaration_type
+            ),
+        }
+    }
+    DeclarationItems {
+        declarations: manifest,
+        declarations_token: declarations_token(&declarations),
+    }
+}
+
+/// Derived from the identifiers and server tokens of every declaration,
+/// and thus changes whenever any are added, removed or modified.
+fn declarations_token(declarations: &[StoredDeclaration]) -> String {
+    let mut hasher = Sha256::new();
+    for declaration in declarations {
+        hasher.update(declaration.identifier.as_bytes());
+        hasher.update(b":");
+        hasher.update(declaration.server_token.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Asks a device to synchronize its declarations, given our current tokens.
+/// If the device has not yet enabled Declarative Device Management, this enables it.
+pub fn request_sync(state: &AppState, connection: &mut SqliteConnection, udid: &str) -> Uuid {
+    let device = devices::table
+        .find(udid)
+        .first::<Device>(connection)
+        .expect("can query devices");
+    let tokens = TokensResponse {
+        sync_tokens: sync_tokens(connection, &device),
+    };
+    let data = serde_json::to_vec(&tokens).expect("should be able to serialize tokens");
+    Command::DeclarativeManagement(DeclarativeManagement {
+        data: Some(ByteBuf::from(data)),
+    })
+    .enqueue(state, connection, udid)
+}
@@ -0,0 +1,208 @@
+use crate::app_state::AppState;
+use crate::database::{ActivationLockBypassCode, activation_lock_bypass_codes};
+use diesel::prelude::*;
+use pbkdf2::pbkdf2_hmac_array;
+use sha2::Sha256;
+use time::OffsetDateTime;
+
+/// The symbols used to encode a bypass code, in order.
+/// Notably, this omits easily confused characters such as B, I, O and S.
+const SYMBOLS: &[u8; 32] = b"0123456789ACDEFGHJKLMNPQRTUVWXYZ";
+
+/// Bypass codes consist of 128 bits, represented with 5 bits per symbol.
+const RAW_LENGTH: usize = 16;
+const SYMBOL_COUNT: usize = 26;
+
+/// A dash is placed after every five symbols.
+const GROUP_LENGTH: usize = 5;
+
+/// Parameters for deriving the hash given to Apple.
+const HASH_SALT: [u8; 4] = [0; 4];
+const HASH_ITERATIONS: u32 = 50_000;
+
+/// An Activation Lock bypass code.
+///
+/// Apple documents its format at:
+/// https://developer.apple.com/documentation/devicemanagement/device_assignment/activation_lock_a_device/creating_and_using_bypass_codes
+pub struct BypassCode {
+    raw: [u8; RAW_LENGTH],
+}
+
+impl BypassCode {
+    /// Generates a new random bypass code.
+    pub fn generate() -> Self {
+        BypassCode {
+            raw: rand::random(),
+        }
+    }
+
+    /// Parses a human-readable code, such as one reported by a device
+    /// via the ActivationLockBypassCode query.
+    /// Dashes are ignored, and symbols are accepted regardless of case.
+    pub fn parse(code: &str) -> Option<Self> {
+        let symbols: Vec<u8> = code
+            .bytes()
+            .filter(|symbol| *symbol != b'-')
+            .map(|symbol| symbol.to_ascii_uppercase())
+            .collect();
+        if symbols.len() != SYMBOL_COUNT {
+            return None;
+        }
+
+        // Read every symbol's 5 bits into our 128-bit value.
+        // Our final symbol only represents the remaining 3 bits.
+        let mut value: u128 = 0;
+        for (index, symbol) in symbols.iter().enumerate() {
+            let bits = SYMBOLS.iter().position(|known| known == symbol)? as u128;
+            if index == SYMBOL_COUNT - 1 {
+                if bits & 0b11 != 0 {
+                    return None;
+                }
+                value = (value << 3) | (bits >> 2);
+            } else {
+                value = (value << 5) | bits;
+            }
+        }
+
+        Some(BypassCode {
+            raw: value.to_be_bytes(),
+        })
+    }
+
+    /// The code to present to a user, formatted as `XXXXX-XXXXX-XXXXX-XXXXX-XXXXX-X`.
+    pub fn code(&self) -> String {
+        let value = u128::from_be_bytes(self.raw);
+
+        let mut code = String::new();
+        for index in 0..SYMBOL_COUNT {
+            if index != 0 && index % GROUP_LENGTH == 0 {
+                code.push('-');
+            }
+
+            // Our last symbol only has 3 bits available,
+            // which are padded to the left of 5.
+            let bits = if index == SYMBOL_COUNT - 1 {
+                (value & 0b111) << 2
+            } else {
+                (value >> (128 - 5 * (index + 1))) & 0b11111
+            };
+            code.push(SYMBOLS[bits as usize] as char);
+        }
+        code
+    }
+
+    /// The hash of this code, as sent to Apple or within enrollment.
+    /// It's derived via PBKDF2-SHA256 from the raw bytes of this code,
+    /// and is represented in hexadecimal form.
+    pub fn hash(&self) -> String {
+        let derived = pbkdf2_hmac_array::<Sha256, 32>(&self.raw, &HASH_SALT, HASH_ITERATIONS);
+        hex::encode(derived)
+    }
+}
+
+/// Our generated code, whose hash may be given to Apple.
+pub const SOURCE_SERVER: &str = "server";
+/// A code generated and reported by the device itself.
+pub const SOURCE_DEVICE: &str = "device";
+
+/// The context bypass codes are sealed under within our vault.
+fn vault_context(udid: &str, source: &str) -> String {
+    format!("activation-lock-bypass-code:{udid}:{source}")
+}
+
+/// Persists the given bypass code for a device, replacing any from the same source.
+pub fn escrow_code(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    code_source: &str,
+    code: &BypassCode,
+) {
+    let record = ActivationLockBypassCode {
+        udid: device_udid.to_string(),
+        source: code_source.to_string(),
+        sealed_code: state
+            .vault
+            .seal_string(&vault_context(device_udid, code_source), &code.code()),
+        code_hash: code.hash(),
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::replace_into(activation_lock_bypass_codes::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting bypass code");
+}
+
+/// Generates a bypass code for a newly enrolled device.
+/// If we've already generated one (i.e. upon re-enrollment), it is kept as-is,
+/// as its hash may have already been given to Apple.
+pub fn escrow_server_code(state: &AppState, connection: &mut SqliteConnection, device_udid: &str) {
+    let existing = activation_lock_bypass_codes::table
+        .find((device_udid, SOURCE_SERVER))
+        .first::<ActivationLockBypassCode>(connection)
+        .optional()
+        .expect("can query bypass codes");
+    if existing.is_none() {
+        escrow_code(
+            state,
+            connection,
+            device_udid,
+            SOURCE_SERVER,
+            &BypassCode::generate(),
+        );
+    }
+}
+
+/// Decrypts an escrowed bypass code.
+pub fn open_code(state: &AppState, record: &ActivationLockBypassCode) -> Option<String> {
+    state.vault.open_string(
+        &vault_context(&record.udid, &record.source),
+        &record.sealed_code,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: [u8; RAW_LENGTH] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    #[test]
+    fn code_round_trips() {
+        let code = BypassCode { raw: RAW }.code();
+        assert_eq!(code, "000H4-0R40M-30F20-9185H-R38F1-W");
+
+        let parsed = BypassCode::parse(&code).expect("code should parse");
+        assert_eq!(parsed.raw, RAW);
+
+        let generated = BypassCode::generate();
+        let parsed = BypassCode::parse(&generated.code()).expect("code should parse");
+        assert_eq!(parsed.raw, generated.raw);
+    }
+
+    #[test]
+    fn parse_ignores_dashes_and_case() {
+        let parsed = BypassCode::parse("000h40r40m30f209185hr38f1w").expect("code should parse");
+        assert_eq!(parsed.raw, RAW);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_codes() {
+        // Too short, and an omitted symbol.
+        assert!(BypassCode::parse("000H4-0R40M-30F20-9185H-R38F1").is_none());
+        assert!(BypassCode::parse("000H4-0R40M-30F20-9185H-R38F1-B").is_none());
+        // Our final symbol may only represent 3 bits.
+        assert!(BypassCode::parse("000H4-0R40M-30F20-9185H-R38F1-X").is_none());
+    }
+
+    #[test]
+    fn hash_matches_known_vector() {
+        assert_eq!(
+            BypassCode { raw: RAW }.hash(),
+            "c5ced1d0c51459c1a887866a5868dd3e9e6f4fc4a8c244f3f9eb9383c0f8aecb"
+        );
+    }
+}
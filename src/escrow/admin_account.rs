@@ -0,0 +1,124 @@
+use crate::app_state::AppState;
+use crate::commands::{
+    self, AccountConfiguration, AutoSetupAdminAccount, Command, DeviceConfigured, DeviceInformation,
+};
+use crate::database::devices;
+use crate::plist::Plist;
+use diesel::prelude::*;
+use pbkdf2::pbkdf2_hmac;
+use serde::Serialize;
+use sha2::Sha512;
+
+use super::device_passwords::{self, PasswordKind};
+
+/// Parameters for deriving password hashes, akin to those macOS creates itself.
+const HASH_SALT_LENGTH: usize = 32;
+const HASH_ENTROPY_LENGTH: usize = 128;
+const HASH_ITERATIONS: u32 = 40_000;
+
+#[derive(Serialize)]
+/// The format of a password hash, as stored within a user's ShadowHashData.
+struct ShadowHashData {
+    #[serde(rename = "SALTED-SHA512-PBKDF2")]
+    salted_sha512_pbkdf2: SaltedSha512Pbkdf2,
+}
+
+#[derive(Serialize)]
+struct SaltedSha512Pbkdf2 {
+    #[serde(with = "serde_bytes")]
+    entropy: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+/// Derives the passwordHash expected by AccountConfiguration and SetAutoAdminPassword.
+/// The device never receives the password itself.
+pub fn admin_password_hash(password: &str) -> Vec<u8> {
+    let salt: [u8; HASH_SALT_LENGTH] = rand::random();
+    let mut entropy = vec![0; HASH_ENTROPY_LENGTH];
+    pbkdf2_hmac::<Sha512>(password.as_bytes(), &salt, HASH_ITERATIONS, &mut entropy);
+
+    let hash_data = ShadowHashData {
+        salted_sha512_pbkdf2: SaltedSha512Pbkdf2 {
+            entropy,
+            salt: salt.to_vec(),
+            iterations: HASH_ITERATIONS,
+        },
+    };
+    Plist(hash_data)
+        .to_xml()
+        .expect("should be able to serialize password hash")
+}
+
+/// Creates our managed administrator account on a Mac awaiting configuration,
+/// and then allows it to continue through Setup Assistant.
+///
+/// The account is only created if one is configured,
+/// and we have not previously generated a password for it.
+pub fn configure_admin_account(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+) {
+    let password_config = &state.config.passwords;
+    let kind = PasswordKind::AdminAccount;
+    if let Some(short_name) = &password_config.admin_account_name
+        && device_passwords::active_password(connection, device_udid, kind).is_none()
+        && !device_passwords::has_pending_password(connection, device_udid, kind)
+    {
+        let password = device_passwords::generate_password();
+        let command_uuid = Command::AccountConfiguration(AccountConfiguration {
+            auto_setup_admin_accounts: Some(vec![AutoSetupAdminAccount {
+                short_name: short_name.clone(),
+                full_name: password_config.admin_account_full_name.clone(),
+                password_hash: admin_password_hash(&password),
+                hidden: Some(true),
+            }]),
+            skip_primary_setup_account_creation: None,
+            set_primary_setup_account_as_regular_user: None,
+        })
+        .enqueue(state, connection, device_udid);
+        device_passwords::record_pending_password(
+            state,
+            connection,
+            device_udid,
+            kind,
+            &command_uuid,
+            &password,
+        );
+    }
+
+    Command::DeviceConfigured(DeviceConfigured::default()).enqueue(state, connection, device_udid);
+}
+
+/// Asks the device for our administrator account's GUID, needed to rotate its password,
+/// if the account has been created and we do not yet know it.
+///
+/// Devices may not respond to this query immediately after creating the account,
+/// so this is attempted again upon token updates and retrieval until we have it.
+pub fn request_admin_account_guid(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+) {
+    let account_guid = devices::table
+        .find(device_udid)
+        .select(devices::admin_account_guid)
+        .first::<Option<String>>(connection)
+        .optional()
+        .expect("can query devices")
+        .flatten();
+    if account_guid.is_some()
+        || device_passwords::active_password(connection, device_udid, PasswordKind::AdminAccount)
+            .is_none()
+        || commands::is_pending(connection, device_udid, "DeviceInformation")
+    {
+        return;
+    }
+
+    Command::DeviceInformation(DeviceInformation {
+        queries: vec!["AutoSetupAdminAccounts".to_string()],
+    })
+    .enqueue(state, connection, device_udid);
+}
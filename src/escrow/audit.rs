@@ -0,0 +1,17 @@
+use crate::database::{NewSecretAccess, secret_accesses};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+/// Records that an escrowed secret was retrieved by an administrator.
+/// The kind should describe the secret, such as "filevault_recovery_key".
+pub fn record_access(connection: &mut SqliteConnection, device_udid: &str, secret_kind: &str) {
+    let record = NewSecretAccess {
+        udid: device_udid.to_string(),
+        secret_kind: secret_kind.to_string(),
+        access_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(secret_accesses::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting secret access");
+}
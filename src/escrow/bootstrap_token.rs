@@ -0,0 +1,57 @@
+use crate::app_state::AppState;
+use crate::database::{BootstrapToken, bootstrap_tokens};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+/// The context bootstrap tokens are sealed under within our vault.
+fn vault_context(udid: &str) -> String {
+    format!("bootstrap-token:{udid}")
+}
+
+/// Persists the bootstrap token escrowed by a Mac, replacing any prior.
+/// It is bound to the identity certificate the Mac escrowed it with.
+pub fn escrow_bootstrap_token(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    fingerprint: &str,
+    token: &[u8],
+) {
+    let record = BootstrapToken {
+        udid: udid.to_string(),
+        sealed_token: state.vault.seal(&vault_context(udid), token),
+        identity_fingerprint: fingerprint.to_string(),
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::replace_into(bootstrap_tokens::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting bootstrap token");
+}
+
+/// Removes the bootstrap token escrowed by a Mac, if any.
+pub fn remove_bootstrap_token(connection: &mut SqliteConnection, udid: &str) {
+    diesel::delete(bootstrap_tokens::table.find(udid))
+        .execute(connection)
+        .expect("error removing bootstrap token");
+}
+
+/// Decrypts the bootstrap token escrowed by the given device, if present.
+/// It is only provided to the same identity certificate that escrowed it.
+pub fn open_bootstrap_token(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    fingerprint: &str,
+) -> Option<Vec<u8>> {
+    let record = bootstrap_tokens::table
+        .find(udid)
+        .first::<BootstrapToken>(connection)
+        .optional()
+        .expect("can query bootstrap tokens")?;
+    if record.identity_fingerprint != fingerprint {
+        println!("refusing to provide bootstrap token for {udid} to a different identity");
+        return None;
+    }
+    state.vault.open(&vault_context(udid), &record.sealed_token)
+}
@@ -0,0 +1,352 @@
+use crate::app_state::AppState;
+use crate::commands::{
+    Command, SetAutoAdminPassword, SetFirmwarePassword, SetRecoveryLock, VerifyFirmwarePassword,
+    VerifyRecoveryLock,
+};
+use crate::database::{DevicePassword, NewDevicePassword, device_passwords, devices};
+use diesel::prelude::*;
+use rand::distr::{Alphanumeric, SampleString};
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// The length of passwords we generate.
+const PASSWORD_LENGTH: usize = 20;
+
+/// How often we check for passwords due to be rotated.
+const ROTATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// The lifecycle of a password we've generated.
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_ACTIVE: &str = "active";
+pub const STATUS_SUPERSEDED: &str = "superseded";
+pub const STATUS_FAILED: &str = "failed";
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+/// The kinds of passwords we manage on Macs.
+pub enum PasswordKind {
+    /// Recovery Lock, available on Macs with Apple silicon.
+    RecoveryLock,
+    /// The firmware password, available on Intel-based Macs.
+    Firmware,
+    /// Our managed administrator account, created via AccountConfiguration.
+    AdminAccount,
+}
+
+impl PasswordKind {
+    const ALL: [PasswordKind; 3] = [
+        PasswordKind::RecoveryLock,
+        PasswordKind::Firmware,
+        PasswordKind::AdminAccount,
+    ];
+
+    /// How this kind is referred to within our database and administrative endpoints.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PasswordKind::RecoveryLock => "recovery_lock",
+            PasswordKind::Firmware => "firmware",
+            PasswordKind::AdminAccount => "admin_account",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        PasswordKind::ALL
+            .into_iter()
+            .find(|known| known.as_str() == kind)
+    }
+
+    /// The kind of password applicable to a Mac, based on its architecture.
+    pub fn for_architecture(is_apple_silicon: bool) -> Self {
+        if is_apple_silicon {
+            PasswordKind::RecoveryLock
+        } else {
+            PasswordKind::Firmware
+        }
+    }
+}
+
+impl TryFrom<String> for PasswordKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        PasswordKind::parse(&value).ok_or_else(|| format!("unknown password kind {value}"))
+    }
+}
+
+/// The context passwords are sealed under within our vault.
+fn vault_context(udid: &str, command_uuid: &str) -> String {
+    format!("device-password:{udid}:{command_uuid}")
+}
+
+/// Generates a new random password, akin to our enrollment challenges.
+pub fn generate_password() -> String {
+    Alphanumeric.sample_string(&mut rand::rng(), PASSWORD_LENGTH)
+}
+
+/// The password of the given kind currently in effect on a device, if any.
+pub fn active_password(
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+) -> Option<DevicePassword> {
+    device_passwords::table
+        .filter(device_passwords::udid.eq(device_udid))
+        .filter(device_passwords::kind.eq(password_kind.as_str()))
+        .filter(device_passwords::status.eq(STATUS_ACTIVE))
+        .first::<DevicePassword>(connection)
+        .optional()
+        .expect("can query device passwords")
+}
+
+/// Whether a password of the given kind is waiting to be set on a device.
+pub fn has_pending_password(
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+) -> bool {
+    let pending_count = device_passwords::table
+        .filter(device_passwords::udid.eq(device_udid))
+        .filter(device_passwords::kind.eq(password_kind.as_str()))
+        .filter(device_passwords::status.eq(STATUS_PENDING))
+        .count()
+        .get_result::<i64>(connection)
+        .expect("can query device passwords");
+    pending_count > 0
+}
+
+/// Generates a new password for the device and queues a command to set it.
+/// If a password is currently active, it's used to authorize the change.
+///
+/// The new password remains pending until the device acknowledges it.
+/// Returns None if the password cannot currently be changed, such as
+/// when we do not yet know the GUID of our administrator account.
+pub fn rotate_password(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+) -> Option<Uuid> {
+    let current_password = active_password(connection, device_udid, password_kind)
+        .and_then(|record| open_password(state, &record));
+    let new_password = generate_password();
+
+    let command = match password_kind {
+        PasswordKind::RecoveryLock => Command::SetRecoveryLock(SetRecoveryLock {
+            current_password,
+            new_password: new_password.clone(),
+        }),
+        PasswordKind::Firmware => Command::SetFirmwarePassword(SetFirmwarePassword {
+            current_password,
+            new_password: new_password.clone(),
+            allow_oroms: None,
+        }),
+        PasswordKind::AdminAccount => {
+            let account_guid = devices::table
+                .find(device_udid)
+                .select(devices::admin_account_guid)
+                .first::<Option<String>>(connection)
+                .optional()
+                .expect("can query devices")
+                .flatten()?;
+            Command::SetAutoAdminPassword(SetAutoAdminPassword {
+                guid: account_guid,
+                password_hash: super::admin_password_hash(&new_password),
+            })
+        }
+    };
+    let command_uuid = command.enqueue(state, connection, device_udid);
+    record_pending_password(
+        state,
+        connection,
+        device_udid,
+        password_kind,
+        &command_uuid,
+        &new_password,
+    );
+
+    Some(command_uuid)
+}
+
+/// Persists a password we've generated, to be set by the given command.
+pub(super) fn record_pending_password(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+    command_uuid: &Uuid,
+    password: &str,
+) {
+    let command_uuid = command_uuid.to_string();
+    let record = NewDevicePassword {
+        udid: device_udid.to_string(),
+        kind: password_kind.as_str().to_string(),
+        sealed_password: state
+            .vault
+            .seal_string(&vault_context(device_udid, &command_uuid), password),
+        command_uuid,
+        status: STATUS_PENDING.to_string(),
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(device_passwords::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting device password");
+}
+
+/// Queues a command to verify the active password on a device.
+/// Returns None if no password is active, or it cannot be verified remotely.
+pub fn verify_password(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+) -> Option<Uuid> {
+    let record = active_password(connection, device_udid, password_kind)?;
+    let password = open_password(state, &record)?;
+
+    let command = match password_kind {
+        PasswordKind::RecoveryLock => Command::VerifyRecoveryLock(VerifyRecoveryLock { password }),
+        PasswordKind::Firmware => {
+            Command::VerifyFirmwarePassword(VerifyFirmwarePassword { password })
+        }
+        PasswordKind::AdminAccount => return None,
+    };
+    Some(command.enqueue(state, connection, device_udid))
+}
+
+/// Marks the password set by the given command as active,
+/// superseding the password previously in effect.
+pub fn password_acknowledged(connection: &mut SqliteConnection, command_uuid: &str) {
+    let record = device_passwords::table
+        .filter(device_passwords::command_uuid.eq(command_uuid))
+        .first::<DevicePassword>(connection)
+        .optional()
+        .expect("can query device passwords");
+    let Some(record) = record else {
+        return;
+    };
+
+    connection
+        .transaction(|connection| {
+            diesel::update(
+                device_passwords::table
+                    .filter(device_passwords::udid.eq(&record.udid))
+                    .filter(device_passwords::kind.eq(&record.kind))
+                    .filter(device_passwords::status.eq(STATUS_ACTIVE)),
+            )
+            .set(device_passwords::status.eq(STATUS_SUPERSEDED))
+            .execute(connection)?;
+
+            diesel::update(device_passwords::table.find(record.id))
+                .set((
+                    device_passwords::status.eq(STATUS_ACTIVE),
+                    device_passwords::activation_date.eq(OffsetDateTime::now_utc()),
+                ))
+                .execute(connection)
+        })
+        .expect("error updating device passwords");
+}
+
+/// Marks the password set by the given command as failed.
+/// The previously active password, if any, remains in effect.
+pub fn password_failed(connection: &mut SqliteConnection, command_uuid: &str) {
+    diesel::update(
+        device_passwords::table
+            .filter(device_passwords::command_uuid.eq(command_uuid))
+            .filter(device_passwords::status.eq(STATUS_PENDING)),
+    )
+    .set(device_passwords::status.eq(STATUS_FAILED))
+    .execute(connection)
+    .expect("error updating device passwords");
+}
+
+/// Records that a device has confirmed its active password.
+pub fn password_verified(
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    password_kind: PasswordKind,
+) {
+    diesel::update(
+        device_passwords::table
+            .filter(device_passwords::udid.eq(device_udid))
+            .filter(device_passwords::kind.eq(password_kind.as_str()))
+            .filter(device_passwords::status.eq(STATUS_ACTIVE)),
+    )
+    .set(device_passwords::verification_date.eq(OffsetDateTime::now_utc()))
+    .execute(connection)
+    .expect("error updating device passwords");
+}
+
+/// Decrypts an escrowed password.
+pub fn open_password(state: &AppState, record: &DevicePassword) -> Option<String> {
+    state.vault.open_string(
+        &vault_context(&record.udid, &record.command_uuid),
+        &record.sealed_password,
+    )
+}
+
+/// Rotates all active passwords older than our configured rotation period.
+/// Devices with a password change already pending are skipped.
+pub fn rotate_expired_passwords(state: &AppState) {
+    let Some(rotation_days) = state.config.passwords.rotation_days else {
+        return;
+    };
+
+    let connection = &mut state.database.connection();
+    let rotation_cutoff = OffsetDateTime::now_utc() - Duration::days(rotation_days);
+    let expired_passwords = device_passwords::table
+        .filter(device_passwords::status.eq(STATUS_ACTIVE))
+        .filter(device_passwords::activation_date.lt(rotation_cutoff))
+        .load::<DevicePassword>(connection)
+        .expect("can query device passwords");
+
+    for record in expired_passwords {
+        let Some(password_kind) = PasswordKind::parse(&record.kind) else {
+            continue;
+        };
+        if has_pending_password(connection, &record.udid, password_kind) {
+            continue;
+        }
+
+        println!("rotating {} password for {}", record.kind, record.udid);
+        rotate_password(state, connection, &record.udid, password_kind);
+    }
+}
+
+/// Periodically rotates expired passwords for as long as we're running.
+pub async fn schedule_rotation(state: AppState) {
+    if state.config.passwords.rotation_days.is_none() {
+        return;
+    }
+
+    let mut interval = tokio::time::interval(ROTATION_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || rotate_expired_passwords(&state))
+            .await
+            .expect("password rotation should not panic");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip() {
+        for kind in PasswordKind::ALL {
+            assert_eq!(PasswordKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(PasswordKind::parse("RecoveryLock"), None);
+    }
+
+    #[test]
+    fn kinds_deserialize_from_their_names() {
+        let kind: PasswordKind =
+            serde_json::from_str("\"recovery_lock\"").expect("kind should deserialize");
+        assert_eq!(kind, PasswordKind::RecoveryLock);
+        assert!(serde_json::from_str::<PasswordKind>("\"passcode\"").is_err());
+    }
+}
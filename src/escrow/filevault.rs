@@ -0,0 +1,109 @@
+use crate::app_state::AppState;
+use crate::database::{FilevaultRecoveryKey, filevault_recovery_keys};
+use crate::payloads::{
+    BasePayload, FileVaultEscrowPayload, Payload, PayloadScope, PayloadType,
+    Pkcs1CertificatePayload, Profile,
+};
+use der::Encode;
+use diesel::prelude::*;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The context recovery keys are sealed under within our vault.
+fn vault_context(udid: &str) -> String {
+    format!("filevault-recovery-key:{udid}")
+}
+
+/// Creates a profile escrowing the FileVault personal recovery key to us.
+/// This contains our escrow certificate, which the device encrypts its key against.
+pub fn escrow_profile(state: &AppState) -> Profile<Payload> {
+    let service_config = &state.config.service;
+    let escrow_cert_contents = state.certificates.escrow_cert.to_der().unwrap();
+
+    let certificate_uuid = Uuid::new_v4();
+    Profile {
+        base: BasePayload {
+            identifier: format!("{}.filevault-escrow", service_config.base_identifier),
+            display_name: Some("FileVault Recovery Key Escrow".to_string()),
+            organization: Some(service_config.organization_name.clone()),
+            ..Default::default()
+        },
+        scope: Some(PayloadScope::System),
+        contents: vec![
+            Payload::Pkcs1Certificate(Pkcs1CertificatePayload {
+                base: BasePayload {
+                    identifier: format!(
+                        "{}.filevault-escrow.certificate",
+                        service_config.base_identifier
+                    ),
+                    payload_type: PayloadType::CertificatePkcs1,
+                    uuid: certificate_uuid,
+                    ..Default::default()
+                },
+                file_name: "escrow_cert.cer".to_string(),
+                certificate: escrow_cert_contents,
+            }),
+            Payload::FileVaultEscrow(FileVaultEscrowPayload {
+                base: BasePayload {
+                    identifier: format!(
+                        "{}.filevault-escrow.escrow",
+                        service_config.base_identifier
+                    ),
+                    payload_type: PayloadType::FileVaultEscrow,
+                    ..Default::default()
+                },
+                location: service_config.organization_name.clone(),
+                encrypt_cert_payload_uuid: certificate_uuid,
+                device_key: None,
+            }),
+        ],
+        ..Default::default()
+    }
+}
+
+/// Decrypts a recovery key sent by the device and persists it.
+pub fn escrow_recovery_key(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    udid: &str,
+    envelope: &[u8],
+) {
+    let Some(recovery_key) = state
+        .certificates
+        .decrypt_escrowed(envelope)
+        .and_then(parse_recovery_key)
+    else {
+        println!("unable to decrypt FileVault recovery key from {udid}");
+        return;
+    };
+
+    let record = FilevaultRecoveryKey {
+        udid: udid.to_string(),
+        sealed_key: state.vault.seal_string(&vault_context(udid), &recovery_key),
+        creation_date: OffsetDateTime::now_utc(),
+        viewed_date: None,
+    };
+    diesel::replace_into(filevault_recovery_keys::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting recovery key");
+}
+
+/// Decrypts an escrowed recovery key.
+pub fn open_recovery_key(state: &AppState, record: &FilevaultRecoveryKey) -> Option<String> {
+    state
+        .vault
+        .open_string(&vault_context(&record.udid), &record.sealed_key)
+}
+
+/// Decrypted recovery keys are either a property list with a RecoveryKey key,
+/// or the recovery key itself as a string.
+fn parse_recovery_key(decrypted: Vec<u8>) -> Option<String> {
+    if let Ok(plist::Value::Dictionary(contents)) = plist::from_bytes(&decrypted) {
+        let recovery_key = contents.get("RecoveryKey")?.as_string()?;
+        return Some(recovery_key.to_string());
+    }
+
+    let recovery_key = String::from_utf8(decrypted).ok()?;
+    Some(recovery_key.trim().to_string())
+}
@@ -0,0 +1,15 @@
+mod activation_lock;
+mod admin_account;
+mod audit;
+mod bootstrap_token;
+mod device_passwords;
+mod filevault;
+mod removal_passwords;
+
+pub use activation_lock::*;
+pub use admin_account::*;
+pub use audit::*;
+pub use bootstrap_token::*;
+pub use device_passwords::*;
+pub use filevault::*;
+pub use removal_passwords::*;
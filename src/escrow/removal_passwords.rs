@@ -0,0 +1,50 @@
+use crate::app_state::AppState;
+use crate::database::{ProfileRemovalPassword, profile_removal_passwords};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+use super::generate_password;
+
+/// The context removal passwords are sealed under within our vault.
+fn vault_context(identifier: &str) -> String {
+    format!("profile-removal-password:{identifier}")
+}
+
+/// The password required to remove the profile with the given identifier.
+/// One is generated and stored upon first use, after which it remains the same.
+pub fn removal_password(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    identifier: &str,
+) -> String {
+    let existing = profile_removal_passwords::table
+        .find(identifier)
+        .first::<ProfileRemovalPassword>(connection)
+        .optional()
+        .expect("can query removal passwords");
+    if let Some(record) = existing {
+        return open_removal_password(state, &record)
+            .expect("stored removal passwords should be decryptable");
+    }
+
+    let password = generate_password();
+    let record = ProfileRemovalPassword {
+        identifier: identifier.to_string(),
+        sealed_password: state
+            .vault
+            .seal_string(&vault_context(identifier), &password),
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(profile_removal_passwords::table)
+        .values(&record)
+        .execute(connection)
+        .expect("error persisting removal password");
+    password
+}
+
+/// Decrypts a stored removal password.
+pub fn open_removal_password(state: &AppState, record: &ProfileRemovalPassword) -> Option<String> {
+    state
+        .vault
+        .open_string(&vault_context(&record.identifier), &record.sealed_password)
+}
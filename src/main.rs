@@ -1,11 +1,18 @@
 mod app_state;
+mod assets;
 mod certificates;
+mod commands;
 mod config;
 mod database;
+mod declarations;
+mod escrow;
 mod payloads;
 mod plist;
 mod routes;
 mod storage;
+mod tools;
+mod users;
+mod vault;
 
 use crate::app_state::AppState;
 use crate::config::Config;
@@ -15,6 +22,16 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    // Administrative tools are run as subcommands, e.g. `mdm-server activation-lock <UDID>`.
+    if let Some(tool_name) = args.get(1)
+        && tools::is_tool(tool_name)
+    {
+        tools::run(&args[1..]);
+        return;
+    }
+
     println!("Starting up...");
     // Allow for logging
     tracing_subscriber::registry()
@@ -25,7 +42,6 @@ async fn main() {
         .init();
 
     // TODO: Allow for a flag-based way to specify this config path
-    let args: Vec<String> = env::args().collect();
     let config_path = match args.get(1) {
         Some(s) => s.clone(),
         None => "./config.toml".to_string(),
@@ -37,6 +53,11 @@ async fn main() {
     // Create our global state for later usage.
     let state = AppState::with_config(config.clone());
 
+    // Rotate device passwords in the background, per our configured policy.
+    tokio::spawn(escrow::schedule_rotation(state.clone()));
+    // Asset URLs expire, so they must be replaced before then.
+    tokio::spawn(declarations::schedule_asset_renewal(state.clone()));
+
     let ssl_cert_path = config.certificate_path("ssl_cert.pem");
     let ssl_key_path = config.certificate_path("ssl_key.pem");
     let tls_config = RustlsConfig::from_pem_file(ssl_cert_path, ssl_key_path)
@@ -0,0 +1,248 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Configures an IMAP or POP mail account.
+/// https://developer.apple.com/documentation/devicemanagement/mail
+pub struct MailPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "EmailAccountDescription")]
+    /// The name of this account - user visible.
+    pub email_account_description: Option<String>,
+    #[serde(rename = "EmailAccountName")]
+    /// The full name of the user, as shown within sent messages.
+    pub email_account_name: Option<String>,
+    #[serde(rename = "EmailAccountType")]
+    pub email_account_type: MailAccountType,
+    #[serde(rename = "EmailAddress")]
+    /// If absent, the user is prompted for their address.
+    pub email_address: Option<String>,
+    #[serde(rename = "IncomingMailServerAuthentication")]
+    pub incoming_mail_server_authentication: MailAuthentication,
+    #[serde(rename = "IncomingMailServerHostName")]
+    pub incoming_mail_server_host_name: String,
+    #[serde(rename = "IncomingMailServerPortNumber")]
+    pub incoming_mail_server_port_number: Option<u16>,
+    #[serde(rename = "IncomingMailServerUseSSL")]
+    pub incoming_mail_server_use_ssl: Option<bool>,
+    #[serde(rename = "IncomingMailServerUsername")]
+    pub incoming_mail_server_username: Option<String>,
+    #[serde(rename = "OutgoingMailServerAuthentication")]
+    pub outgoing_mail_server_authentication: MailAuthentication,
+    #[serde(rename = "OutgoingMailServerHostName")]
+    pub outgoing_mail_server_host_name: String,
+    #[serde(rename = "OutgoingMailServerPortNumber")]
+    pub outgoing_mail_server_port_number: Option<u16>,
+    #[serde(rename = "OutgoingMailServerUseSSL")]
+    pub outgoing_mail_server_use_ssl: Option<bool>,
+    #[serde(rename = "OutgoingMailServerUsername")]
+    pub outgoing_mail_server_username: Option<String>,
+    #[serde(rename = "OutgoingPasswordSameAsIncomingPassword")]
+    /// Whether the user is only prompted once for their password.
+    pub outgoing_password_same_as_incoming_password: Option<bool>,
+    #[serde(rename = "PreventMove")]
+    /// Whether messages may not be moved to other accounts.
+    pub prevent_move: Option<bool>,
+    #[serde(rename = "PreventAppSheet")]
+    /// Whether third-party apps may not send mail via this account.
+    pub prevent_app_sheet: Option<bool>,
+}
+
+impl Default for MailPayload {
+    /// Creates an IMAP payload with password authentication and all options unset.
+    fn default() -> Self {
+        MailPayload {
+            base: BasePayload {
+                payload_type: PayloadType::Mail,
+                ..Default::default()
+            },
+            email_account_description: None,
+            email_account_name: None,
+            email_account_type: MailAccountType::Imap,
+            email_address: None,
+            incoming_mail_server_authentication: MailAuthentication::Password,
+            // Please ensure you set both server host names.
+            incoming_mail_server_host_name: "".to_string(),
+            incoming_mail_server_port_number: None,
+            incoming_mail_server_use_ssl: None,
+            incoming_mail_server_username: None,
+            outgoing_mail_server_authentication: MailAuthentication::Password,
+            outgoing_mail_server_host_name: "".to_string(),
+            outgoing_mail_server_port_number: None,
+            outgoing_mail_server_use_ssl: None,
+            outgoing_mail_server_username: None,
+            outgoing_password_same_as_incoming_password: None,
+            prevent_move: None,
+            prevent_app_sheet: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum MailAccountType {
+    #[serde(rename = "EmailTypeIMAP")]
+    Imap,
+    #[serde(rename = "EmailTypePOP")]
+    Pop,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum MailAuthentication {
+    #[serde(rename = "EmailAuthPassword")]
+    Password,
+    #[serde(rename = "EmailAuthCRAMMD5")]
+    CramMd5,
+    #[serde(rename = "EmailAuthNTLM")]
+    Ntlm,
+    #[serde(rename = "EmailAuthHTTPMD5")]
+    HttpMd5,
+    #[serde(rename = "EmailAuthNone")]
+    None,
+}
+
+#[payload]
+/// Configures an Exchange ActiveSync account for mail, contacts and calendars.
+/// https://developer.apple.com/documentation/devicemanagement/exchangeactivesync
+pub struct ExchangeActiveSyncPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "AccountName")]
+    /// The name of this account - user visible.
+    pub account_name: Option<String>,
+    #[serde(rename = "EmailAddress")]
+    /// If absent, the user is prompted for their address.
+    pub email_address: Option<String>,
+    #[serde(rename = "Host")]
+    pub host: String,
+    #[serde(rename = "SSL")]
+    pub ssl: Option<bool>,
+    #[serde(rename = "UserName")]
+    /// If absent, the user is prompted for their user name.
+    pub user_name: Option<String>,
+    #[serde(rename = "OAuth")]
+    /// Whether to authenticate via OAuth rather than a password.
+    pub oauth: Option<bool>,
+    #[serde(rename = "OAuthSignInURL")]
+    pub oauth_sign_in_url: Option<String>,
+    #[serde(rename = "PayloadCertificateUUID")]
+    /// The UUID of an identity payload within the same profile,
+    /// used to authenticate via a client certificate.
+    pub payload_certificate_uuid: Option<Uuid>,
+    #[serde(rename = "MailNumberOfPastDaysToSync")]
+    /// Zero synchronizes all mail.
+    pub mail_number_of_past_days_to_sync: Option<u32>,
+    #[serde(rename = "PreventMove")]
+    pub prevent_move: Option<bool>,
+    #[serde(rename = "PreventAppSheet")]
+    pub prevent_app_sheet: Option<bool>,
+}
+
+impl Default for ExchangeActiveSyncPayload {
+    /// Creates an Exchange ActiveSync payload with all options unset.
+    fn default() -> Self {
+        ExchangeActiveSyncPayload {
+            base: BasePayload {
+                payload_type: PayloadType::ExchangeActiveSync,
+                ..Default::default()
+            },
+            account_name: None,
+            email_address: None,
+            // Please ensure you set a host.
+            host: "".to_string(),
+            ssl: None,
+            user_name: None,
+            oauth: None,
+            oauth_sign_in_url: None,
+            payload_certificate_uuid: None,
+            mail_number_of_past_days_to_sync: None,
+            prevent_move: None,
+            prevent_app_sheet: None,
+        }
+    }
+}
+
+#[payload]
+/// Configures a CalDAV account.
+/// https://developer.apple.com/documentation/devicemanagement/caldav
+pub struct CalDavPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "CalDAVAccountDescription")]
+    /// The name of this account - user visible.
+    pub account_description: Option<String>,
+    #[serde(rename = "CalDAVHostName")]
+    pub host_name: String,
+    #[serde(rename = "CalDAVPort")]
+    pub port: Option<u16>,
+    #[serde(rename = "CalDAVPrincipalURL")]
+    /// If absent, the principal is discovered via the host.
+    pub principal_url: Option<String>,
+    #[serde(rename = "CalDAVUseSSL")]
+    pub use_ssl: Option<bool>,
+    #[serde(rename = "CalDAVUsername")]
+    /// If absent, the user is prompted for their user name.
+    pub username: Option<String>,
+}
+
+impl Default for CalDavPayload {
+    /// Creates a CalDAV payload with all options unset.
+    fn default() -> Self {
+        CalDavPayload {
+            base: BasePayload {
+                payload_type: PayloadType::CalDav,
+                ..Default::default()
+            },
+            account_description: None,
+            // Please ensure you set a host name.
+            host_name: "".to_string(),
+            port: None,
+            principal_url: None,
+            use_ssl: None,
+            username: None,
+        }
+    }
+}
+
+#[payload]
+/// Configures a CardDAV account.
+/// https://developer.apple.com/documentation/devicemanagement/carddav
+pub struct CardDavPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "CardDAVAccountDescription")]
+    /// The name of this account - user visible.
+    pub account_description: Option<String>,
+    #[serde(rename = "CardDAVHostName")]
+    pub host_name: String,
+    #[serde(rename = "CardDAVPort")]
+    pub port: Option<u16>,
+    #[serde(rename = "CardDAVPrincipalURL")]
+    pub principal_url: Option<String>,
+    #[serde(rename = "CardDAVUseSSL")]
+    pub use_ssl: Option<bool>,
+    #[serde(rename = "CardDAVUsername")]
+    pub username: Option<String>,
+}
+
+impl Default for CardDavPayload {
+    /// Creates a CardDAV payload with all options unset.
+    fn default() -> Self {
+        CardDavPayload {
+            base: BasePayload {
+                payload_type: PayloadType::CardDav,
+                ..Default::default()
+            },
+            account_description: None,
+            // Please ensure you set a host name.
+            host_name: "".to_string(),
+            port: None,
+            principal_url: None,
+            use_ssl: None,
+            username: None,
+        }
+    }
+}
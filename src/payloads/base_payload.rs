@@ -1,6 +1,9 @@
 use optional_value::payload;
+use plist::Date;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use uuid::{Builder, Uuid};
 
 use super::PayloadType;
 
@@ -50,6 +53,19 @@ impl Default for BasePayload {
     }
 }
 
+/// Creates a name-based (version 5) UUID, which is identical for the same namespace and name.
+/// https://datatracker.ietf.org/doc/html/rfc9562#section-5.5
+pub fn name_based_uuid(namespace: &Uuid, name: &str) -> Uuid {
+    let mut hasher = Sha1::new();
+    hasher.update(namespace.as_bytes());
+    hasher.update(name.as_bytes());
+    let digest = hasher.finalize();
+
+    let mut sha1_bytes = [0; 16];
+    sha1_bytes.copy_from_slice(&digest[..16]);
+    Builder::from_sha1_bytes(sha1_bytes).into_uuid()
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub enum PayloadScope {
     System,
@@ -69,6 +85,8 @@ where
     pub contents: Vec<T>,
     #[serde(rename = "PayloadScope")]
     pub scope: Option<PayloadScope>,
+    #[serde(flatten)]
+    pub options: ProfileOptions,
 }
 
 impl<T> Default for Profile<T>
@@ -80,6 +98,75 @@ where
             base: BasePayload::default(),
             scope: None,
             contents: vec![],
+            options: ProfileOptions::default(),
+        }
+    }
+}
+
+#[payload]
+#[derive(Default)]
+/// Top-level keys governing how a profile is installed and removed.
+/// https://developer.apple.com/documentation/devicemanagement/toplevel
+pub struct ProfileOptions {
+    #[serde(rename = "PayloadRemovalDisallowed")]
+    /// If true, the user may not remove this profile,
+    /// unless it contains a removal password and they provide it.
+    pub removal_disallowed: Option<bool>,
+    #[serde(rename = "ConsentText")]
+    /// Text shown to the user prior to installation, keyed by language code (e.g. "en").
+    /// The "default" key is shown if no other language matches.
+    pub consent_text: Option<BTreeMap<String, String>>,
+    #[serde(rename = "RemovalDate")]
+    /// The date this profile is automatically removed on.
+    pub removal_date: Option<Date>,
+    #[serde(rename = "DurationUntilRemoval")]
+    /// The number of seconds after installation this profile is automatically removed.
+    pub duration_until_removal: Option<f64>,
+    #[serde(rename = "TargetDeviceType")]
+    /// The type of device this profile may be installed on.
+    pub target_device_type: Option<TargetDeviceType>,
+    #[serde(rename = "HasRemovalPasscode")]
+    /// Whether this profile contains a removal password payload.
+    pub has_removal_passcode: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum TargetDeviceType {
+    Any,
+    /// iPhone and iPad.
+    Ios,
+    Watch,
+    HomePod,
+    AppleTv,
+    Mac,
+}
+
+impl From<TargetDeviceType> for u8 {
+    fn from(value: TargetDeviceType) -> Self {
+        match value {
+            TargetDeviceType::Any => 0,
+            TargetDeviceType::Ios => 1,
+            TargetDeviceType::Watch => 2,
+            TargetDeviceType::HomePod => 3,
+            TargetDeviceType::AppleTv => 4,
+            TargetDeviceType::Mac => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for TargetDeviceType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TargetDeviceType::Any),
+            1 => Ok(TargetDeviceType::Ios),
+            2 => Ok(TargetDeviceType::Watch),
+            3 => Ok(TargetDeviceType::HomePod),
+            4 => Ok(TargetDeviceType::AppleTv),
+            5 => Ok(TargetDeviceType::Mac),
+            _ => Err(format!("unknown target device type {value}")),
         }
     }
 }
@@ -1,4 +1,5 @@
 use optional_value::payload;
+use serde::{Deserialize, Serialize};
 
 use super::BasePayload;
 
@@ -81,3 +82,103 @@ impl Default for ScepPayloadContents {
         }
     }
 }
+
+#[payload]
+/// A DER-encoded certificate, i.e. an intermediate or an encryption certificate.
+/// https://developer.apple.com/documentation/devicemanagement/certificatepkcs1
+pub struct Pkcs1CertificatePayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "PayloadCertificateFileName")]
+    pub file_name: String,
+    #[serde(rename = "PayloadContent", with = "serde_bytes")]
+    /// The certificate, either DER-encoded or PEM-encoded.
+    pub certificate: Vec<u8>,
+}
+
+#[payload]
+/// A PKCS #12 identity: a certificate alongside its private key.
+/// https://developer.apple.com/documentation/devicemanagement/certificatepkcs12
+pub struct Pkcs12CertificatePayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "PayloadCertificateFileName")]
+    pub file_name: Option<String>,
+    #[serde(rename = "PayloadContent", with = "serde_bytes")]
+    pub identity: Vec<u8>,
+    #[serde(rename = "Password")]
+    /// The password protecting this identity.
+    /// If absent, the user is prompted for it upon installation.
+    pub password: Option<String>,
+    #[serde(rename = "AllowAllAppsAccess")]
+    /// Whether all apps may access the private key. Only available on macOS.
+    pub allow_all_apps_access: Option<bool>,
+    #[serde(rename = "KeyIsExtractable")]
+    /// Whether the private key may be exported. Only available on macOS.
+    pub key_is_extractable: Option<bool>,
+}
+
+#[payload]
+/// Requests a certificate from an ACME server, such as our own.
+/// https://developer.apple.com/documentation/devicemanagement/acmecertificate
+pub struct AcmeCertificatePayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "DirectoryURL")]
+    /// The URL of the ACME server's directory resource.
+    pub directory_url: String,
+    #[serde(rename = "ClientIdentifier")]
+    /// A unique identifier for this request, used by the server to issue a single certificate.
+    pub client_identifier: String,
+    #[serde(rename = "KeySize")]
+    /// For RSA, between 1024 and 4096 bits. For ECSECPrimeRandom, 256 or 384.
+    pub key_size: i32,
+    #[serde(rename = "KeyType")]
+    pub key_type: AcmeKeyType,
+    #[serde(rename = "HardwareBound")]
+    /// Whether the private key is generated within (and bound to) the Secure Enclave.
+    /// This requires ECSECPrimeRandom keys.
+    pub hardware_bound: bool,
+    #[serde(rename = "Subject")]
+    /// An array of single-element arrays with a single-element array of paired properties,
+    /// in the same form as SCEP's subject.
+    pub subject: Vec<Vec<Vec<String>>>,
+    #[serde(rename = "SubjectAltName")]
+    pub subject_alt_name: Option<AcmeSubjectAltName>,
+    #[serde(rename = "UsageFlags")]
+    /// A bitmask of key usages: 1 for signing, 4 for encryption.
+    pub usage_flags: Option<i32>,
+    #[serde(rename = "ExtendedKeyUsage")]
+    /// OIDs of extended key usages, such as "1.3.6.1.5.5.7.3.2" for client authentication.
+    pub extended_key_usage: Option<Vec<String>>,
+    #[serde(rename = "Attest")]
+    /// Whether to provide an attestation of the device's identity to the server.
+    /// This requires a hardware bound key.
+    pub attest: Option<bool>,
+    #[serde(rename = "AllowAllAppsAccess")]
+    /// Only available on macOS.
+    pub allow_all_apps_access: Option<bool>,
+    #[serde(rename = "KeyIsExtractable")]
+    /// Only available on macOS. Must be false for hardware bound keys.
+    pub key_is_extractable: Option<bool>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AcmeKeyType {
+    #[serde(rename = "RSA")]
+    Rsa,
+    #[serde(rename = "ECSECPrimeRandom")]
+    EcSecPrimeRandom,
+}
+
+#[payload]
+pub struct AcmeSubjectAltName {
+    #[serde(rename = "rfc822Name")]
+    pub rfc822_name: Option<String>,
+    #[serde(rename = "dNSName")]
+    pub dns_name: Option<String>,
+    #[serde(rename = "uniformResourceIdentifier")]
+    pub uniform_resource_identifier: Option<String>,
+    #[serde(rename = "ntPrincipalName")]
+    pub nt_principal_name: Option<String>,
+}
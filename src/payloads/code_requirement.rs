@@ -0,0 +1,405 @@
+/// Validates the syntax of a code requirement, such as
+/// `identifier "com.example.agent" and anchor apple generic`.
+/// https://developer.apple.com/library/archive/documentation/Security/Conceptual/CodeSigningGuide/RequirementLang/RequirementLang.html
+///
+/// Devices silently ignore policies whose requirements fail to parse,
+/// so this allows us to catch typos prior to signing.
+/// Only syntax is checked: a valid requirement may still match nothing.
+pub fn validate_code_requirement(requirement: &str) -> Result<(), String> {
+    let tokens = tokenize(requirement)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        length: requirement.len(),
+    };
+
+    // Requirements copied from `codesign -d -r-` are prefixed with their type.
+    if parser.peek_word("designated") {
+        parser.position += 1;
+        parser.expect_punct("=>")?;
+    }
+    parser.parse_expression()?;
+    match parser.next() {
+        None => Ok(()),
+        Some((offset, token)) => Err(format!(
+            "unexpected {} at offset {offset}",
+            token.describe()
+        )),
+    }
+}
+
+#[derive(PartialEq)]
+enum Token {
+    /// Keywords, unquoted strings, numbers and key paths such as `subject.OU`.
+    Word(String),
+    /// A quoted string, with its escapes resolved.
+    String(String),
+    /// A hash constant, such as `H"0123abcd"`.
+    Hash(String),
+    Punct(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{word}'"),
+            Token::String(string) => format!("string \"{string}\""),
+            Token::Hash(_) => "hash constant".to_string(),
+            Token::Punct(punct) => format!("'{punct}'"),
+        }
+    }
+}
+
+/// Punctuation, ordered such that longer operators match first.
+const PUNCTUATION: [&str; 11] = ["=>", "<=", ">=", "(", ")", "[", "]", "!", "=", "<", ">"];
+
+/// Splits a requirement into tokens, alongside their byte offsets.
+fn tokenize(requirement: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = vec![];
+    let mut remaining = requirement;
+    loop {
+        remaining = remaining.trim_start();
+        // Comments may be in either C or C++ style.
+        if let Some(comment) = remaining.strip_prefix("/*") {
+            let Some(end) = comment.find("*/") else {
+                return Err("unterminated comment".to_string());
+            };
+            remaining = &comment[end + 2..];
+            continue;
+        }
+        if let Some(comment) = remaining.strip_prefix("//") {
+            remaining = comment.find('\n').map_or("", |end| &comment[end..]);
+            continue;
+        }
+
+        let offset = requirement.len() - remaining.len();
+        let Some(character) = remaining.chars().next() else {
+            return Ok(tokens);
+        };
+
+        if let Some(punct) = PUNCTUATION
+            .iter()
+            .find(|punct| remaining.starts_with(**punct))
+        {
+            tokens.push((offset, Token::Punct(punct)));
+            remaining = &remaining[punct.len()..];
+        } else if character == '"' {
+            let (string, rest) = read_string(&remaining[1..])
+                .ok_or(format!("unterminated string at offset {offset}"))?;
+            tokens.push((offset, Token::String(string)));
+            remaining = rest;
+        } else if remaining.starts_with("H\"") {
+            let (hash, rest) = read_string(&remaining[2..])
+                .ok_or(format!("unterminated hash constant at offset {offset}"))?;
+            let is_hex = hash.chars().all(|digit| digit.is_ascii_hexdigit());
+            if hash.is_empty() || hash.len() % 2 != 0 || !is_hex {
+                return Err(format!("invalid hash constant at offset {offset}"));
+            }
+            tokens.push((offset, Token::Hash(hash)));
+            remaining = rest;
+        } else if is_word_character(character) {
+            let end = remaining
+                .find(|character| !is_word_character(character))
+                .unwrap_or(remaining.len());
+            tokens.push((offset, Token::Word(remaining[..end].to_string())));
+            remaining = &remaining[end..];
+        } else {
+            return Err(format!("unexpected '{character}' at offset {offset}"));
+        }
+    }
+}
+
+/// Characters permitted within unquoted strings.
+/// Wildcards (`*`) may prefix or suffix values being matched against.
+fn is_word_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || "._-/*:".contains(character)
+}
+
+/// Reads a quoted string, given the contents following its opening quote.
+/// Returns the string and the contents following its closing quote.
+fn read_string(contents: &str) -> Option<(String, &str)> {
+    let mut string = String::new();
+    let mut characters = contents.char_indices();
+    while let Some((index, character)) = characters.next() {
+        match character {
+            '"' => return Some((string, &contents[index + 1..])),
+            '\\' => string.push(characters.next()?.1),
+            _ => string.push(character),
+        }
+    }
+    None
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// The length of the requirement, for errors at its end.
+    length: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<(usize, &Token)> {
+        let (offset, token) = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some((*offset, token))
+    }
+
+    fn peek_word(&self, expected: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some((_, Token::Word(word))) if word == expected)
+    }
+
+    fn peek_punct(&self, expected: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some((_, Token::Punct(punct))) if *punct == expected)
+    }
+
+    /// Describes what was found instead of the expected item.
+    fn unexpected(&self, expected: &str) -> String {
+        match self.tokens.get(self.position) {
+            Some((offset, token)) => format!(
+                "expected {expected} at offset {offset}, found {}",
+                token.describe()
+            ),
+            None => format!("expected {expected} at offset {}", self.length),
+        }
+    }
+
+    fn expect_punct(&mut self, expected: &str) -> Result<(), String> {
+        if !self.peek_punct(expected) {
+            return Err(self.unexpected(&format!("'{expected}'")));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Consumes a word, returning it.
+    fn expect_word(&mut self, expected: &str) -> Result<String, String> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Word(word))) => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// `expression := and-expression ("or" and-expression)*`
+    fn parse_expression(&mut self) -> Result<(), String> {
+        self.parse_and_expression()?;
+        while self.peek_word("or") {
+            self.position += 1;
+            self.parse_and_expression()?;
+        }
+        Ok(())
+    }
+
+    /// `and-expression := unary ("and" unary)*`
+    fn parse_and_expression(&mut self) -> Result<(), String> {
+        self.parse_unary()?;
+        while self.peek_word("and") {
+            self.position += 1;
+            self.parse_unary()?;
+        }
+        Ok(())
+    }
+
+    fn parse_unary(&mut self) -> Result<(), String> {
+        if self.peek_punct("!") {
+            self.position += 1;
+            return self.parse_unary();
+        }
+        if self.peek_punct("(") {
+            self.position += 1;
+            self.parse_expression()?;
+            return self.expect_punct(")");
+        }
+
+        let keyword = self.expect_word("a requirement")?;
+        match keyword.as_str() {
+            "always" | "true" | "never" | "false" | "notarized" | "legacy" => Ok(()),
+            "identifier" => {
+                if self.peek_punct("=") {
+                    self.position += 1;
+                }
+                self.parse_string("an identifier")
+            }
+            "anchor" => self.parse_anchor(),
+            "certificate" | "cert" => {
+                self.parse_certificate_position()?;
+                self.parse_certificate_match()
+            }
+            "info" | "entitlement" => {
+                self.parse_key()?;
+                self.parse_match()
+            }
+            "cdhash" => self.parse_hash(),
+            "platform" => {
+                self.expect_punct("=")?;
+                let platform = self.expect_word("a platform number")?;
+                if platform.parse::<u32>().is_err() {
+                    return Err(format!("invalid platform {platform}"));
+                }
+                Ok(())
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected("a requirement"))
+            }
+        }
+    }
+
+    /// `anchor apple [generic] | anchor trusted | anchor <certificate match>`
+    fn parse_anchor(&mut self) -> Result<(), String> {
+        if self.peek_word("apple") {
+            self.position += 1;
+            if self.peek_word("generic") {
+                self.position += 1;
+            }
+            return Ok(());
+        }
+        self.parse_certificate_match()
+    }
+
+    /// `leaf | root | anchor | <index>`, where negative indices count from the anchor.
+    fn parse_certificate_position(&mut self) -> Result<(), String> {
+        let position = self.expect_word("a certificate position")?;
+        let is_index = position
+            .strip_prefix('-')
+            .unwrap_or(&position)
+            .parse::<u32>()
+            .is_ok();
+        if !matches!(position.as_str(), "leaf" | "root" | "anchor") && !is_index {
+            return Err(format!("invalid certificate position {position}"));
+        }
+        Ok(())
+    }
+
+    /// `trusted | = <hash> | [<field>] <match>`
+    fn parse_certificate_match(&mut self) -> Result<(), String> {
+        if self.peek_word("trusted") {
+            self.position += 1;
+            return Ok(());
+        }
+        if self.peek_punct("=") {
+            self.position += 1;
+            return self.parse_hash();
+        }
+        if self.peek_punct("[") {
+            self.parse_key()?;
+            return self.parse_match();
+        }
+        Err(self.unexpected("'trusted', '=' or '['"))
+    }
+
+    /// `[<key>]`, where keys are either unquoted or quoted.
+    fn parse_key(&mut self) -> Result<(), String> {
+        self.expect_punct("[")?;
+        self.parse_string("a key")?;
+        self.expect_punct("]")
+    }
+
+    /// `[exists | absent | <operator> <value>]`
+    /// If omitted, the key must exist. `codesign` outputs this as `/* exists */`.
+    fn parse_match(&mut self) -> Result<(), String> {
+        if self.peek_word("exists") || self.peek_word("absent") {
+            self.position += 1;
+            return Ok(());
+        }
+        match self.tokens.get(self.position) {
+            Some((_, Token::Punct("=" | "<" | ">" | "<=" | ">="))) => self.position += 1,
+            _ => return Ok(()),
+        }
+        // Wildcards may also prefix or suffix quoted values.
+        if self.peek_word("*") {
+            self.position += 1;
+        }
+        self.parse_string("a value")?;
+        if self.peek_word("*") {
+            self.position += 1;
+        }
+        Ok(())
+    }
+
+    /// Either a quoted or unquoted string.
+    fn parse_string(&mut self, expected: &str) -> Result<(), String> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::String(_) | Token::Word(_))) => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// A hash constant, or the path to a certificate to hash.
+    fn parse_hash(&mut self) -> Result<(), String> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Hash(_) | Token::String(_))) => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.unexpected("a hash constant")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_requirements() {
+        let requirements = [
+            r#"identifier "com.example.agent" and anchor apple generic"#,
+            r#"designated => identifier "com.example.agent" and anchor apple generic and certificate leaf[subject.OU] = "ABCDE12345""#,
+            r#"anchor apple generic and certificate 1[field.1.2.840.113635.100.6.2.6] /* exists */"#,
+            r#"!(info[CFBundleVersion] >= "2.0" or cdhash H"0123abcd")"#,
+            r#"identifier = com.example.* // Any of our tools."#,
+            "platform = 1",
+        ];
+        for requirement in requirements {
+            assert_eq!(
+                validate_code_requirement(requirement),
+                Ok(()),
+                "{requirement}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_requirements() {
+        let requirements = [
+            ("", "expected a requirement at offset 0"),
+            (
+                r#"identifier "com.example.agent" and"#,
+                "expected a requirement at offset 34",
+            ),
+            (
+                r#"identifier "com.example.agent"#,
+                "unterminated string at offset 11",
+            ),
+            (
+                r#"anchor apple generic identifier "com.example.agent""#,
+                "unexpected 'identifier' at offset 21",
+            ),
+            (r#"cdhash H"abc""#, "invalid hash constant at offset 7"),
+            (
+                "certificate middle trusted",
+                "invalid certificate position middle",
+            ),
+            ("(anchor trusted", "expected ')' at offset 15"),
+            ("anchor apple /* generic", "unterminated comment"),
+            (
+                "signed by apple",
+                "expected a requirement at offset 0, found 'signed'",
+            ),
+        ];
+        for (requirement, error) in requirements {
+            assert_eq!(
+                validate_code_requirement(requirement),
+                Err(error.to_string()),
+                "{requirement}"
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use optional_value::payload;
+use plist::{Date, Dictionary};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{BasePayload, Payload, PayloadType};
+
+#[payload]
+/// Applies preferences to arbitrary domains, in the format of Workgroup Manager.
+/// https://developer.apple.com/documentation/devicemanagement/managedpreferences
+pub struct ManagedPreferencesPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "PayloadContent")]
+    /// Preferences to apply, keyed by their domain, such as "com.example.app".
+    pub content: BTreeMap<String, ManagedPreferenceDomain>,
+}
+
+#[payload]
+#[derive(Default)]
+pub struct ManagedPreferenceDomain {
+    #[serde(rename = "Forced")]
+    /// Preferences which are always enforced.
+    pub forced: Option<Vec<ManagedPreferenceSettings>>,
+    #[serde(rename = "Set-Once")]
+    /// Preferences which are applied once, after which the user may change them.
+    pub set_once: Option<Vec<ManagedPreferenceSettings>>,
+}
+
+#[payload]
+pub struct ManagedPreferenceSettings {
+    #[serde(rename = "mcx_preference_settings")]
+    pub preference_settings: Dictionary,
+    #[serde(rename = "mcx_data_timestamp")]
+    /// Set-Once preferences are applied again whenever this changes.
+    pub data_timestamp: Option<Date>,
+}
+
+#[payload]
+/// Applies preferences directly to a domain, which is given as its PayloadType.
+/// https://developer.apple.com/documentation/devicemanagement/applicationpreferences
+///
+/// Its settings must not contain keys common to all payloads, such as PayloadUUID.
+pub struct CustomSettingsPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(flatten)]
+    pub settings: Dictionary,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// How custom settings are delivered to their domain.
+pub enum CustomSettingsFormat {
+    /// Enforced via a managed preferences payload.
+    Forced,
+    /// Applied once via a managed preferences payload.
+    SetOnce,
+    /// Applied via a payload whose type is the domain itself.
+    Direct,
+}
+
+/// Creates a payload applying the given settings to a preference domain.
+pub fn custom_settings(
+    identifier: String,
+    domain: &str,
+    settings: Dictionary,
+    format: CustomSettingsFormat,
+) -> Payload {
+    let managed_settings = |data_timestamp| {
+        Some(vec![ManagedPreferenceSettings {
+            preference_settings: settings.clone(),
+            data_timestamp,
+        }])
+    };
+    let domain_settings = match format {
+        CustomSettingsFormat::Forced => ManagedPreferenceDomain {
+            forced: managed_settings(None),
+            ..Default::default()
+        },
+        CustomSettingsFormat::SetOnce => ManagedPreferenceDomain {
+            set_once: managed_settings(Some(whole_seconds_now())),
+            ..Default::default()
+        },
+        CustomSettingsFormat::Direct => {
+            return Payload::CustomSettings(CustomSettingsPayload {
+                base: BasePayload {
+                    identifier,
+                    payload_type: PayloadType::Custom(domain.to_string()),
+                    ..Default::default()
+                },
+                settings,
+            });
+        }
+    };
+
+    Payload::ManagedPreferences(ManagedPreferencesPayload {
+        base: BasePayload {
+            identifier,
+            payload_type: PayloadType::ManagedPreferences,
+            ..Default::default()
+        },
+        content: BTreeMap::from([(domain.to_string(), domain_settings)]),
+    })
+}
+
+/// The current time, without fractional seconds.
+/// Devices are unable to parse dates containing them.
+fn whole_seconds_now() -> Date {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time should be after the epoch");
+    Date::from(UNIX_EPOCH + Duration::from_secs(elapsed.as_secs()))
+}
+
+/// Keys within the given settings which would collide with common payload keys,
+/// were they applied directly.
+pub fn reserved_setting_keys(settings: &Dictionary) -> Vec<&str> {
+    settings
+        .keys()
+        .map(String::as_str)
+        .filter(|key| key.starts_with("Payload"))
+        .collect()
+}
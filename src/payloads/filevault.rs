@@ -0,0 +1,23 @@
+use optional_value::payload;
+use uuid::Uuid;
+
+use super::BasePayload;
+
+#[payload]
+/// Escrows the FileVault personal recovery key of a Mac to us.
+/// The key is encrypted to the referenced certificate payload,
+/// and reported via SecurityInfo.
+/// https://developer.apple.com/documentation/devicemanagement/fderecoverykeyescrow
+pub struct FileVaultEscrowPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "Location")]
+    /// A description of where the recovery key is escrowed to - user visible.
+    pub location: String,
+    #[serde(rename = "EncryptCertPayloadUUID")]
+    /// The UUID of the certificate payload to encrypt recovery keys against.
+    pub encrypt_cert_payload_uuid: Uuid,
+    #[serde(rename = "DeviceKey")]
+    /// An optional identifier returned alongside the recovery key, such as a serial number.
+    pub device_key: Option<String>,
+}
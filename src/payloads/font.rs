@@ -0,0 +1,21 @@
+use optional_value::payload;
+
+use super::BasePayload;
+
+#[payload]
+/// Installs a font.
+/// https://developer.apple.com/documentation/devicemanagement/font
+///
+/// Unlike certificate payloads, its contents are given via `Font`
+/// rather than `PayloadContent`.
+pub struct FontPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "Name")]
+    /// The user-visible name of the font.
+    /// This is ignored, as the font's own name is displayed instead.
+    pub name: Option<String>,
+    #[serde(rename = "Font", with = "serde_bytes")]
+    /// The contents of a TrueType (.ttf) or OpenType (.otf) font.
+    pub font: Vec<u8>,
+}
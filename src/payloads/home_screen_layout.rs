@@ -0,0 +1,76 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Arranges the Home Screen. Only available on supervised devices.
+/// https://developer.apple.com/documentation/devicemanagement/homescreenlayout
+///
+/// Apps which are not present are placed on pages following this layout.
+pub struct HomeScreenLayoutPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "Dock")]
+    pub dock: Option<Vec<HomeScreenItem>>,
+    #[serde(rename = "Pages")]
+    /// Each page, containing its items in order.
+    pub pages: Vec<Vec<HomeScreenItem>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "Type")]
+/// An item on the Home Screen, within the dock, a page or a folder.
+/// https://developer.apple.com/documentation/devicemanagement/homescreenlayout/icon
+pub enum HomeScreenItem {
+    Application {
+        #[serde(rename = "BundleID")]
+        bundle_id: String,
+    },
+    Folder {
+        #[serde(rename = "DisplayName")]
+        display_name: String,
+        #[serde(rename = "Pages")]
+        /// Folders may not contain other folders.
+        pages: Vec<Vec<HomeScreenItem>>,
+    },
+    WebClip {
+        #[serde(rename = "URL")]
+        /// The URL of a web clip installed via another payload.
+        url: String,
+    },
+}
+
+impl Default for HomeScreenLayoutPayload {
+    /// Creates an empty layout.
+    fn default() -> Self {
+        HomeScreenLayoutPayload {
+            base: BasePayload {
+                payload_type: PayloadType::HomeScreenLayout,
+                ..Default::default()
+            },
+            dock: None,
+            pages: vec![],
+        }
+    }
+}
+
+impl HomeScreenLayoutPayload {
+    /// Every item within this layout, including those within folders.
+    pub fn items(&self) -> Vec<&HomeScreenItem> {
+        let mut items = vec![];
+        let mut remaining: Vec<&HomeScreenItem> = self
+            .dock
+            .iter()
+            .flatten()
+            .chain(self.pages.iter().flatten())
+            .collect();
+        while let Some(item) = remaining.pop() {
+            if let HomeScreenItem::Folder { pages, .. } = item {
+                remaining.extend(pages.iter().flatten());
+            }
+            items.push(item);
+        }
+        items
+    }
+}
@@ -0,0 +1,352 @@
+use optional_value::payload;
+use plist::{Dictionary, Value};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use super::{BasePayload, Payload, PayloadScope, PayloadType, Profile, ProfileOptions};
+use crate::certificates::{ProfileSigner, open_signed_profile};
+
+#[payload]
+/// A payload whose type we do not handle, or whose keys we do not fully understand.
+/// Its keys are retained as-is, so that it is installed exactly as imported.
+pub struct RawPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(flatten)]
+    /// Every key other than those common to all payloads.
+    pub contents: Dictionary,
+}
+
+/// Keys common to all payloads, as represented by `BasePayload`.
+const BASE_KEYS: [&str; 7] = [
+    "PayloadDescription",
+    "PayloadDisplayName",
+    "PayloadIdentifier",
+    "PayloadOrganization",
+    "PayloadType",
+    "PayloadUUID",
+    "PayloadVersion",
+];
+
+/// Keys within the top-level payload which we are able to retain.
+const PROFILE_KEYS: [&str; 8] = [
+    "PayloadContent",
+    "PayloadScope",
+    "PayloadRemovalDisallowed",
+    "ConsentText",
+    "RemovalDate",
+    "DurationUntilRemoval",
+    "TargetDeviceType",
+    "HasRemovalPasscode",
+];
+
+/// A profile created outside of this server, such as via Apple Configurator.
+pub struct ImportedProfile {
+    /// Present if the profile was signed.
+    pub signer: Option<ProfileSigner>,
+    /// Issues within the profile, such as duplicate PayloadUUIDs or unknown keys.
+    pub warnings: Vec<String>,
+    /// Absent if the profile could not be represented; its warnings describe why.
+    pub profile: Option<Profile<Payload>>,
+}
+
+/// Imports a signed or unsigned .mobileconfig file.
+///
+/// Payloads of known types are deserialized into their typed representation.
+/// Payloads of unknown types, or containing keys we do not know of, are kept raw.
+/// Returns an error if the given contents are not a profile at all.
+pub fn import_profile(contents: Vec<u8>) -> Result<ImportedProfile, String> {
+    // Signed profiles are a DER-encoded CMS envelope (a SEQUENCE),
+    // whereas unsigned profiles are either XML or binary plists.
+    let (profile_contents, signer) = if contents.first() == Some(&0x30) {
+        open_signed_profile(contents).ok_or("unable to parse signed profile")?
+    } else {
+        (contents, None)
+    };
+
+    let profile_value = Value::from_reader(Cursor::new(profile_contents))
+        .map_err(|e| format!("unable to parse profile: {e}"))?;
+    let Value::Dictionary(profile_dictionary) = profile_value else {
+        return Err("profile is not a dictionary".to_string());
+    };
+
+    let mut warnings = lint_profile(&profile_dictionary);
+    let profile = read_profile(&profile_dictionary, &mut warnings);
+    Ok(ImportedProfile {
+        signer,
+        warnings,
+        profile,
+    })
+}
+
+/// Checks the keys common to all payloads across the profile,
+/// such as missing identifiers and duplicate UUIDs.
+fn lint_profile(profile: &Dictionary) -> Vec<String> {
+    let mut warnings = vec![];
+    let label = payload_label(profile, None);
+    if profile.get("PayloadType").and_then(Value::as_string) != Some("Configuration") {
+        warnings.push(format!("{label}: PayloadType must be Configuration"));
+    }
+
+    let contents = match profile.get("PayloadContent") {
+        Some(Value::Array(contents)) => contents.as_slice(),
+        _ => {
+            warnings.push(format!("{label}: PayloadContent must be an array"));
+            &[]
+        }
+    };
+    let mut payloads = vec![(label, profile)];
+    for (index, value) in contents.iter().enumerate() {
+        let Value::Dictionary(payload) = value else {
+            warnings.push(format!("PayloadContent[{index}] is not a dictionary"));
+            continue;
+        };
+        payloads.push((payload_label(payload, Some(index)), payload));
+    }
+
+    let mut seen_uuids: HashMap<String, &str> = HashMap::new();
+    for (label, payload) in &payloads {
+        if payload_identifier(payload).is_none() {
+            warnings.push(format!("{label}: PayloadIdentifier is missing"));
+        }
+        if !payload.contains_key("PayloadType") {
+            warnings.push(format!("{label}: PayloadType is missing"));
+        }
+
+        match payload
+            .get("PayloadVersion")
+            .and_then(Value::as_signed_integer)
+        {
+            Some(1) => {}
+            Some(version) => warnings.push(format!("{label}: PayloadVersion is {version}, not 1")),
+            None => warnings.push(format!("{label}: PayloadVersion is missing")),
+        }
+
+        let Some(uuid) = payload.get("PayloadUUID").and_then(Value::as_string) else {
+            warnings.push(format!("{label}: PayloadUUID is missing"));
+            continue;
+        };
+        if uuid::Uuid::parse_str(uuid).is_err() {
+            warnings.push(format!("{label}: PayloadUUID {uuid} is not a valid UUID"));
+        }
+        // Devices compare UUIDs irrespective of case.
+        match seen_uuids.get(&uuid.to_uppercase()) {
+            Some(other_label) => warnings.push(format!(
+                "{label}: PayloadUUID {uuid} is also used by {other_label}"
+            )),
+            None => {
+                seen_uuids.insert(uuid.to_uppercase(), label);
+            }
+        }
+    }
+
+    for key in profile.keys() {
+        if !BASE_KEYS.contains(&key.as_str()) && !PROFILE_KEYS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "{}: {key} is not supported and will not be retained",
+                payloads[0].0
+            ));
+        }
+    }
+    warnings
+}
+
+/// Reads the profile into its typed representation, where possible.
+pub(super) fn read_profile(
+    profile: &Dictionary,
+    warnings: &mut Vec<String>,
+) -> Option<Profile<Payload>> {
+    let label = payload_label(profile, None);
+    let base = match deserialize::<BasePayload>(profile) {
+        Ok(base) => base,
+        Err(e) => {
+            warnings.push(format!("{label}: unable to import - {e}"));
+            return None;
+        }
+    };
+    let scope = match profile.get("PayloadScope") {
+        Some(value) => match plist::from_value::<PayloadScope>(value) {
+            Ok(scope) => Some(scope),
+            Err(e) => {
+                warnings.push(format!("{label}: unable to import PayloadScope - {e}"));
+                return None;
+            }
+        },
+        None => None,
+    };
+    let options = match deserialize::<ProfileOptions>(profile) {
+        Ok(options) => options,
+        Err(e) => {
+            warnings.push(format!("{label}: unable to import - {e}"));
+            return None;
+        }
+    };
+
+    let contents = match profile.get("PayloadContent") {
+        Some(Value::Array(contents)) => contents.as_slice(),
+        _ => &[],
+    };
+    let mut payloads = vec![];
+    for (index, value) in contents.iter().enumerate() {
+        let Value::Dictionary(payload) = value else {
+            return None;
+        };
+        let label = payload_label(payload, Some(index));
+        match read_payload(payload, &label, warnings) {
+            Ok(payload) => payloads.push(payload),
+            Err(e) => {
+                warnings.push(format!("{label}: unable to import - {e}"));
+                return None;
+            }
+        }
+    }
+
+    Some(Profile {
+        base,
+        contents: payloads,
+        scope,
+        options,
+    })
+}
+
+/// Reads a payload into its typed representation, if its type is known
+/// and we understand all keys within. Otherwise, it is kept raw.
+fn read_payload(
+    payload: &Dictionary,
+    label: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Payload, plist::Error> {
+    let base = deserialize::<BasePayload>(payload)?;
+    if let Some(typed_payload) = typed_payload(&base.payload_type, payload) {
+        match typed_payload {
+            Ok(typed_payload) => {
+                let typed_value = plist::to_value(&typed_payload)?;
+                let mut unknown_keys = vec![];
+                find_unknown_keys(
+                    &Value::Dictionary(payload.clone()),
+                    &typed_value,
+                    "",
+                    &mut unknown_keys,
+                );
+                if unknown_keys.is_empty() {
+                    return Ok(typed_payload);
+                }
+                for key in unknown_keys {
+                    warnings.push(format!("{label}: {key} is not a known key"));
+                }
+            }
+            Err(e) => warnings.push(format!(
+                "{label}: does not match its type {} - {e}",
+                base.payload_type.as_str()
+            )),
+        }
+    }
+
+    let mut contents = payload.clone();
+    for key in BASE_KEYS {
+        contents.remove(key);
+    }
+    Ok(Payload::Raw(RawPayload { base, contents }))
+}
+
+/// Deserializes the payload of the given type into its typed representation.
+/// Returns None if we have no such representation.
+fn typed_payload(
+    payload_type: &PayloadType,
+    payload: &Dictionary,
+) -> Option<Result<Payload, plist::Error>> {
+    let typed_payload = match payload_type {
+        PayloadType::Mdm => deserialize(payload).map(Payload::Mdm),
+        PayloadType::CertificateRoot => deserialize(payload).map(Payload::RootCertificate),
+        PayloadType::CertificatePkcs1 => deserialize(payload).map(Payload::Pkcs1Certificate),
+        PayloadType::CertificatePkcs12 => deserialize(payload).map(Payload::Pkcs12Certificate),
+        PayloadType::Acme => deserialize(payload).map(Payload::AcmeCertificate),
+        PayloadType::Scep => deserialize(payload).map(Payload::Scep),
+        PayloadType::FileVaultEscrow => deserialize(payload).map(Payload::FileVaultEscrow),
+        PayloadType::Font => deserialize(payload).map(Payload::Font),
+        PayloadType::WiFi => deserialize(payload).map(Payload::WiFi),
+        PayloadType::Vpn => deserialize(payload).map(Payload::Vpn),
+        PayloadType::AppLayerVpn => deserialize(payload).map(Payload::AppLayerVpn),
+        PayloadType::VpnAppMapping => deserialize(payload).map(Payload::VpnAppMapping),
+        PayloadType::Passcode => deserialize(payload).map(Payload::Passcode),
+        PayloadType::Restrictions => deserialize(payload).map(Payload::Restrictions),
+        PayloadType::WebClip => deserialize(payload).map(Payload::WebClip),
+        PayloadType::HomeScreenLayout => deserialize(payload).map(Payload::HomeScreenLayout),
+        PayloadType::Mail => deserialize(payload).map(Payload::Mail),
+        PayloadType::ExchangeActiveSync => deserialize(payload).map(Payload::ExchangeActiveSync),
+        PayloadType::CalDav => deserialize(payload).map(Payload::CalDav),
+        PayloadType::CardDav => deserialize(payload).map(Payload::CardDav),
+        PayloadType::PrivacyPreferences => deserialize(payload).map(Payload::PrivacyPreferences),
+        PayloadType::SystemExtensionPolicy => {
+            deserialize(payload).map(Payload::SystemExtensionPolicy)
+        }
+        PayloadType::KernelExtensionPolicy => {
+            deserialize(payload).map(Payload::KernelExtensionPolicy)
+        }
+        PayloadType::ServiceManagement => deserialize(payload).map(Payload::ServiceManagement),
+        PayloadType::ManagedPreferences => deserialize(payload).map(Payload::ManagedPreferences),
+        PayloadType::ProfileRemovalPassword => {
+            deserialize(payload).map(Payload::ProfileRemovalPassword)
+        }
+        // Neither of these are valid within a profile's contents.
+        PayloadType::Configuration | PayloadType::ProfileService => return None,
+        PayloadType::Custom(_) => return None,
+    };
+    Some(typed_payload)
+}
+
+fn deserialize<T: DeserializeOwned>(payload: &Dictionary) -> Result<T, plist::Error> {
+    plist::from_value(&Value::Dictionary(payload.clone()))
+}
+
+/// Collects the path of every key present within the original value,
+/// but absent from its typed representation.
+fn find_unknown_keys(original: &Value, typed: &Value, path: &str, unknown_keys: &mut Vec<String>) {
+    match (original, typed) {
+        (Value::Dictionary(original), Value::Dictionary(typed)) => {
+            for (key, value) in original {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match typed.get(key) {
+                    Some(typed_value) => {
+                        find_unknown_keys(value, typed_value, &key_path, unknown_keys)
+                    }
+                    None => unknown_keys.push(key_path),
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(typed)) => {
+            for (index, (value, typed_value)) in original.iter().zip(typed).enumerate() {
+                find_unknown_keys(
+                    value,
+                    typed_value,
+                    &format!("{path}[{index}]"),
+                    unknown_keys,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The non-empty identifier of this payload, if present.
+fn payload_identifier(payload: &Dictionary) -> Option<&str> {
+    payload
+        .get("PayloadIdentifier")
+        .and_then(Value::as_string)
+        .filter(|identifier| !identifier.is_empty())
+}
+
+/// How we refer to a payload within warnings: its identifier if present,
+/// or its position within the profile otherwise.
+fn payload_label(payload: &Dictionary, index: Option<usize>) -> String {
+    match (payload_identifier(payload), index) {
+        (Some(identifier), _) => identifier.to_string(),
+        (None, Some(index)) => format!("PayloadContent[{index}]"),
+        (None, None) => "profile".to_string(),
+    }
+}
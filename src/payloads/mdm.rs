@@ -0,0 +1,44 @@
+use optional_value::payload;
+use uuid::Uuid;
+
+use super::BasePayload;
+
+/// Permits every MDM command and query.
+/// https://developer.apple.com/documentation/devicemanagement/mdm#Access-Rights
+pub const ALL_ACCESS_RIGHTS: i32 = 8191;
+
+/// Indicates that we are able to escrow bootstrap tokens.
+pub const BOOTSTRAP_TOKEN_CAPABILITY: &str = "com.apple.mdm.bootstraptoken";
+
+#[payload]
+/// Enrolls a device into MDM.
+/// https://developer.apple.com/documentation/devicemanagement/mdm
+pub struct MdmPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "IdentityCertificateUUID")]
+    /// The UUID of the identity payload the device authenticates with.
+    /// It must be present within the same profile.
+    pub identity_certificate_uuid: Uuid,
+    #[serde(rename = "Topic")]
+    /// The topic of our APNs push certificate.
+    pub topic: String,
+    #[serde(rename = "ServerURL")]
+    /// Where the device retrieves commands, and reports their results.
+    pub server_url: String,
+    #[serde(rename = "CheckInURL")]
+    /// Where check-in messages are sent. If absent, the server URL is used.
+    pub check_in_url: Option<String>,
+    #[serde(rename = "SignMessage")]
+    /// Whether the device signs its messages via the Mdm-Signature header.
+    /// We require this to identify the device.
+    pub sign_message: Option<bool>,
+    #[serde(rename = "CheckOutWhenRemoved")]
+    /// Whether the device sends a CheckOut message when this profile is removed.
+    pub check_out_when_removed: Option<bool>,
+    #[serde(rename = "AccessRights")]
+    /// A bitmask of the commands and queries we may send.
+    pub access_rights: i32,
+    #[serde(rename = "ServerCapabilities")]
+    pub server_capabilities: Option<Vec<String>>,
+}
@@ -1,8 +1,48 @@
+mod accounts;
 mod base_payload;
 mod certificates;
+mod code_requirement;
+mod custom_settings;
+mod filevault;
+mod font;
+mod home_screen_layout;
+mod import;
+mod mdm;
+mod passcode;
+mod payload;
 mod payload_types;
-mod ser;
+mod privacy_preferences;
+mod removal_password;
+mod restrictions;
+pub(crate) mod ser;
+mod service_management;
+mod system_extensions;
+mod template;
+mod versioning;
+mod vpn;
+mod web_clip;
+mod wifi;
 
+pub use accounts::*;
 pub use base_payload::*;
 pub use certificates::*;
+pub use code_requirement::*;
+pub use custom_settings::*;
+pub use filevault::*;
+pub use font::*;
+pub use home_screen_layout::*;
+pub use import::*;
+pub use mdm::*;
+pub use passcode::*;
+pub use payload::*;
 pub use payload_types::*;
+pub use privacy_preferences::*;
+pub use removal_password::*;
+pub use restrictions::*;
+pub use service_management::*;
+pub use system_extensions::*;
+pub use template::*;
+pub use versioning::*;
+pub use vpn::*;
+pub use web_clip::*;
+pub use wifi::*;
@@ -0,0 +1,81 @@
+use optional_value::payload;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Enforces a passcode policy.
+/// https://developer.apple.com/documentation/devicemanagement/passcode
+pub struct PasscodePayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(flatten)]
+    pub policy: PasscodePolicy,
+}
+
+impl Default for PasscodePayload {
+    /// Creates a payload with no policy set.
+    fn default() -> Self {
+        PasscodePayload {
+            base: BasePayload {
+                payload_type: PayloadType::Passcode,
+                ..Default::default()
+            },
+            policy: PasscodePolicy::default(),
+        }
+    }
+}
+
+#[payload]
+#[derive(Default)]
+/// All passcode requirements available. Unset requirements are not enforced.
+pub struct PasscodePolicy {
+    #[serde(rename = "allowSimple")]
+    /// Whether repeating, ascending and descending sequences are permitted.
+    pub allow_simple: Option<bool>,
+    #[serde(rename = "changeAtNextAuth")]
+    /// Whether the user must change their password upon next authentication. Only available on macOS.
+    pub change_at_next_auth: Option<bool>,
+    #[serde(rename = "customRegex")]
+    /// Only available on macOS.
+    pub custom_regex: Option<PasscodeCustomRegex>,
+    #[serde(rename = "forcePIN")]
+    /// Whether a passcode must be set.
+    pub force_pin: Option<bool>,
+    #[serde(rename = "maxFailedAttempts")]
+    /// Failed attempts permitted before the device is erased (iOS) or locked (macOS), between 2 and 11.
+    pub max_failed_attempts: Option<i32>,
+    #[serde(rename = "maxGracePeriod")]
+    /// Minutes after locking before a passcode is required to unlock.
+    pub max_grace_period: Option<i32>,
+    #[serde(rename = "maxInactivity")]
+    /// Minutes of inactivity before the device locks.
+    pub max_inactivity: Option<i32>,
+    #[serde(rename = "maxPINAgeInDays")]
+    /// Days before the passcode must be changed, between 1 and 730.
+    pub max_pin_age_in_days: Option<i32>,
+    #[serde(rename = "minComplexChars")]
+    /// The minimum number of non-alphanumeric characters, between 0 and 4.
+    pub min_complex_chars: Option<i32>,
+    #[serde(rename = "minLength")]
+    /// The minimum length of the passcode, between 0 and 16.
+    pub min_length: Option<i32>,
+    #[serde(rename = "minutesUntilFailedLoginReset")]
+    /// Minutes before failed attempts are reset. Only available on macOS.
+    pub minutes_until_failed_login_reset: Option<i32>,
+    #[serde(rename = "pinHistory")]
+    /// The number of previous passcodes which may not be reused, between 1 and 50.
+    pub pin_history: Option<i32>,
+    #[serde(rename = "requireAlphanumeric")]
+    /// Whether the passcode must contain both letters and numbers.
+    pub require_alphanumeric: Option<bool>,
+}
+
+#[payload]
+pub struct PasscodeCustomRegex {
+    #[serde(rename = "passwordContentRegex")]
+    /// An ICU regular expression passwords must match.
+    pub password_content_regex: String,
+    #[serde(rename = "passwordContentDescription")]
+    /// Localized descriptions of this requirement, keyed by language (or "default").
+    pub password_content_description: Option<std::collections::BTreeMap<String, String>>,
+}
@@ -0,0 +1,296 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{
+    AcmeCertificatePayload, AppLayerVpnPayload, BasePayload, CalDavPayload, CardDavPayload,
+    CustomSettingsPayload, ExchangeActiveSyncPayload, FileVaultEscrowPayload, FontPayload,
+    HomeScreenItem, HomeScreenLayoutPayload, KernelExtensionPolicyPayload, MailPayload,
+    ManagedPreferencesPayload, MdmPayload, PasscodePayload, Pkcs1CertificatePayload,
+    Pkcs12CertificatePayload, PrivacyPreferencesPayload, Profile, ProfileRemovalPasswordPayload,
+    RawPayload, RestrictionsPayload, RootCertificatePayload, ScepPayload, ServiceManagementPayload,
+    SystemExtensionPolicyPayload, VpnAppMappingPayload, VpnPayload, WebClipPayload, WiFiPayload,
+    is_valid_team_identifier, reserved_setting_keys, validate_code_requirement,
+};
+
+#[derive(Clone, Serialize)]
+#[serde(untagged)]
+/// Any payload we are able to provide.
+/// This permits a profile to contain payloads of differing types.
+// Profiles only contain a handful of payloads, so their size is of little concern.
+#[allow(clippy::large_enum_variant)]
+pub enum Payload {
+    Mdm(MdmPayload),
+    RootCertificate(RootCertificatePayload),
+    Pkcs1Certificate(Pkcs1CertificatePayload),
+    Pkcs12Certificate(Pkcs12CertificatePayload),
+    AcmeCertificate(AcmeCertificatePayload),
+    Scep(ScepPayload),
+    FileVaultEscrow(FileVaultEscrowPayload),
+    Font(FontPayload),
+    WiFi(WiFiPayload),
+    Vpn(VpnPayload),
+    AppLayerVpn(AppLayerVpnPayload),
+    VpnAppMapping(VpnAppMappingPayload),
+    Passcode(PasscodePayload),
+    Restrictions(RestrictionsPayload),
+    WebClip(WebClipPayload),
+    HomeScreenLayout(HomeScreenLayoutPayload),
+    Mail(MailPayload),
+    ExchangeActiveSync(ExchangeActiveSyncPayload),
+    CalDav(CalDavPayload),
+    CardDav(CardDavPayload),
+    PrivacyPreferences(PrivacyPreferencesPayload),
+    SystemExtensionPolicy(SystemExtensionPolicyPayload),
+    KernelExtensionPolicy(KernelExtensionPolicyPayload),
+    ServiceManagement(ServiceManagementPayload),
+    ManagedPreferences(ManagedPreferencesPayload),
+    CustomSettings(CustomSettingsPayload),
+    ProfileRemovalPassword(ProfileRemovalPasswordPayload),
+    /// A payload imported as-is, such as one of a type we do not know.
+    Raw(RawPayload),
+}
+
+impl Payload {
+    /// The common keys of this payload.
+    pub fn base(&self) -> &BasePayload {
+        match self {
+            Payload::Mdm(payload) => &payload.base,
+            Payload::RootCertificate(payload) => &payload.base,
+            Payload::Pkcs1Certificate(payload) => &payload.base,
+            Payload::Pkcs12Certificate(payload) => &payload.base,
+            Payload::AcmeCertificate(payload) => &payload.base,
+            Payload::Scep(payload) => &payload.base,
+            Payload::FileVaultEscrow(payload) => &payload.base,
+            Payload::Font(payload) => &payload.base,
+            Payload::WiFi(payload) => &payload.base,
+            Payload::Vpn(payload) => &payload.base,
+            Payload::AppLayerVpn(payload) => &payload.base,
+            Payload::VpnAppMapping(payload) => &payload.base,
+            Payload::Passcode(payload) => &payload.base,
+            Payload::Restrictions(payload) => &payload.base,
+            Payload::WebClip(payload) => &payload.base,
+            Payload::HomeScreenLayout(payload) => &payload.base,
+            Payload::Mail(payload) => &payload.base,
+            Payload::ExchangeActiveSync(payload) => &payload.base,
+            Payload::CalDav(payload) => &payload.base,
+            Payload::CardDav(payload) => &payload.base,
+            Payload::PrivacyPreferences(payload) => &payload.base,
+            Payload::SystemExtensionPolicy(payload) => &payload.base,
+            Payload::KernelExtensionPolicy(payload) => &payload.base,
+            Payload::ServiceManagement(payload) => &payload.base,
+            Payload::ManagedPreferences(payload) => &payload.base,
+            Payload::CustomSettings(payload) => &payload.base,
+            Payload::ProfileRemovalPassword(payload) => &payload.base,
+            Payload::Raw(payload) => &payload.base,
+        }
+    }
+
+    /// Keys set within this payload which are only honored on supervised devices.
+    pub fn supervised_only_keys(&self) -> Vec<&'static str> {
+        match self {
+            Payload::Restrictions(payload) => payload.restrictions.supervised_only_keys(),
+            // This payload is ignored entirely on unsupervised devices.
+            Payload::HomeScreenLayout(_) => vec!["Pages"],
+            _ => vec![],
+        }
+    }
+
+    /// The UUIDs of certificate payloads this payload refers to.
+    /// These must be present within the same profile.
+    pub fn certificate_references(&self) -> Vec<Uuid> {
+        let mut references = vec![];
+        match self {
+            Payload::Mdm(payload) => {
+                references.push(payload.identity_certificate_uuid);
+            }
+            Payload::FileVaultEscrow(payload) => {
+                references.push(payload.encrypt_cert_payload_uuid);
+            }
+            Payload::WiFi(payload) => {
+                references.extend(payload.payload_certificate_uuid);
+                if let Some(eap_config) = &payload.eap_client_configuration {
+                    references.extend(eap_config.payload_certificate_anchor_uuid.iter().flatten());
+                }
+            }
+            Payload::Vpn(payload) => {
+                references.extend(
+                    payload
+                        .ikev2
+                        .iter()
+                        .flat_map(|ikev2| ikev2.payload_certificate_uuid),
+                );
+                let tunnels = payload
+                    .always_on
+                    .iter()
+                    .flat_map(|always_on| &always_on.tunnel_configurations);
+                references.extend(tunnels.flat_map(|tunnel| tunnel.ikev2.payload_certificate_uuid));
+            }
+            Payload::AppLayerVpn(payload) => {
+                references.extend(
+                    payload
+                        .ikev2
+                        .iter()
+                        .flat_map(|ikev2| ikev2.payload_certificate_uuid),
+                );
+            }
+            Payload::ExchangeActiveSync(payload) => {
+                references.extend(payload.payload_certificate_uuid);
+            }
+            _ => {}
+        }
+        references
+    }
+
+    /// Describes every policy within this payload which devices would reject or ignore.
+    pub fn policy_warnings(&self) -> Vec<String> {
+        match self {
+            Payload::PrivacyPreferences(payload) => payload.policy_warnings(),
+            Payload::VpnAppMapping(payload) => payload
+                .app_layer_vpn_mapping
+                .iter()
+                .filter_map(|mapping| {
+                    let reason =
+                        validate_code_requirement(&mapping.designated_requirement).err()?;
+                    Some(format!(
+                        "designated requirement for {} is invalid - {reason}",
+                        mapping.identifier
+                    ))
+                })
+                .collect(),
+            Payload::SystemExtensionPolicy(payload) => {
+                team_identifier_warnings(payload.team_identifiers())
+            }
+            Payload::KernelExtensionPolicy(payload) => {
+                team_identifier_warnings(payload.team_identifiers())
+            }
+            Payload::ServiceManagement(payload) => {
+                team_identifier_warnings(payload.team_identifiers())
+            }
+            Payload::CustomSettings(payload) => reserved_setting_keys(&payload.settings)
+                .into_iter()
+                .map(|key| format!("{key} is reserved and may not be used as a setting"))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The bundle identifiers of apps this payload refers to.
+    /// These are expected to be installed on the device.
+    pub fn bundle_identifiers(&self) -> Vec<&str> {
+        match self {
+            Payload::WebClip(payload) => payload
+                .target_application_bundle_identifier
+                .iter()
+                .map(String::as_str)
+                .collect(),
+            Payload::HomeScreenLayout(payload) => payload
+                .items()
+                .into_iter()
+                .filter_map(|item| match item {
+                    HomeScreenItem::Application { bundle_id } => Some(bundle_id.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            Payload::VpnAppMapping(payload) => payload
+                .app_layer_vpn_mapping
+                .iter()
+                .map(|mapping| mapping.identifier.as_str())
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Describes every malformed team identifier.
+fn team_identifier_warnings(team_ids: Vec<&str>) -> Vec<String> {
+    team_ids
+        .into_iter()
+        .filter(|team_id| !is_valid_team_identifier(team_id))
+        .map(|team_id| format!("team identifier {team_id} is invalid"))
+        .collect()
+}
+
+impl Profile<Payload> {
+    /// Describes every certificate reference which does not resolve
+    /// to a certificate, PKCS #12, SCEP or ACME payload within this profile.
+    pub fn unresolved_references(&self) -> Vec<String> {
+        self.contents
+            .iter()
+            .flat_map(|payload| {
+                let identifier = &payload.base().identifier;
+                payload
+                    .certificate_references()
+                    .into_iter()
+                    .filter_map(move |reference| {
+                        match self.find_payload(&reference) {
+                            None => Some(format!(
+                                "{identifier}: certificate payload {reference} is not present"
+                            )),
+                            Some(target) if !target.base().payload_type.is_certificate() => {
+                                Some(format!(
+                                    "{identifier}: payload {reference} is of type {}, not a certificate payload",
+                                    target.base().payload_type.as_str()
+                                ))
+                            }
+                            Some(_) => None,
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    /// Describes every policy devices would reject or ignore,
+    /// such as those containing malformed code requirements.
+    pub fn policy_warnings(&self) -> Vec<String> {
+        self.contents
+            .iter()
+            .flat_map(|payload| {
+                let identifier = &payload.base().identifier;
+                payload
+                    .policy_warnings()
+                    .into_iter()
+                    .map(move |warning| format!("{identifier}: {warning}"))
+            })
+            .collect()
+    }
+
+    /// The payload with the given UUID within this profile, if present.
+    pub fn find_payload(&self, uuid: &Uuid) -> Option<&Payload> {
+        self.contents
+            .iter()
+            .find(|payload| &payload.base().uuid == uuid)
+    }
+
+    /// Describes every supervised-only key within this profile.
+    /// These are ignored if the profile is installed on an unsupervised device.
+    pub fn supervision_warnings(&self) -> Vec<String> {
+        self.contents
+            .iter()
+            .flat_map(|payload| {
+                let identifier = &payload.base().identifier;
+                payload
+                    .supervised_only_keys()
+                    .into_iter()
+                    .map(move |key| format!("{identifier}: {key} requires a supervised device"))
+            })
+            .collect()
+    }
+
+    /// Describes every app this profile refers to which is not installed.
+    /// Built-in apps (those within com.apple.*) are assumed to be present,
+    /// as devices may omit them when reporting their installed applications.
+    pub fn missing_applications(&self, installed: &[String]) -> Vec<String> {
+        self.contents
+            .iter()
+            .flat_map(|payload| {
+                let identifier = &payload.base().identifier;
+                payload
+                    .bundle_identifiers()
+                    .into_iter()
+                    .filter(|bundle_id| !bundle_id.starts_with("com.apple."))
+                    .filter(|bundle_id| !installed.iter().any(|app| app == bundle_id))
+                    .map(move |bundle_id| format!("{identifier}: app {bundle_id} is not installed"))
+            })
+            .collect()
+    }
+}
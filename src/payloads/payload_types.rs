@@ -1,21 +1,133 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
-#[serde(into = "&str")]
+#[serde(into = "String", from = "String")]
 pub enum PayloadType {
     Configuration,
     ProfileService,
+    Mdm,
     CertificateRoot,
+    CertificatePkcs1,
+    CertificatePkcs12,
+    Acme,
     Scep,
+    FileVaultEscrow,
+    Font,
+    WiFi,
+    Vpn,
+    AppLayerVpn,
+    VpnAppMapping,
+    Passcode,
+    Restrictions,
+    WebClip,
+    HomeScreenLayout,
+    Mail,
+    ExchangeActiveSync,
+    CalDav,
+    CardDav,
+    PrivacyPreferences,
+    SystemExtensionPolicy,
+    KernelExtensionPolicy,
+    ServiceManagement,
+    ManagedPreferences,
+    ProfileRemovalPassword,
+    /// Any other type, such as the preference domain of custom settings.
+    Custom(String),
 }
 
-impl From<PayloadType> for &str {
-    fn from(value: PayloadType) -> Self {
-        match value {
+impl PayloadType {
+    /// Every type other than custom types.
+    const KNOWN: [PayloadType; 28] = [
+        PayloadType::Configuration,
+        PayloadType::ProfileService,
+        PayloadType::Mdm,
+        PayloadType::CertificateRoot,
+        PayloadType::CertificatePkcs1,
+        PayloadType::CertificatePkcs12,
+        PayloadType::Acme,
+        PayloadType::Scep,
+        PayloadType::FileVaultEscrow,
+        PayloadType::Font,
+        PayloadType::WiFi,
+        PayloadType::Vpn,
+        PayloadType::AppLayerVpn,
+        PayloadType::VpnAppMapping,
+        PayloadType::Passcode,
+        PayloadType::Restrictions,
+        PayloadType::WebClip,
+        PayloadType::HomeScreenLayout,
+        PayloadType::Mail,
+        PayloadType::ExchangeActiveSync,
+        PayloadType::CalDav,
+        PayloadType::CardDav,
+        PayloadType::PrivacyPreferences,
+        PayloadType::SystemExtensionPolicy,
+        PayloadType::KernelExtensionPolicy,
+        PayloadType::ServiceManagement,
+        PayloadType::ManagedPreferences,
+        PayloadType::ProfileRemovalPassword,
+    ];
+
+    /// The value of PayloadType for this type.
+    pub fn as_str(&self) -> &str {
+        match self {
             PayloadType::Configuration => "Configuration",
             PayloadType::ProfileService => "Profile Service",
+            PayloadType::Mdm => "com.apple.mdm",
             PayloadType::CertificateRoot => "com.apple.security.root",
+            PayloadType::CertificatePkcs1 => "com.apple.security.pkcs1",
+            PayloadType::CertificatePkcs12 => "com.apple.security.pkcs12",
+            PayloadType::Acme => "com.apple.security.acme",
             PayloadType::Scep => "com.apple.security.scep",
+            PayloadType::FileVaultEscrow => "com.apple.security.FDERecoveryKeyEscrow",
+            PayloadType::Font => "com.apple.font",
+            PayloadType::WiFi => "com.apple.wifi.managed",
+            PayloadType::Vpn => "com.apple.vpn.managed",
+            PayloadType::AppLayerVpn => "com.apple.vpn.managed.applayer",
+            PayloadType::VpnAppMapping => "com.apple.vpn.managed.appmapping",
+            PayloadType::Passcode => "com.apple.mobiledevice.passwordpolicy",
+            PayloadType::Restrictions => "com.apple.applicationaccess",
+            PayloadType::WebClip => "com.apple.webClip.managed",
+            PayloadType::HomeScreenLayout => "com.apple.homescreenlayout",
+            PayloadType::Mail => "com.apple.mail.managed",
+            PayloadType::ExchangeActiveSync => "com.apple.eas.account",
+            PayloadType::CalDav => "com.apple.caldav.account",
+            PayloadType::CardDav => "com.apple.carddav.account",
+            PayloadType::PrivacyPreferences => "com.apple.TCC.configuration-profile-policy",
+            PayloadType::SystemExtensionPolicy => "com.apple.system-extension-policy",
+            PayloadType::KernelExtensionPolicy => "com.apple.syspolicy.kernel-extension-policy",
+            PayloadType::ServiceManagement => "com.apple.servicemanagement",
+            PayloadType::ManagedPreferences => "com.apple.ManagedClient.preferences",
+            PayloadType::ProfileRemovalPassword => "com.apple.profileRemovalPassword",
+            PayloadType::Custom(payload_type) => payload_type,
         }
     }
+
+    /// Whether payloads of this type provide a certificate or identity
+    /// which other payloads may reference by UUID.
+    pub fn is_certificate(&self) -> bool {
+        matches!(
+            self,
+            PayloadType::CertificateRoot
+                | PayloadType::CertificatePkcs1
+                | PayloadType::CertificatePkcs12
+                | PayloadType::Acme
+                | PayloadType::Scep
+        )
+    }
+}
+
+impl From<PayloadType> for String {
+    fn from(value: PayloadType) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl From<String> for PayloadType {
+    fn from(value: String) -> Self {
+        PayloadType::KNOWN
+            .into_iter()
+            .find(|payload_type| payload_type.as_str() == value)
+            .unwrap_or(PayloadType::Custom(value))
+    }
 }
@@ -0,0 +1,250 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+
+use super::{BasePayload, PayloadType, validate_code_requirement};
+
+#[payload]
+/// Grants or denies apps access to privacy-protected services on macOS,
+/// such as Full Disk Access. Only honored when installed via MDM.
+/// https://developer.apple.com/documentation/devicemanagement/privacypreferencespolicycontrol
+pub struct PrivacyPreferencesPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "Services")]
+    pub services: PrivacyServices,
+}
+
+impl Default for PrivacyPreferencesPayload {
+    /// Creates a payload with no services configured.
+    fn default() -> Self {
+        PrivacyPreferencesPayload {
+            base: BasePayload {
+                payload_type: PayloadType::PrivacyPreferences,
+                ..Default::default()
+            },
+            services: PrivacyServices::default(),
+        }
+    }
+}
+
+#[payload]
+#[derive(Default)]
+/// The apps granted or denied access to each service.
+/// https://developer.apple.com/documentation/devicemanagement/privacypreferencespolicycontrol/services
+///
+/// Camera, Microphone, ListenEvent and ScreenCapture may only be denied;
+/// see `RESTRICTED_SERVICES`.
+pub struct PrivacyServices {
+    #[serde(rename = "Accessibility")]
+    pub accessibility: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "AddressBook")]
+    pub address_book: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "AppleEvents")]
+    /// Each identity must specify the app receiving its Apple events.
+    pub apple_events: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "BluetoothAlways")]
+    pub bluetooth_always: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "Calendar")]
+    pub calendar: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "Camera")]
+    pub camera: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "FileProviderPresence")]
+    pub file_provider_presence: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "ListenEvent")]
+    /// Input monitoring.
+    pub listen_event: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "MediaLibrary")]
+    pub media_library: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "Microphone")]
+    pub microphone: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "Photos")]
+    pub photos: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "PostEvent")]
+    /// Sending keystrokes and other events via CoreGraphics.
+    pub post_event: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "Reminders")]
+    pub reminders: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "ScreenCapture")]
+    pub screen_capture: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SpeechRecognition")]
+    pub speech_recognition: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyAllFiles")]
+    /// Full Disk Access.
+    pub system_policy_all_files: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyAppBundles")]
+    /// Modifying other apps.
+    pub system_policy_app_bundles: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyAppData")]
+    /// Accessing data of other apps.
+    pub system_policy_app_data: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyDesktopFolder")]
+    pub system_policy_desktop_folder: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyDocumentsFolder")]
+    pub system_policy_documents_folder: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyDownloadsFolder")]
+    pub system_policy_downloads_folder: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyNetworkVolumes")]
+    pub system_policy_network_volumes: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicyRemovableVolumes")]
+    pub system_policy_removable_volumes: Option<Vec<PrivacyIdentity>>,
+    #[serde(rename = "SystemPolicySysAdminFiles")]
+    pub system_policy_sys_admin_files: Option<Vec<PrivacyIdentity>>,
+}
+
+impl PrivacyServices {
+    /// Every configured service, alongside its key and identities.
+    pub fn all(&self) -> Vec<(&'static str, &Vec<PrivacyIdentity>)> {
+        let services = [
+            ("Accessibility", &self.accessibility),
+            ("AddressBook", &self.address_book),
+            ("AppleEvents", &self.apple_events),
+            ("BluetoothAlways", &self.bluetooth_always),
+            ("Calendar", &self.calendar),
+            ("Camera", &self.camera),
+            ("FileProviderPresence", &self.file_provider_presence),
+            ("ListenEvent", &self.listen_event),
+            ("MediaLibrary", &self.media_library),
+            ("Microphone", &self.microphone),
+            ("Photos", &self.photos),
+            ("PostEvent", &self.post_event),
+            ("Reminders", &self.reminders),
+            ("ScreenCapture", &self.screen_capture),
+            ("SpeechRecognition", &self.speech_recognition),
+            ("SystemPolicyAllFiles", &self.system_policy_all_files),
+            ("SystemPolicyAppBundles", &self.system_policy_app_bundles),
+            ("SystemPolicyAppData", &self.system_policy_app_data),
+            (
+                "SystemPolicyDesktopFolder",
+                &self.system_policy_desktop_folder,
+            ),
+            (
+                "SystemPolicyDocumentsFolder",
+                &self.system_policy_documents_folder,
+            ),
+            (
+                "SystemPolicyDownloadsFolder",
+                &self.system_policy_downloads_folder,
+            ),
+            (
+                "SystemPolicyNetworkVolumes",
+                &self.system_policy_network_volumes,
+            ),
+            (
+                "SystemPolicyRemovableVolumes",
+                &self.system_policy_removable_volumes,
+            ),
+            (
+                "SystemPolicySysAdminFiles",
+                &self.system_policy_sys_admin_files,
+            ),
+        ];
+        services
+            .into_iter()
+            .filter_map(|(key, identities)| Some((key, identities.as_ref()?)))
+            .collect()
+    }
+}
+
+/// Services which the user must grant access to themselves.
+/// These may only be denied, or delegated to standard users.
+pub const RESTRICTED_SERVICES: [&str; 4] = ["Camera", "Microphone", "ListenEvent", "ScreenCapture"];
+
+#[payload]
+/// An app granted or denied access to a service.
+/// https://developer.apple.com/documentation/devicemanagement/privacypreferencespolicycontrol/services/identity
+pub struct PrivacyIdentity {
+    #[serde(rename = "Identifier")]
+    /// Either a bundle identifier or an absolute path, per `IdentifierType`.
+    pub identifier: String,
+    #[serde(rename = "IdentifierType")]
+    pub identifier_type: PrivacyIdentifierType,
+    #[serde(rename = "CodeRequirement")]
+    /// The app's designated requirement, as output by `codesign -display -r -`.
+    pub code_requirement: String,
+    #[serde(rename = "Allowed")]
+    /// Prefer `Authorization`. Exactly one of these must be specified.
+    pub allowed: Option<bool>,
+    #[serde(rename = "Authorization")]
+    pub authorization: Option<PrivacyAuthorization>,
+    #[serde(rename = "StaticCode")]
+    /// Whether the app's code should be validated statically, rather than when running.
+    pub static_code: Option<bool>,
+    #[serde(rename = "Comment")]
+    pub comment: Option<String>,
+    #[serde(rename = "AEReceiverIdentifier")]
+    /// The app receiving Apple events. Required for AppleEvents.
+    pub ae_receiver_identifier: Option<String>,
+    #[serde(rename = "AEReceiverIdentifierType")]
+    pub ae_receiver_identifier_type: Option<PrivacyIdentifierType>,
+    #[serde(rename = "AEReceiverCodeRequirement")]
+    pub ae_receiver_code_requirement: Option<String>,
+}
+
+impl PrivacyIdentity {
+    /// Whether this identity grants access, rather than denying it.
+    pub fn grants_access(&self) -> bool {
+        matches!(self.authorization, Some(PrivacyAuthorization::Allow))
+            || self.allowed == Some(true)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PrivacyIdentifierType {
+    #[serde(rename = "bundleID")]
+    BundleId,
+    #[serde(rename = "path")]
+    Path,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PrivacyAuthorization {
+    Allow,
+    Deny,
+    /// Permits standard users to grant access to ListenEvent and ScreenCapture.
+    AllowStandardUserToSetSystemService,
+}
+
+impl PrivacyPreferencesPayload {
+    /// Describes every identity which devices would reject or ignore,
+    /// such as those with malformed code requirements.
+    pub fn policy_warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        for (service, identities) in self.services.all() {
+            for identity in identities {
+                let identifier = &identity.identifier;
+                if let Err(reason) = validate_code_requirement(&identity.code_requirement) {
+                    warnings.push(format!(
+                        "{service}: code requirement for {identifier} is invalid - {reason}"
+                    ));
+                }
+                if let Some(requirement) = &identity.ae_receiver_code_requirement
+                    && let Err(reason) = validate_code_requirement(requirement)
+                {
+                    warnings.push(format!(
+                        "{service}: receiver code requirement for {identifier} is invalid - {reason}"
+                    ));
+                }
+                if identity.allowed.is_some() == identity.authorization.is_some() {
+                    warnings.push(format!(
+                        "{service}: {identifier} must specify exactly one of Allowed or Authorization"
+                    ));
+                }
+                if service == "AppleEvents"
+                    && (identity.ae_receiver_identifier.is_none()
+                        || identity.ae_receiver_identifier_type.is_none()
+                        || identity.ae_receiver_code_requirement.is_none())
+                {
+                    warnings.push(format!(
+                        "{service}: {identifier} must specify its Apple event receiver"
+                    ));
+                }
+                if RESTRICTED_SERVICES.contains(&service) && identity.grants_access() {
+                    warnings.push(format!(
+                        "{service}: access for {identifier} may only be denied"
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
@@ -0,0 +1,40 @@
+use optional_value::payload;
+
+use super::{BasePayload, Payload, Profile};
+
+#[payload]
+/// Requires a password to remove the profile containing this payload.
+/// https://developer.apple.com/documentation/devicemanagement/profileremovalpassword
+pub struct ProfileRemovalPasswordPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "RemovalPassword")]
+    /// If not specified, the password stored by us for this profile is filled in
+    /// upon installation.
+    pub removal_password: Option<String>,
+}
+
+impl Profile<Payload> {
+    /// Fills in the given password for removal password payloads without one,
+    /// marking this profile as having a removal password if any are present.
+    /// The password is only requested if needed.
+    pub fn fill_removal_password(&mut self, password: impl FnOnce() -> String) {
+        let needs_password = self.contents.iter().any(|payload| {
+            matches!(payload, Payload::ProfileRemovalPassword(payload) if payload.removal_password.is_none())
+        });
+        let password = needs_password.then(password);
+
+        let mut has_removal_password = false;
+        for payload in &mut self.contents {
+            if let Payload::ProfileRemovalPassword(payload) = payload {
+                has_removal_password = true;
+                if payload.removal_password.is_none() {
+                    payload.removal_password = password.clone();
+                }
+            }
+        }
+        if has_removal_password {
+            self.options.has_removal_passcode = Some(true);
+        }
+    }
+}
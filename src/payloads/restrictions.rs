@@ -0,0 +1,414 @@
+use optional_value::payload;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Restricts device functionality.
+/// https://developer.apple.com/documentation/devicemanagement/restrictions
+pub struct RestrictionsPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(flatten)]
+    pub restrictions: Restrictions,
+}
+
+impl Default for RestrictionsPayload {
+    /// Creates a payload with no restrictions set.
+    fn default() -> Self {
+        RestrictionsPayload {
+            base: BasePayload {
+                payload_type: PayloadType::Restrictions,
+                ..Default::default()
+            },
+            restrictions: Restrictions::default(),
+        }
+    }
+}
+
+#[payload]
+#[derive(Default)]
+/// All restrictions available. Unset restrictions retain their default behavior.
+///
+/// Several are only honored on supervised devices - see `supervised_only_keys`.
+pub struct Restrictions {
+    #[serde(rename = "allowAccountModification")]
+    /// Whether the user may modify accounts, such as Apple ID or Mail. Supervised only.
+    pub allow_account_modification: Option<bool>,
+    #[serde(rename = "allowActivityContinuation")]
+    /// Whether Handoff is permitted.
+    pub allow_activity_continuation: Option<bool>,
+    #[serde(rename = "allowAddingGameCenterFriends")]
+    pub allow_adding_game_center_friends: Option<bool>,
+    #[serde(rename = "allowAirDrop")]
+    /// Supervised only.
+    pub allow_air_drop: Option<bool>,
+    #[serde(rename = "allowAirPlayIncomingRequests")]
+    /// Supervised only.
+    pub allow_air_play_incoming_requests: Option<bool>,
+    #[serde(rename = "allowAirPrint")]
+    /// Supervised only.
+    pub allow_air_print: Option<bool>,
+    #[serde(rename = "allowAirPrintCredentialsStorage")]
+    /// Supervised only.
+    pub allow_air_print_credentials_storage: Option<bool>,
+    #[serde(rename = "allowAirPrintiBeaconDiscovery")]
+    /// Supervised only.
+    pub allow_air_print_ibeacon_discovery: Option<bool>,
+    #[serde(rename = "allowAppCellularDataModification")]
+    /// Supervised only.
+    pub allow_app_cellular_data_modification: Option<bool>,
+    #[serde(rename = "allowAppClips")]
+    /// Supervised only.
+    pub allow_app_clips: Option<bool>,
+    #[serde(rename = "allowAppInstallation")]
+    /// Whether the App Store is available for installing apps. Supervised only.
+    pub allow_app_installation: Option<bool>,
+    #[serde(rename = "allowAppRemoval")]
+    /// Supervised only.
+    pub allow_app_removal: Option<bool>,
+    #[serde(rename = "allowApplePersonalizedAdvertising")]
+    pub allow_apple_personalized_advertising: Option<bool>,
+    #[serde(rename = "allowAssistant")]
+    /// Whether Siri is permitted.
+    pub allow_assistant: Option<bool>,
+    #[serde(rename = "allowAssistantWhileLocked")]
+    pub allow_assistant_while_locked: Option<bool>,
+    #[serde(rename = "allowAutoCorrection")]
+    /// Supervised only.
+    pub allow_auto_correction: Option<bool>,
+    #[serde(rename = "allowAutoUnlock")]
+    /// Whether an Apple Watch may unlock a Mac.
+    pub allow_auto_unlock: Option<bool>,
+    #[serde(rename = "allowAutomaticAppDownloads")]
+    /// Supervised only.
+    pub allow_automatic_app_downloads: Option<bool>,
+    #[serde(rename = "allowBluetoothModification")]
+    /// Supervised only.
+    pub allow_bluetooth_modification: Option<bool>,
+    #[serde(rename = "allowBookstore")]
+    /// Supervised only.
+    pub allow_bookstore: Option<bool>,
+    #[serde(rename = "allowBookstoreErotica")]
+    pub allow_bookstore_erotica: Option<bool>,
+    #[serde(rename = "allowCamera")]
+    pub allow_camera: Option<bool>,
+    #[serde(rename = "allowCellularPlanModification")]
+    /// Supervised only.
+    pub allow_cellular_plan_modification: Option<bool>,
+    #[serde(rename = "allowChat")]
+    /// Whether iMessage is permitted. Supervised only.
+    pub allow_chat: Option<bool>,
+    #[serde(rename = "allowCloudBackup")]
+    pub allow_cloud_backup: Option<bool>,
+    #[serde(rename = "allowCloudDocumentSync")]
+    pub allow_cloud_document_sync: Option<bool>,
+    #[serde(rename = "allowCloudKeychainSync")]
+    pub allow_cloud_keychain_sync: Option<bool>,
+    #[serde(rename = "allowCloudPhotoLibrary")]
+    pub allow_cloud_photo_library: Option<bool>,
+    #[serde(rename = "allowContentCaching")]
+    pub allow_content_caching: Option<bool>,
+    #[serde(rename = "allowDefinitionLookup")]
+    /// Supervised only.
+    pub allow_definition_lookup: Option<bool>,
+    #[serde(rename = "allowDeviceNameModification")]
+    /// Supervised only.
+    pub allow_device_name_modification: Option<bool>,
+    #[serde(rename = "allowDiagnosticSubmission")]
+    pub allow_diagnostic_submission: Option<bool>,
+    #[serde(rename = "allowDictation")]
+    /// Supervised only.
+    pub allow_dictation: Option<bool>,
+    #[serde(rename = "allowEnablingRestrictions")]
+    /// Whether the user may enable Screen Time restrictions. Supervised only.
+    pub allow_enabling_restrictions: Option<bool>,
+    #[serde(rename = "allowEnterpriseAppTrust")]
+    pub allow_enterprise_app_trust: Option<bool>,
+    #[serde(rename = "allowEnterpriseBookBackup")]
+    pub allow_enterprise_book_backup: Option<bool>,
+    #[serde(rename = "allowEraseContentAndSettings")]
+    /// Supervised only.
+    pub allow_erase_content_and_settings: Option<bool>,
+    #[serde(rename = "allowExplicitContent")]
+    pub allow_explicit_content: Option<bool>,
+    #[serde(rename = "allowFilesNetworkDriveAccess")]
+    /// Supervised only.
+    pub allow_files_network_drive_access: Option<bool>,
+    #[serde(rename = "allowFilesUSBDriveAccess")]
+    /// Supervised only.
+    pub allow_files_usb_drive_access: Option<bool>,
+    #[serde(rename = "allowFindMyDevice")]
+    /// Supervised only.
+    pub allow_find_my_device: Option<bool>,
+    #[serde(rename = "allowFindMyFriends")]
+    /// Supervised only.
+    pub allow_find_my_friends: Option<bool>,
+    #[serde(rename = "allowFindMyFriendsModification")]
+    /// Supervised only.
+    pub allow_find_my_friends_modification: Option<bool>,
+    #[serde(rename = "allowFingerprintForUnlock")]
+    /// Whether Touch ID or Face ID may unlock the device.
+    pub allow_fingerprint_for_unlock: Option<bool>,
+    #[serde(rename = "allowFingerprintModification")]
+    /// Supervised only.
+    pub allow_fingerprint_modification: Option<bool>,
+    #[serde(rename = "allowGameCenter")]
+    /// Supervised only.
+    pub allow_game_center: Option<bool>,
+    #[serde(rename = "allowGlobalBackgroundFetchWhenRoaming")]
+    pub allow_global_background_fetch_when_roaming: Option<bool>,
+    #[serde(rename = "allowInAppPurchases")]
+    pub allow_in_app_purchases: Option<bool>,
+    #[serde(rename = "allowKeyboardShortcuts")]
+    /// Supervised only.
+    pub allow_keyboard_shortcuts: Option<bool>,
+    #[serde(rename = "allowManagedAppsCloudSync")]
+    pub allow_managed_apps_cloud_sync: Option<bool>,
+    #[serde(rename = "allowMultiplayerGaming")]
+    pub allow_multiplayer_gaming: Option<bool>,
+    #[serde(rename = "allowMusicService")]
+    /// Supervised only.
+    pub allow_music_service: Option<bool>,
+    #[serde(rename = "allowNews")]
+    /// Supervised only.
+    pub allow_news: Option<bool>,
+    #[serde(rename = "allowNFC")]
+    /// Supervised only.
+    pub allow_nfc: Option<bool>,
+    #[serde(rename = "allowNotificationsModification")]
+    /// Supervised only.
+    pub allow_notifications_modification: Option<bool>,
+    #[serde(rename = "allowOpenFromManagedToUnmanaged")]
+    pub allow_open_from_managed_to_unmanaged: Option<bool>,
+    #[serde(rename = "allowOpenFromUnmanagedToManaged")]
+    pub allow_open_from_unmanaged_to_managed: Option<bool>,
+    #[serde(rename = "allowOTAPKIUpdates")]
+    pub allow_ota_pki_updates: Option<bool>,
+    #[serde(rename = "allowPairedWatch")]
+    /// Supervised only.
+    pub allow_paired_watch: Option<bool>,
+    #[serde(rename = "allowPassbookWhileLocked")]
+    pub allow_passbook_while_locked: Option<bool>,
+    #[serde(rename = "allowPasscodeModification")]
+    /// Supervised only.
+    pub allow_passcode_modification: Option<bool>,
+    #[serde(rename = "allowPasswordAutoFill")]
+    /// Supervised only.
+    pub allow_password_auto_fill: Option<bool>,
+    #[serde(rename = "allowPasswordProximityRequests")]
+    /// Supervised only.
+    pub allow_password_proximity_requests: Option<bool>,
+    #[serde(rename = "allowPasswordSharing")]
+    /// Supervised only.
+    pub allow_password_sharing: Option<bool>,
+    #[serde(rename = "allowPersonalHotspotModification")]
+    /// Supervised only.
+    pub allow_personal_hotspot_modification: Option<bool>,
+    #[serde(rename = "allowPhotoStream")]
+    pub allow_photo_stream: Option<bool>,
+    #[serde(rename = "allowPodcasts")]
+    /// Supervised only.
+    pub allow_podcasts: Option<bool>,
+    #[serde(rename = "allowPredictiveKeyboard")]
+    /// Supervised only.
+    pub allow_predictive_keyboard: Option<bool>,
+    #[serde(rename = "allowProximitySetupToNewDevice")]
+    /// Supervised only.
+    pub allow_proximity_setup_to_new_device: Option<bool>,
+    #[serde(rename = "allowRadioService")]
+    /// Supervised only.
+    pub allow_radio_service: Option<bool>,
+    #[serde(rename = "allowRemoteAppPairing")]
+    /// Supervised only.
+    pub allow_remote_app_pairing: Option<bool>,
+    #[serde(rename = "allowRemoteScreenObservation")]
+    /// Whether Classroom may observe the screen.
+    pub allow_remote_screen_observation: Option<bool>,
+    #[serde(rename = "allowSafari")]
+    pub allow_safari: Option<bool>,
+    #[serde(rename = "allowScreenShot")]
+    pub allow_screen_shot: Option<bool>,
+    #[serde(rename = "allowSharedStream")]
+    pub allow_shared_stream: Option<bool>,
+    #[serde(rename = "allowSpellCheck")]
+    /// Supervised only.
+    pub allow_spell_check: Option<bool>,
+    #[serde(rename = "allowSpotlightInternetResults")]
+    pub allow_spotlight_internet_results: Option<bool>,
+    #[serde(rename = "allowSystemAppRemoval")]
+    /// Supervised only.
+    pub allow_system_app_removal: Option<bool>,
+    #[serde(rename = "allowUIAppInstallation")]
+    /// Supervised only.
+    pub allow_ui_app_installation: Option<bool>,
+    #[serde(rename = "allowUIConfigurationProfileInstallation")]
+    /// Supervised only.
+    pub allow_ui_configuration_profile_installation: Option<bool>,
+    #[serde(rename = "allowUnmanagedReadManagedContacts")]
+    pub allow_unmanaged_read_managed_contacts: Option<bool>,
+    #[serde(rename = "allowUntrustedTLSPrompt")]
+    pub allow_untrusted_tls_prompt: Option<bool>,
+    #[serde(rename = "allowUSBRestrictedMode")]
+    /// Supervised only.
+    pub allow_usb_restricted_mode: Option<bool>,
+    #[serde(rename = "allowVideoConferencing")]
+    /// Whether FaceTime is permitted.
+    pub allow_video_conferencing: Option<bool>,
+    #[serde(rename = "allowVPNCreation")]
+    /// Supervised only.
+    pub allow_vpn_creation: Option<bool>,
+    #[serde(rename = "allowWallpaperModification")]
+    /// Supervised only.
+    pub allow_wallpaper_modification: Option<bool>,
+    #[serde(rename = "allowListedAppBundleIDs")]
+    /// If set, only these apps may be launched. Supervised only.
+    pub allow_listed_app_bundle_ids: Option<Vec<String>>,
+    #[serde(rename = "blockedAppBundleIDs")]
+    /// Apps which may not be launched. Supervised only.
+    pub blocked_app_bundle_ids: Option<Vec<String>>,
+    #[serde(rename = "autonomousSingleAppModePermittedAppIDs")]
+    /// Apps which may enter Single App Mode on their own. Supervised only.
+    pub autonomous_single_app_mode_permitted_app_ids: Option<Vec<String>>,
+    #[serde(rename = "enforcedSoftwareUpdateDelay")]
+    /// How many days to delay software updates, between 1 and 90. Supervised only.
+    pub enforced_software_update_delay: Option<i32>,
+    #[serde(rename = "forceAirDropUnmanaged")]
+    pub force_air_drop_unmanaged: Option<bool>,
+    #[serde(rename = "forceAirPrintTrustedTLSRequirement")]
+    /// Supervised only.
+    pub force_air_print_trusted_tls_requirement: Option<bool>,
+    #[serde(rename = "forceAssistantProfanityFilter")]
+    /// Supervised only.
+    pub force_assistant_profanity_filter: Option<bool>,
+    #[serde(rename = "forceAutomaticDateAndTime")]
+    /// Supervised only.
+    pub force_automatic_date_and_time: Option<bool>,
+    #[serde(rename = "forceBypassScreenCaptureAlert")]
+    /// Supervised only.
+    pub force_bypass_screen_capture_alert: Option<bool>,
+    #[serde(rename = "forceClassroomAutomaticallyJoinClasses")]
+    pub force_classroom_automatically_join_classes: Option<bool>,
+    #[serde(rename = "forceClassroomUnpromptedScreenObservation")]
+    pub force_classroom_unprompted_screen_observation: Option<bool>,
+    #[serde(rename = "forceDelayedSoftwareUpdates")]
+    /// Supervised only.
+    pub force_delayed_software_updates: Option<bool>,
+    #[serde(rename = "forceEncryptedBackup")]
+    pub force_encrypted_backup: Option<bool>,
+    #[serde(rename = "forceITunesStorePasswordEntry")]
+    pub force_itunes_store_password_entry: Option<bool>,
+    #[serde(rename = "forceLimitAdTracking")]
+    pub force_limit_ad_tracking: Option<bool>,
+    #[serde(rename = "forceWatchWristDetection")]
+    pub force_watch_wrist_detection: Option<bool>,
+    #[serde(rename = "forceWiFiPowerOn")]
+    /// Supervised only.
+    pub force_wifi_power_on: Option<bool>,
+    #[serde(rename = "forceWiFiToAllowedNetworksOnly")]
+    /// Supervised only.
+    pub force_wifi_to_allowed_networks_only: Option<bool>,
+    #[serde(rename = "ratingApps")]
+    /// The maximum app rating permitted, e.g. 1000 for all apps.
+    pub rating_apps: Option<i32>,
+    #[serde(rename = "ratingMovies")]
+    /// The maximum movie rating permitted, e.g. 1000 for all movies.
+    pub rating_movies: Option<i32>,
+    #[serde(rename = "ratingRegion")]
+    /// The region ratings are interpreted within, such as "us".
+    pub rating_region: Option<String>,
+    #[serde(rename = "ratingTVShows")]
+    /// The maximum TV show rating permitted, e.g. 1000 for all shows.
+    pub rating_tv_shows: Option<i32>,
+    #[serde(rename = "safariAcceptCookies")]
+    /// 0 never accepts cookies, 1 accepts from visited sites, 1.5 from the current site, and 2 always.
+    pub safari_accept_cookies: Option<f64>,
+    #[serde(rename = "safariAllowAutoFill")]
+    pub safari_allow_auto_fill: Option<bool>,
+    #[serde(rename = "safariAllowJavaScript")]
+    pub safari_allow_java_script: Option<bool>,
+    #[serde(rename = "safariAllowPopups")]
+    pub safari_allow_popups: Option<bool>,
+    #[serde(rename = "safariForceFraudWarning")]
+    pub safari_force_fraud_warning: Option<bool>,
+}
+
+/// Restrictions which are ignored on unsupervised devices.
+const SUPERVISED_ONLY_KEYS: &[&str] = &[
+    "allowAccountModification",
+    "allowAirDrop",
+    "allowAirPlayIncomingRequests",
+    "allowAirPrint",
+    "allowAirPrintCredentialsStorage",
+    "allowAirPrintiBeaconDiscovery",
+    "allowAppCellularDataModification",
+    "allowAppClips",
+    "allowAppInstallation",
+    "allowAppRemoval",
+    "allowAutoCorrection",
+    "allowAutomaticAppDownloads",
+    "allowBluetoothModification",
+    "allowBookstore",
+    "allowCellularPlanModification",
+    "allowChat",
+    "allowDefinitionLookup",
+    "allowDeviceNameModification",
+    "allowDictation",
+    "allowEnablingRestrictions",
+    "allowEraseContentAndSettings",
+    "allowFilesNetworkDriveAccess",
+    "allowFilesUSBDriveAccess",
+    "allowFindMyDevice",
+    "allowFindMyFriends",
+    "allowFindMyFriendsModification",
+    "allowFingerprintModification",
+    "allowGameCenter",
+    "allowKeyboardShortcuts",
+    "allowMusicService",
+    "allowNews",
+    "allowNFC",
+    "allowNotificationsModification",
+    "allowPairedWatch",
+    "allowPasscodeModification",
+    "allowPasswordAutoFill",
+    "allowPasswordProximityRequests",
+    "allowPasswordSharing",
+    "allowPersonalHotspotModification",
+    "allowPodcasts",
+    "allowPredictiveKeyboard",
+    "allowProximitySetupToNewDevice",
+    "allowRadioService",
+    "allowRemoteAppPairing",
+    "allowSpellCheck",
+    "allowSystemAppRemoval",
+    "allowUIAppInstallation",
+    "allowUIConfigurationProfileInstallation",
+    "allowUSBRestrictedMode",
+    "allowVPNCreation",
+    "allowWallpaperModification",
+    "allowListedAppBundleIDs",
+    "blockedAppBundleIDs",
+    "autonomousSingleAppModePermittedAppIDs",
+    "enforcedSoftwareUpdateDelay",
+    "forceAirPrintTrustedTLSRequirement",
+    "forceAssistantProfanityFilter",
+    "forceAutomaticDateAndTime",
+    "forceBypassScreenCaptureAlert",
+    "forceDelayedSoftwareUpdates",
+    "forceWiFiPowerOn",
+    "forceWiFiToAllowedNetworksOnly",
+];
+
+impl Restrictions {
+    /// The keys set within these restrictions that require supervision.
+    pub fn supervised_only_keys(&self) -> Vec<&'static str> {
+        let Ok(plist::Value::Dictionary(contents)) = plist::to_value(self) else {
+            return vec![];
+        };
+
+        SUPERVISED_ONLY_KEYS
+            .iter()
+            .filter(|key| contents.contains_key(key))
+            .copied()
+            .collect()
+    }
+}
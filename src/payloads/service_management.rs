@@ -0,0 +1,72 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Manages login items and background services on macOS,
+/// preventing users from disabling those matching its rules.
+/// https://developer.apple.com/documentation/devicemanagement/servicemanagementmanagedloginitems
+pub struct ServiceManagementPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "Rules")]
+    pub rules: Vec<ServiceManagementRule>,
+}
+
+impl Default for ServiceManagementPayload {
+    /// Creates a payload without any rules.
+    fn default() -> Self {
+        ServiceManagementPayload {
+            base: BasePayload {
+                payload_type: PayloadType::ServiceManagement,
+                ..Default::default()
+            },
+            rules: vec![],
+        }
+    }
+}
+
+#[payload]
+/// https://developer.apple.com/documentation/devicemanagement/servicemanagementmanagedloginitems/rulesitem
+pub struct ServiceManagementRule {
+    #[serde(rename = "RuleType")]
+    pub rule_type: ServiceManagementRuleType,
+    #[serde(rename = "RuleValue")]
+    /// The value matched against, per `RuleType`.
+    pub rule_value: String,
+    #[serde(rename = "TeamIdentifier")]
+    /// If set, items must additionally be signed by this team.
+    pub team_identifier: Option<String>,
+    #[serde(rename = "Comment")]
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ServiceManagementRuleType {
+    BundleIdentifier,
+    BundleIdentifierPrefix,
+    Label,
+    LabelPrefix,
+    Program,
+    ProgramPrefix,
+    TeamIdentifier,
+}
+
+impl ServiceManagementPayload {
+    /// Every team identifier this payload's rules refer to.
+    pub fn team_identifiers(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .flat_map(|rule| {
+                let rule_team_id = match rule.rule_type {
+                    ServiceManagementRuleType::TeamIdentifier => Some(rule.rule_value.as_str()),
+                    _ => None,
+                };
+                rule_team_id
+                    .into_iter()
+                    .chain(rule.team_identifier.as_deref())
+            })
+            .collect()
+    }
+}
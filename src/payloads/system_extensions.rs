@@ -0,0 +1,209 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{BasePayload, PayloadType};
+
+/// Bundle identifiers or extension types, keyed by team identifier.
+pub type TeamMap<T> = BTreeMap<String, Vec<T>>;
+
+#[payload]
+/// Allows system extensions to load on macOS without user approval.
+/// https://developer.apple.com/documentation/devicemanagement/systemextensions
+pub struct SystemExtensionPolicyPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "AllowUserOverrides")]
+    /// Whether users may approve additional extensions themselves.
+    pub allow_user_overrides: Option<bool>,
+    #[serde(rename = "AllowedTeamIdentifiers")]
+    /// Teams whose extensions are all allowed.
+    pub allowed_team_identifiers: Option<Vec<String>>,
+    #[serde(rename = "AllowedSystemExtensions")]
+    pub allowed_system_extensions: Option<TeamMap<String>>,
+    #[serde(rename = "AllowedSystemExtensionTypes")]
+    /// Limits the types of extensions each team may load.
+    /// If a team is absent, all types are allowed.
+    pub allowed_system_extension_types: Option<TeamMap<SystemExtensionType>>,
+    #[serde(rename = "RemovableSystemExtensions")]
+    /// Extensions which may be removed without the user authenticating.
+    pub removable_system_extensions: Option<TeamMap<String>>,
+    #[serde(rename = "NonRemovableSystemExtensions")]
+    /// Extensions which may not be removed by their own app.
+    pub non_removable_system_extensions: Option<TeamMap<String>>,
+    #[serde(rename = "NonRemovableFromUISystemExtensions")]
+    /// Extensions which may not be removed via System Settings.
+    pub non_removable_from_ui_system_extensions: Option<TeamMap<String>>,
+}
+
+impl Default for SystemExtensionPolicyPayload {
+    /// Creates a payload allowing no extensions.
+    fn default() -> Self {
+        SystemExtensionPolicyPayload {
+            base: BasePayload {
+                payload_type: PayloadType::SystemExtensionPolicy,
+                ..Default::default()
+            },
+            allow_user_overrides: None,
+            allowed_team_identifiers: None,
+            allowed_system_extensions: None,
+            allowed_system_extension_types: None,
+            removable_system_extensions: None,
+            non_removable_system_extensions: None,
+            non_removable_from_ui_system_extensions: None,
+        }
+    }
+}
+
+impl SystemExtensionPolicyPayload {
+    /// Allows the given extension to load.
+    pub fn allow_extension(&mut self, team_id: &str, bundle_id: &str) {
+        insert_into(&mut self.allowed_system_extensions, team_id, bundle_id);
+    }
+
+    /// Allows all extensions from the given team to load.
+    pub fn allow_team(&mut self, team_id: &str) {
+        push_unique(&mut self.allowed_team_identifiers, team_id);
+    }
+
+    /// Limits the given team's extensions to the given types.
+    pub fn allow_types(&mut self, team_id: &str, types: &[SystemExtensionType]) {
+        for extension_type in types {
+            insert_into(
+                &mut self.allowed_system_extension_types,
+                team_id,
+                extension_type.clone(),
+            );
+        }
+    }
+
+    /// Permits the given extension to be removed without authentication.
+    pub fn allow_removal(&mut self, team_id: &str, bundle_id: &str) {
+        insert_into(&mut self.removable_system_extensions, team_id, bundle_id);
+    }
+
+    /// Every team identifier this policy refers to.
+    pub fn team_identifiers(&self) -> Vec<&str> {
+        let team_maps = [
+            &self.allowed_system_extensions,
+            &self.removable_system_extensions,
+            &self.non_removable_system_extensions,
+            &self.non_removable_from_ui_system_extensions,
+        ];
+        let mut team_ids: Vec<&str> = self
+            .allowed_team_identifiers
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        team_ids.extend(team_maps.into_iter().flatten().flat_map(team_keys));
+        team_ids.extend(
+            self.allowed_system_extension_types
+                .iter()
+                .flat_map(team_keys),
+        );
+        team_ids
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum SystemExtensionType {
+    #[serde(rename = "DriverExtension")]
+    Driver,
+    #[serde(rename = "NetworkExtension")]
+    Network,
+    #[serde(rename = "EndpointSecurityExtension")]
+    EndpointSecurity,
+}
+
+#[payload]
+/// Allows kernel extensions to load on macOS without user approval.
+/// https://developer.apple.com/documentation/devicemanagement/systempolicykernelextensions
+pub struct KernelExtensionPolicyPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "AllowUserOverrides")]
+    /// Whether users may approve additional extensions themselves.
+    pub allow_user_overrides: Option<bool>,
+    #[serde(rename = "AllowNonAdminUserApprovals")]
+    pub allow_non_admin_user_approvals: Option<bool>,
+    #[serde(rename = "AllowedTeamIdentifiers")]
+    /// Teams whose extensions are all allowed.
+    pub allowed_team_identifiers: Option<Vec<String>>,
+    #[serde(rename = "AllowedKernelExtensions")]
+    /// Unsigned legacy extensions are listed under an empty team identifier.
+    pub allowed_kernel_extensions: Option<TeamMap<String>>,
+}
+
+impl Default for KernelExtensionPolicyPayload {
+    /// Creates a payload allowing no extensions.
+    fn default() -> Self {
+        KernelExtensionPolicyPayload {
+            base: BasePayload {
+                payload_type: PayloadType::KernelExtensionPolicy,
+                ..Default::default()
+            },
+            allow_user_overrides: None,
+            allow_non_admin_user_approvals: None,
+            allowed_team_identifiers: None,
+            allowed_kernel_extensions: None,
+        }
+    }
+}
+
+impl KernelExtensionPolicyPayload {
+    /// Allows the given extension to load.
+    pub fn allow_extension(&mut self, team_id: &str, bundle_id: &str) {
+        insert_into(&mut self.allowed_kernel_extensions, team_id, bundle_id);
+    }
+
+    /// Allows all extensions from the given team to load.
+    pub fn allow_team(&mut self, team_id: &str) {
+        push_unique(&mut self.allowed_team_identifiers, team_id);
+    }
+
+    /// Every team identifier this policy refers to, excluding that of unsigned extensions.
+    pub fn team_identifiers(&self) -> Vec<&str> {
+        let mut team_ids: Vec<&str> = self
+            .allowed_team_identifiers
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        team_ids.extend(self.allowed_kernel_extensions.iter().flat_map(team_keys));
+        team_ids.retain(|team_id| !team_id.is_empty());
+        team_ids
+    }
+}
+
+/// Adds a value beneath the given team, creating the map if necessary.
+fn insert_into<T: PartialEq>(map: &mut Option<TeamMap<T>>, team_id: &str, value: impl Into<T>) {
+    let values = map
+        .get_or_insert_default()
+        .entry(team_id.to_string())
+        .or_default();
+    let value = value.into();
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+/// Adds a team identifier, creating the list if necessary.
+fn push_unique(team_ids: &mut Option<Vec<String>>, team_id: &str) {
+    let team_ids = team_ids.get_or_insert_default();
+    if !team_ids.iter().any(|existing| existing == team_id) {
+        team_ids.push(team_id.to_string());
+    }
+}
+
+fn team_keys<T>(map: &TeamMap<T>) -> impl Iterator<Item = &str> {
+    map.keys().map(String::as_str)
+}
+
+/// Whether this is a well-formed team identifier, such as "ABCDE12345".
+pub fn is_valid_team_identifier(team_id: &str) -> bool {
+    team_id.len() == 10
+        && team_id
+            .chars()
+            .all(|character| character.is_ascii_uppercase() || character.is_ascii_digit())
+}
@@ -0,0 +1,224 @@
+use plist::Value;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::{Payload, Profile, read_profile};
+use crate::database::{Device, User};
+
+/// Variables available to profile templates, alongside their shorthand.
+/// For example, a device's serial number may be referenced as either
+/// `{{device.serial_number}}` or `$SERIALNUMBER`.
+const VARIABLES: [(&str, &str); 9] = [
+    ("device.udid", "UDID"),
+    ("device.serial_number", "SERIALNUMBER"),
+    ("device.name", "DEVICENAME"),
+    ("device.asset_tag", "ASSETTAG"),
+    ("device.imei", "IMEI"),
+    ("device.product", "PRODUCT"),
+    ("user.user_name", "USERNAME"),
+    ("user.full_name", "FULLNAME"),
+    ("user.email", "EMAIL"),
+];
+
+/// The values of template variables for a single device.
+pub struct TemplateVariables {
+    udid: String,
+    /// A variable's value is absent if it is not yet known for this device,
+    /// such as if the device has no assigned user.
+    values: BTreeMap<&'static str, Option<String>>,
+}
+
+impl TemplateVariables {
+    /// Collects variables from the device's inventory and its assigned user, if any.
+    pub fn for_device(device: &Device, user: Option<&User>) -> Self {
+        let values = BTreeMap::from([
+            ("device.udid", Some(device.udid.clone())),
+            ("device.serial_number", Some(device.serial_number.clone())),
+            ("device.name", device.device_name.clone()),
+            ("device.asset_tag", device.asset_tag.clone()),
+            ("device.imei", device.imei.clone()),
+            ("device.product", Some(device.product.clone())),
+            ("user.user_name", user.map(|user| user.user_name.clone())),
+            (
+                "user.full_name",
+                user.and_then(|user| user.full_name.clone()),
+            ),
+            (
+                "user.email",
+                user.and_then(|user| user.email_address.clone()),
+            ),
+        ]);
+        TemplateVariables {
+            udid: device.udid.clone(),
+            values,
+        }
+    }
+
+    /// The value of the given variable.
+    fn value(&self, name: &str) -> Result<&str, String> {
+        match self.values.get(name) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(format!(
+                "{{{{{name}}}}} has no value for device {}",
+                self.udid
+            )),
+            None => Err(format!("{{{{{name}}}}} is not a known variable")),
+        }
+    }
+
+    /// Replaces every placeholder within the given string,
+    /// collecting an error for every placeholder which cannot be replaced.
+    /// Unknown shorthand (such as `$5`) is left as-is, as it is likely not a placeholder.
+    fn expand(&self, contents: &str, errors: &mut Vec<String>) -> String {
+        let mut result = String::new();
+        let mut remaining = contents;
+        while let Some(start) = remaining.find(['{', '$']) {
+            result.push_str(&remaining[..start]);
+            let placeholder = &remaining[start..];
+
+            if let Some(inner) = placeholder.strip_prefix("{{") {
+                let Some(end) = inner.find("}}") else {
+                    errors.push(format!("unterminated placeholder within \"{contents}\""));
+                    return contents.to_string();
+                };
+                match self.value(inner[..end].trim()) {
+                    Ok(value) => result.push_str(value),
+                    Err(e) => errors.push(e),
+                }
+                remaining = &inner[end + 2..];
+            } else if let Some(inner) = placeholder.strip_prefix('$') {
+                let end = inner
+                    .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+                    .unwrap_or(inner.len());
+                match VARIABLES.iter().find(|(_, alias)| *alias == &inner[..end]) {
+                    Some((name, _)) => {
+                        match self.value(name) {
+                            Ok(value) => result.push_str(value),
+                            Err(e) => errors.push(e),
+                        }
+                        remaining = &inner[end..];
+                    }
+                    None => {
+                        result.push('$');
+                        remaining = inner;
+                    }
+                }
+            } else {
+                result.push('{');
+                remaining = &placeholder[1..];
+            }
+        }
+        result.push_str(remaining);
+        result
+    }
+
+    /// Expands placeholders within every string in the given value.
+    fn expand_value(&self, value: &mut Value, errors: &mut Vec<String>) {
+        match value {
+            Value::String(contents) => *contents = self.expand(contents, errors),
+            Value::Dictionary(dictionary) => {
+                for (_, value) in dictionary.iter_mut() {
+                    self.expand_value(value, errors);
+                }
+            }
+            Value::Array(array) => {
+                for value in array {
+                    self.expand_value(value, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<T> Profile<T>
+where
+    T: Serialize,
+{
+    /// Renders this profile as a template for a single device,
+    /// expanding placeholders within all of its strings.
+    ///
+    /// Payload UUIDs are derived from the rendered contents once installed
+    /// (see `version_profile`), so rendering the same profile is stable per device.
+    pub fn render(&self, variables: &TemplateVariables) -> Result<Profile<Payload>, String> {
+        let Value::Dictionary(mut profile) =
+            plist::to_value(self).map_err(|e| format!("unable to serialize profile: {e}"))?
+        else {
+            return Err("profile is not a dictionary".to_string());
+        };
+
+        let mut errors = vec![];
+        for (_, value) in profile.iter_mut() {
+            variables.expand_value(value, &mut errors);
+        }
+        if !errors.is_empty() {
+            errors.sort();
+            errors.dedup();
+            return Err(errors.join("; "));
+        }
+
+        let mut warnings = vec![];
+        read_profile(&profile, &mut warnings).ok_or_else(|| warnings.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> TemplateVariables {
+        TemplateVariables {
+            udid: "00008030-000000000000002E".to_string(),
+            values: BTreeMap::from([
+                ("device.serial_number", Some("C02ABCDEF123".to_string())),
+                ("device.name", None),
+            ]),
+        }
+    }
+
+    #[test]
+    fn expands_placeholders_and_shorthand() {
+        let mut errors = vec![];
+        let expanded = variables().expand(
+            "{{ device.serial_number }}-$SERIALNUMBER costs $5 {not a placeholder}",
+            &mut errors,
+        );
+        assert_eq!(
+            expanded,
+            "C02ABCDEF123-C02ABCDEF123 costs $5 {not a placeholder}"
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn leaves_unterminated_placeholders() {
+        let mut errors = vec![];
+        let expanded = variables().expand("$SERIALNUMBER {{device.name", &mut errors);
+        assert_eq!(expanded, "$SERIALNUMBER {{device.name");
+        assert_eq!(
+            errors,
+            ["unterminated placeholder within \"$SERIALNUMBER {{device.name\""]
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_shorthand() {
+        let mut errors = vec![];
+        let expanded = variables().expand("$FOO and $FOO_BAR", &mut errors);
+        assert_eq!(expanded, "$FOO and $FOO_BAR");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_and_missing_values() {
+        let mut errors = vec![];
+        variables().expand("{{device.foo}} $DEVICENAME", &mut errors);
+        assert_eq!(
+            errors,
+            [
+                "{{device.foo}} is not a known variable",
+                "{{device.name}} has no value for device 00008030-000000000000002E",
+            ]
+        );
+    }
+}
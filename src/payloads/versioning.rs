@@ -0,0 +1,197 @@
+use plist::{Dictionary, Value};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::name_based_uuid;
+
+/// A profile whose UUIDs are derived from its contents.
+pub struct VersionedProfile {
+    /// The identifier of the top-level payload.
+    pub identifier: String,
+    /// The SHA-256 hash of the profile's contents, in hexadecimal form.
+    /// Payload UUIDs are not considered, as they are derived from this hash.
+    pub content_hash: String,
+    pub contents: Value,
+}
+
+/// Replaces the UUIDs of a profile and its payloads with deterministic UUIDs.
+///
+/// Devices treat a profile with a differing PayloadUUID as a different revision,
+/// even if nothing else has changed. Each UUID is instead a name-based UUID under
+/// the given namespace, derived from the payload's identifier and the hash of the
+/// profile's contents. Installing an unchanged profile therefore results in the
+/// same UUIDs, whereas changed profiles replace their prior revision.
+///
+/// References to these UUIDs, such as to certificate payloads, are updated to match.
+pub fn version_profile<T: Serialize>(
+    profile: &T,
+    namespace: &Uuid,
+) -> Result<VersionedProfile, plist::Error> {
+    let mut contents = plist::to_value(profile)?;
+    let profile = contents
+        .as_dictionary_mut()
+        .expect("profiles should serialize as a dictionary");
+
+    // Remove all existing UUIDs, as they may be random.
+    // Any references are replaced with a placeholder naming the payload's position.
+    let mut previous_uuids = vec![];
+    for_each_payload(profile, |payload| {
+        let uuid = payload.insert("PayloadUUID".to_string(), Value::from(""));
+        let uuid = uuid.as_ref().and_then(Value::as_string);
+        previous_uuids.push(uuid.map(str::to_uppercase));
+    });
+    replace_strings(&mut contents, &|string| {
+        let string = Some(string.to_uppercase());
+        let position = previous_uuids.iter().position(|uuid| uuid == &string)?;
+        Some(uuid_placeholder(position))
+    });
+
+    let mut xml_contents = vec![];
+    plist::to_writer_xml(&mut xml_contents, &contents)?;
+    let content_hash = hex::encode(Sha256::digest(&xml_contents));
+
+    // With our hash known, we can derive our UUIDs.
+    let profile = contents
+        .as_dictionary_mut()
+        .expect("profiles should serialize as a dictionary");
+    let identifier = payload_identifier(profile).to_string();
+    let mut uuids = vec![];
+    for_each_payload(profile, |payload| {
+        let position = uuids.len();
+        let name = format!("{}/{position}/{content_hash}", payload_identifier(payload));
+        let uuid = name_based_uuid(namespace, &name);
+        payload.insert("PayloadUUID".to_string(), Value::from(uuid.to_string()));
+        uuids.push(uuid);
+    });
+    replace_strings(&mut contents, &|string| {
+        let position = (0..uuids.len()).find(|position| string == uuid_placeholder(*position))?;
+        Some(uuids[position].to_string())
+    });
+
+    Ok(VersionedProfile {
+        identifier,
+        content_hash,
+        contents,
+    })
+}
+
+/// Calls the given function for the top-level payload,
+/// followed by every payload within its contents.
+fn for_each_payload(profile: &mut Dictionary, mut function: impl FnMut(&mut Dictionary)) {
+    function(profile);
+    // Some profiles, such as those for enrollment, have a single payload as their contents.
+    // These lack UUIDs of their own.
+    if let Some(Value::Array(contents)) = profile.get_mut("PayloadContent") {
+        for payload in contents.iter_mut().filter_map(Value::as_dictionary_mut) {
+            function(payload);
+        }
+    }
+}
+
+/// Stands in for a payload's UUID while hashing the profile's contents.
+fn uuid_placeholder(position: usize) -> String {
+    format!("PayloadUUID:{position}")
+}
+
+/// Replaces every string within the given value for which a replacement is given.
+/// PayloadUUID values themselves are left as-is.
+fn replace_strings(value: &mut Value, replacement: &impl Fn(&str) -> Option<String>) {
+    match value {
+        Value::String(contents) => {
+            if let Some(replaced) = replacement(contents) {
+                *contents = replaced;
+            }
+        }
+        Value::Dictionary(dictionary) => {
+            for (key, value) in dictionary.iter_mut() {
+                if key != "PayloadUUID" {
+                    replace_strings(value, replacement);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                replace_strings(value, replacement);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn payload_identifier(payload: &Dictionary) -> &str {
+    payload
+        .get("PayloadIdentifier")
+        .and_then(Value::as_string)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A profile containing a certificate, and a payload referencing it.
+    fn profile(display_name: &str) -> Value {
+        let certificate_uuid = Uuid::new_v4().to_string();
+        let certificate = Dictionary::from_iter([
+            ("PayloadIdentifier", Value::from("com.example.certificate")),
+            ("PayloadUUID", Value::from(certificate_uuid.as_str())),
+        ]);
+        let wifi = Dictionary::from_iter([
+            ("PayloadIdentifier", Value::from("com.example.wifi")),
+            ("PayloadUUID", Value::from(Uuid::new_v4().to_string())),
+            ("PayloadCertificateUUID", Value::from(certificate_uuid)),
+        ]);
+        Value::Dictionary(Dictionary::from_iter([
+            ("PayloadIdentifier", Value::from("com.example")),
+            ("PayloadUUID", Value::from(Uuid::new_v4().to_string())),
+            ("PayloadDisplayName", Value::from(display_name)),
+            (
+                "PayloadContent",
+                Value::Array(vec![certificate.into(), wifi.into()]),
+            ),
+        ]))
+    }
+
+    fn payload_uuids(versioned: &VersionedProfile) -> Vec<String> {
+        let mut profile = versioned.contents.as_dictionary().unwrap().clone();
+        let mut uuids = vec![];
+        for_each_payload(&mut profile, |payload| {
+            uuids.push(payload["PayloadUUID"].as_string().unwrap().to_string());
+        });
+        uuids
+    }
+
+    #[test]
+    fn unchanged_profiles_have_stable_uuids() {
+        let namespace = Uuid::new_v4();
+        let first = version_profile(&profile("Example"), &namespace).unwrap();
+        let second = version_profile(&profile("Example"), &namespace).unwrap();
+        assert_eq!(first.identifier, "com.example");
+        assert_eq!(first.content_hash, second.content_hash);
+        assert_eq!(first.contents, second.contents);
+    }
+
+    #[test]
+    fn changed_profiles_have_new_uuids() {
+        let namespace = Uuid::new_v4();
+        let first = version_profile(&profile("Example"), &namespace).unwrap();
+        let second = version_profile(&profile("Changed"), &namespace).unwrap();
+        assert_ne!(first.content_hash, second.content_hash);
+        let first_uuids = payload_uuids(&first);
+        for uuid in payload_uuids(&second) {
+            assert!(!first_uuids.contains(&uuid));
+        }
+    }
+
+    #[test]
+    fn references_follow_their_payload() {
+        let versioned = version_profile(&profile("Example"), &Uuid::new_v4()).unwrap();
+        let uuids = payload_uuids(&versioned);
+        let wifi = &versioned.contents.as_dictionary().unwrap()["PayloadContent"]
+            .as_array()
+            .unwrap()[1];
+        let reference = wifi.as_dictionary().unwrap()["PayloadCertificateUUID"].as_string();
+        assert_eq!(reference, Some(uuids[1].as_str()));
+    }
+}
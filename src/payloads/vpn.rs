@@ -0,0 +1,351 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Configures a VPN connection.
+/// https://developer.apple.com/documentation/devicemanagement/vpn
+pub struct VpnPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "UserDefinedName")]
+    /// The name of this connection - user visible.
+    pub user_defined_name: String,
+    #[serde(rename = "VPNType")]
+    pub vpn_type: VpnType,
+    #[serde(rename = "VPNSubType")]
+    /// The bundle identifier of a VPN app, for custom VPN types.
+    pub vpn_sub_type: Option<String>,
+    #[serde(rename = "IKEv2")]
+    /// Required when the VPN type is IKEv2.
+    pub ikev2: Option<Ikev2Settings>,
+    #[serde(rename = "AlwaysOn")]
+    /// Required when the VPN type is AlwaysOn. Only available on supervised iOS devices.
+    pub always_on: Option<AlwaysOnSettings>,
+}
+
+impl Default for VpnPayload {
+    /// Creates an IKEv2 payload with all options unset.
+    fn default() -> Self {
+        VpnPayload {
+            base: BasePayload {
+                payload_type: PayloadType::Vpn,
+                ..Default::default()
+            },
+            // Please ensure you set a name.
+            user_defined_name: "".to_string(),
+            vpn_type: VpnType::Ikev2,
+            vpn_sub_type: None,
+            ikev2: None,
+            always_on: None,
+        }
+    }
+}
+
+#[payload]
+/// Configures a VPN connection used only by specific apps.
+/// Apps are associated with this connection via its VPNUUID.
+/// https://developer.apple.com/documentation/devicemanagement/applayervpn
+pub struct AppLayerVpnPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "VPNUUID")]
+    /// An identifier apps are mapped against, either within an app mapping payload (macOS)
+    /// or within a managed app's attributes (iOS).
+    pub vpn_uuid: String,
+    #[serde(rename = "UserDefinedName")]
+    pub user_defined_name: String,
+    #[serde(rename = "VPNType")]
+    pub vpn_type: VpnType,
+    #[serde(rename = "VPNSubType")]
+    pub vpn_sub_type: Option<String>,
+    #[serde(rename = "IKEv2")]
+    pub ikev2: Option<Ikev2Settings>,
+    #[serde(rename = "OnDemandMatchAppEnabled")]
+    /// Whether launching a mapped app automatically connects this VPN.
+    pub on_demand_match_app_enabled: Option<bool>,
+    #[serde(rename = "SafariDomains")]
+    /// Domains for which Safari should use this VPN.
+    pub safari_domains: Option<Vec<String>>,
+    #[serde(rename = "AssociatedDomains")]
+    pub associated_domains: Option<Vec<String>>,
+    #[serde(rename = "ExcludedDomains")]
+    pub excluded_domains: Option<Vec<String>>,
+}
+
+impl Default for AppLayerVpnPayload {
+    /// Creates an IKEv2 payload with a random VPNUUID and all options unset.
+    fn default() -> Self {
+        AppLayerVpnPayload {
+            base: BasePayload {
+                payload_type: PayloadType::AppLayerVpn,
+                ..Default::default()
+            },
+            vpn_uuid: Uuid::new_v4().to_string(),
+            // Please ensure you set a name.
+            user_defined_name: "".to_string(),
+            vpn_type: VpnType::Ikev2,
+            vpn_sub_type: None,
+            ikev2: None,
+            on_demand_match_app_enabled: None,
+            safari_domains: None,
+            associated_domains: None,
+            excluded_domains: None,
+        }
+    }
+}
+
+#[payload]
+/// Maps apps on macOS to per-app VPN connections.
+/// https://developer.apple.com/documentation/devicemanagement/vpnappmapping
+pub struct VpnAppMappingPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "AppLayerVPNMapping")]
+    pub app_layer_vpn_mapping: Vec<AppLayerVpnMapping>,
+}
+
+#[payload]
+pub struct AppLayerVpnMapping {
+    #[serde(rename = "Identifier")]
+    /// The bundle identifier of the app.
+    pub identifier: String,
+    #[serde(rename = "VPNUUID")]
+    /// The VPNUUID of an app layer VPN payload.
+    pub vpn_uuid: String,
+    #[serde(rename = "DesignatedRequirement")]
+    /// The app's designated code requirement.
+    pub designated_requirement: String,
+    #[serde(rename = "SigningIdentifier")]
+    pub signing_identifier: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum VpnType {
+    #[serde(rename = "IKEv2")]
+    Ikev2,
+    #[serde(rename = "IPSec")]
+    IpSec,
+    #[serde(rename = "L2TP")]
+    L2tp,
+    #[serde(rename = "AlwaysOn")]
+    AlwaysOn,
+    #[serde(rename = "VPN")]
+    /// A VPN provided by an app, identified by VPNSubType.
+    Custom,
+}
+
+#[payload]
+/// Settings for an IKEv2 connection.
+/// https://developer.apple.com/documentation/devicemanagement/vpn/ikev2
+pub struct Ikev2Settings {
+    #[serde(rename = "RemoteAddress")]
+    /// The IP address or hostname of the VPN server.
+    pub remote_address: String,
+    #[serde(rename = "LocalIdentifier")]
+    pub local_identifier: Option<String>,
+    #[serde(rename = "RemoteIdentifier")]
+    pub remote_identifier: Option<String>,
+    #[serde(rename = "AuthenticationMethod")]
+    pub authentication_method: Option<Ikev2AuthenticationMethod>,
+    #[serde(rename = "SharedSecret")]
+    /// Used with shared secret authentication.
+    pub shared_secret: Option<String>,
+    #[serde(rename = "PayloadCertificateUUID")]
+    /// The UUID of an identity certificate payload within the same profile,
+    /// used with certificate authentication.
+    pub payload_certificate_uuid: Option<Uuid>,
+    #[serde(rename = "CertificateType")]
+    pub certificate_type: Option<Ikev2CertificateType>,
+    #[serde(rename = "ServerCertificateIssuerCommonName")]
+    pub server_certificate_issuer_common_name: Option<String>,
+    #[serde(rename = "ServerCertificateCommonName")]
+    pub server_certificate_common_name: Option<String>,
+    #[serde(rename = "ExtendedAuthEnabled")]
+    /// Set to 1 to enable EAP-based authentication.
+    pub extended_auth_enabled: Option<i32>,
+    #[serde(rename = "AuthName")]
+    pub auth_name: Option<String>,
+    #[serde(rename = "AuthPassword")]
+    pub auth_password: Option<String>,
+    #[serde(rename = "DeadPeerDetectionRate")]
+    /// One of "None", "Low", "Medium" or "High".
+    pub dead_peer_detection_rate: Option<String>,
+    #[serde(rename = "DisableMOBIKE")]
+    pub disable_mobike: Option<i32>,
+    #[serde(rename = "EnablePFS")]
+    /// Set to 1 to enable Perfect Forward Secrecy.
+    pub enable_pfs: Option<i32>,
+    #[serde(rename = "EnableCertificateRevocationCheck")]
+    pub enable_certificate_revocation_check: Option<i32>,
+    #[serde(rename = "IKESecurityAssociationParameters")]
+    pub ike_security_association_parameters: Option<SecurityAssociationParameters>,
+    #[serde(rename = "ChildSecurityAssociationParameters")]
+    pub child_security_association_parameters: Option<SecurityAssociationParameters>,
+    #[serde(rename = "IncludeAllNetworks")]
+    /// Whether all traffic should be routed through this VPN.
+    pub include_all_networks: Option<bool>,
+    #[serde(rename = "ExcludeLocalNetworks")]
+    pub exclude_local_networks: Option<bool>,
+    #[serde(rename = "OnDemandEnabled")]
+    /// Set to 1 to connect automatically, per the rules below.
+    pub on_demand_enabled: Option<i32>,
+    #[serde(rename = "OnDemandRules")]
+    /// Evaluated in order - the first matching rule applies.
+    pub on_demand_rules: Option<Vec<OnDemandRule>>,
+}
+
+impl Default for Ikev2Settings {
+    /// Creates settings with all options unset.
+    fn default() -> Self {
+        Ikev2Settings {
+            // Please ensure you set a remote address.
+            remote_address: "".to_string(),
+            local_identifier: None,
+            remote_identifier: None,
+            authentication_method: None,
+            shared_secret: None,
+            payload_certificate_uuid: None,
+            certificate_type: None,
+            server_certificate_issuer_common_name: None,
+            server_certificate_common_name: None,
+            extended_auth_enabled: None,
+            auth_name: None,
+            auth_password: None,
+            dead_peer_detection_rate: None,
+            disable_mobike: None,
+            enable_pfs: None,
+            enable_certificate_revocation_check: None,
+            ike_security_association_parameters: None,
+            child_security_association_parameters: None,
+            include_all_networks: None,
+            exclude_local_networks: None,
+            on_demand_enabled: None,
+            on_demand_rules: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Ikev2AuthenticationMethod {
+    None,
+    SharedSecret,
+    Certificate,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Ikev2CertificateType {
+    #[serde(rename = "RSA")]
+    Rsa,
+    #[serde(rename = "ECDSA256")]
+    Ecdsa256,
+    #[serde(rename = "ECDSA384")]
+    Ecdsa384,
+    #[serde(rename = "ECDSA521")]
+    Ecdsa521,
+    #[serde(rename = "Ed25519")]
+    Ed25519,
+}
+
+#[payload]
+/// Proposals for an IKE or child security association.
+/// https://developer.apple.com/documentation/devicemanagement/vpn/ikev2/ikesecurityassociationparameters
+pub struct SecurityAssociationParameters {
+    #[serde(rename = "EncryptionAlgorithm")]
+    /// For example, "AES-256" or "AES-256-GCM".
+    pub encryption_algorithm: Option<String>,
+    #[serde(rename = "IntegrityAlgorithm")]
+    /// For example, "SHA2-256".
+    pub integrity_algorithm: Option<String>,
+    #[serde(rename = "DiffieHellmanGroup")]
+    /// For example, 14 (2048-bit MODP) or 19 (256-bit ECP).
+    pub diffie_hellman_group: Option<i32>,
+    #[serde(rename = "LifeTimeInMinutes")]
+    pub lifetime_in_minutes: Option<i32>,
+}
+
+#[payload]
+/// A rule determining when to connect automatically.
+/// https://developer.apple.com/documentation/devicemanagement/vpn/ikev2/ondemandrulesitem
+pub struct OnDemandRule {
+    #[serde(rename = "Action")]
+    pub action: OnDemandAction,
+    #[serde(rename = "ActionParameters")]
+    /// Only used with the EvaluateConnection action.
+    pub action_parameters: Option<Vec<OnDemandActionParameters>>,
+    #[serde(rename = "DNSDomainMatch")]
+    pub dns_domain_match: Option<Vec<String>>,
+    #[serde(rename = "DNSServerAddressMatch")]
+    pub dns_server_address_match: Option<Vec<String>>,
+    #[serde(rename = "InterfaceTypeMatch")]
+    /// One of "Ethernet", "WiFi" or "Cellular".
+    pub interface_type_match: Option<String>,
+    #[serde(rename = "SSIDMatch")]
+    pub ssid_match: Option<Vec<String>>,
+    #[serde(rename = "URLStringProbe")]
+    /// A URL which must return HTTP 200 for this rule to match.
+    pub url_string_probe: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum OnDemandAction {
+    Allow,
+    Connect,
+    Disconnect,
+    EvaluateConnection,
+    Ignore,
+}
+
+#[payload]
+pub struct OnDemandActionParameters {
+    #[serde(rename = "Domains")]
+    pub domains: Vec<String>,
+    #[serde(rename = "DomainAction")]
+    /// Either "ConnectIfNeeded" or "NeverConnect".
+    pub domain_action: String,
+    #[serde(rename = "RequiredDNSServers")]
+    pub required_dns_servers: Option<Vec<String>>,
+    #[serde(rename = "RequiredURLStringProbe")]
+    pub required_url_string_probe: Option<String>,
+}
+
+#[payload]
+/// Settings for an always-on VPN.
+/// https://developer.apple.com/documentation/devicemanagement/vpn/alwayson
+pub struct AlwaysOnSettings {
+    #[serde(rename = "TunnelConfigurations")]
+    pub tunnel_configurations: Vec<AlwaysOnTunnelConfiguration>,
+    #[serde(rename = "UIToggleEnabled")]
+    /// Set to 1 to allow the user to disable this VPN.
+    pub ui_toggle_enabled: Option<i32>,
+    #[serde(rename = "AllowCaptiveWebSheet")]
+    /// Whether traffic from captive network logins may bypass the tunnel.
+    pub allow_captive_web_sheet: Option<bool>,
+    #[serde(rename = "ServiceExceptions")]
+    pub service_exceptions: Option<Vec<AlwaysOnServiceException>>,
+}
+
+#[payload]
+/// An IKEv2 tunnel for the given interfaces.
+pub struct AlwaysOnTunnelConfiguration {
+    #[serde(rename = "ProtocolType")]
+    /// Must be "IKEv2".
+    pub protocol_type: String,
+    #[serde(rename = "Interfaces")]
+    /// Either or both of "Cellular" and "WiFi".
+    pub interfaces: Vec<String>,
+    #[serde(flatten)]
+    pub ikev2: Ikev2Settings,
+}
+
+#[payload]
+pub struct AlwaysOnServiceException {
+    #[serde(rename = "ServiceName")]
+    /// For example, "VoiceMail" or "AirPrint".
+    pub service_name: String,
+    #[serde(rename = "Action")]
+    /// Either "Allow" or "Drop".
+    pub action: String,
+}
@@ -0,0 +1,57 @@
+use optional_value::payload;
+use serde_bytes::ByteBuf;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Adds a web clip to the Home Screen.
+/// https://developer.apple.com/documentation/devicemanagement/webclip
+pub struct WebClipPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "Label")]
+    /// The name of this web clip - user visible.
+    pub label: String,
+    #[serde(rename = "Icon")]
+    /// A PNG, GIF or JPEG image, ideally 180 by 180 pixels.
+    pub icon: Option<ByteBuf>,
+    #[serde(rename = "Precomposed")]
+    /// Whether the icon should be displayed without any added visual effects.
+    pub precomposed: Option<bool>,
+    #[serde(rename = "FullScreen")]
+    /// Whether the web clip launches as a standalone web app,
+    /// without Safari's interface. Only available on iOS.
+    pub full_screen: Option<bool>,
+    #[serde(rename = "IgnoreManifestScope")]
+    /// Whether a full screen web clip may navigate outside of its manifest's scope.
+    pub ignore_manifest_scope: Option<bool>,
+    #[serde(rename = "IsRemovable")]
+    /// Whether the user may remove this web clip. Defaults to true.
+    pub is_removable: Option<bool>,
+    #[serde(rename = "TargetApplicationBundleIdentifier")]
+    /// The app which opens the URL, instead of Safari.
+    pub target_application_bundle_identifier: Option<String>,
+}
+
+impl Default for WebClipPayload {
+    /// Creates a web clip with all options unset.
+    fn default() -> Self {
+        WebClipPayload {
+            base: BasePayload {
+                payload_type: PayloadType::WebClip,
+                ..Default::default()
+            },
+            // Please ensure you set a URL and label.
+            url: "".to_string(),
+            label: "".to_string(),
+            icon: None,
+            precomposed: None,
+            full_screen: None,
+            ignore_manifest_scope: None,
+            is_removable: None,
+            target_application_bundle_identifier: None,
+        }
+    }
+}
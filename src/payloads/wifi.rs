@@ -0,0 +1,218 @@
+use optional_value::payload;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{BasePayload, PayloadType};
+
+#[payload]
+/// Configures a Wi-Fi network.
+/// https://developer.apple.com/documentation/devicemanagement/wifi
+pub struct WiFiPayload {
+    #[serde(flatten)]
+    pub base: BasePayload,
+    #[serde(rename = "SSID_STR")]
+    /// The SSID of the network to join.
+    pub ssid: String,
+    #[serde(rename = "HIDDEN_NETWORK")]
+    /// Whether this network does not broadcast its SSID.
+    pub hidden_network: Option<bool>,
+    #[serde(rename = "AutoJoin")]
+    /// Whether the device should join this network automatically. Defaults to true.
+    pub auto_join: Option<bool>,
+    #[serde(rename = "EncryptionType")]
+    pub encryption_type: Option<WiFiEncryptionType>,
+    #[serde(rename = "Password")]
+    /// The password for WEP, WPA or WPA2/WPA3 Personal networks.
+    pub password: Option<String>,
+    #[serde(rename = "EAPClientConfiguration")]
+    /// Required for WPA/WPA2/WPA3 Enterprise networks.
+    pub eap_client_configuration: Option<EapClientConfiguration>,
+    #[serde(rename = "PayloadCertificateUUID")]
+    /// The UUID of a certificate payload (such as PKCS #12 or SCEP) within the same profile,
+    /// used as the identity for EAP-TLS.
+    pub payload_certificate_uuid: Option<Uuid>,
+    #[serde(rename = "ProxyType")]
+    pub proxy_type: Option<ProxyType>,
+    #[serde(rename = "ProxyServer")]
+    /// Required for manual proxy configurations.
+    pub proxy_server: Option<String>,
+    #[serde(rename = "ProxyServerPort")]
+    /// Required for manual proxy configurations.
+    pub proxy_server_port: Option<u16>,
+    #[serde(rename = "ProxyUsername")]
+    pub proxy_username: Option<String>,
+    #[serde(rename = "ProxyPassword")]
+    pub proxy_password: Option<String>,
+    #[serde(rename = "ProxyPACURL")]
+    /// The URL of a PAC file, used for automatic proxy configurations.
+    pub proxy_pac_url: Option<String>,
+    #[serde(rename = "ProxyPACFallbackAllowed")]
+    /// Whether to connect directly if the PAC file is unreachable.
+    pub proxy_pac_fallback_allowed: Option<bool>,
+}
+
+impl Default for WiFiPayload {
+    /// Creates a payload with all options unset.
+    fn default() -> Self {
+        WiFiPayload {
+            base: BasePayload {
+                payload_type: PayloadType::WiFi,
+                ..Default::default()
+            },
+            // Please ensure you set an SSID.
+            ssid: "".to_string(),
+            hidden_network: None,
+            auto_join: None,
+            encryption_type: None,
+            password: None,
+            eap_client_configuration: None,
+            payload_certificate_uuid: None,
+            proxy_type: None,
+            proxy_server: None,
+            proxy_server_port: None,
+            proxy_username: None,
+            proxy_password: None,
+            proxy_pac_url: None,
+            proxy_pac_fallback_allowed: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+/// The security of a Wi-Fi network.
+/// Personal and Enterprise variants are distinguished by EAPClientConfiguration.
+pub enum WiFiEncryptionType {
+    #[serde(rename = "None")]
+    Open,
+    #[serde(rename = "WEP")]
+    Wep,
+    #[serde(rename = "WPA")]
+    /// WPA, WPA2 or WPA3.
+    Wpa,
+    #[serde(rename = "WPA2")]
+    /// WPA2 or WPA3.
+    Wpa2,
+    #[serde(rename = "WPA3")]
+    Wpa3,
+    #[serde(rename = "Any")]
+    Any,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ProxyType {
+    None,
+    Manual,
+    Auto,
+}
+
+#[payload]
+/// Enterprise (802.1X) configuration for a network.
+/// https://developer.apple.com/documentation/devicemanagement/wifi/eapclientconfiguration
+pub struct EapClientConfiguration {
+    #[serde(rename = "AcceptEAPTypes")]
+    pub accept_eap_types: Vec<EapType>,
+    #[serde(rename = "UserName")]
+    pub user_name: Option<String>,
+    #[serde(rename = "UserPassword")]
+    /// If absent, the user is prompted for their password.
+    pub user_password: Option<String>,
+    #[serde(rename = "OuterIdentity")]
+    /// The identity sent outside of the tunnel for TTLS, PEAP and EAP-FAST.
+    pub outer_identity: Option<String>,
+    #[serde(rename = "TTLSInnerAuthentication")]
+    pub ttls_inner_authentication: Option<TtlsInnerAuthentication>,
+    #[serde(rename = "TLSTrustedServerNames")]
+    /// Common names the server's certificate must match, such as "radius.example.com".
+    /// Wildcards (e.g. "*.example.com") are permitted.
+    pub tls_trusted_server_names: Option<Vec<String>>,
+    #[serde(rename = "PayloadCertificateAnchorUUID")]
+    /// The UUIDs of certificate payloads within the same profile
+    /// to trust when evaluating the server's certificate.
+    pub payload_certificate_anchor_uuid: Option<Vec<Uuid>>,
+    #[serde(rename = "TLSAllowTrustExceptions")]
+    /// Whether the user may be prompted to trust an unrecognized server certificate.
+    pub tls_allow_trust_exceptions: Option<bool>,
+    #[serde(rename = "TLSMinimumVersion")]
+    /// For example, "1.2".
+    pub tls_minimum_version: Option<String>,
+    #[serde(rename = "TLSMaximumVersion")]
+    pub tls_maximum_version: Option<String>,
+}
+
+impl Default for EapClientConfiguration {
+    /// Creates a configuration with all options unset.
+    fn default() -> Self {
+        EapClientConfiguration {
+            // Please ensure you set accepted EAP types.
+            accept_eap_types: vec![],
+            user_name: None,
+            user_password: None,
+            outer_identity: None,
+            ttls_inner_authentication: None,
+            tls_trusted_server_names: None,
+            payload_certificate_anchor_uuid: None,
+            tls_allow_trust_exceptions: None,
+            tls_minimum_version: None,
+            tls_maximum_version: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+/// EAP methods, represented by their IANA-assigned numbers.
+pub enum EapType {
+    Tls,
+    Leap,
+    EapSim,
+    Ttls,
+    EapAka,
+    Peap,
+    EapFast,
+}
+
+impl From<EapType> for u8 {
+    fn from(value: EapType) -> Self {
+        match value {
+            EapType::Tls => 13,
+            EapType::Leap => 17,
+            EapType::EapSim => 18,
+            EapType::Ttls => 21,
+            EapType::EapAka => 23,
+            EapType::Peap => 25,
+            EapType::EapFast => 43,
+        }
+    }
+}
+
+impl TryFrom<u8> for EapType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            13 => Ok(EapType::Tls),
+            17 => Ok(EapType::Leap),
+            18 => Ok(EapType::EapSim),
+            21 => Ok(EapType::Ttls),
+            23 => Ok(EapType::EapAka),
+            25 => Ok(EapType::Peap),
+            43 => Ok(EapType::EapFast),
+            _ => Err(format!("unknown EAP type {value}")),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+/// The authentication used within an EAP-TTLS tunnel.
+pub enum TtlsInnerAuthentication {
+    #[serde(rename = "PAP")]
+    Pap,
+    #[serde(rename = "CHAP")]
+    Chap,
+    #[serde(rename = "MSCHAP")]
+    MsChap,
+    #[serde(rename = "MSCHAPv2")]
+    MsChapV2,
+    #[serde(rename = "EAP")]
+    Eap,
+}
@@ -1,11 +1,14 @@
 use axum::Router;
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post, put};
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
 use crate::app_state::AppState;
 
+mod acme;
+mod admin;
 mod enroll;
+mod mdm;
 mod metadata;
 mod scep;
 
@@ -23,6 +26,119 @@ pub fn create_routes(state: AppState) -> Router {
             "/devicemanagement/mdm/dep_anchor_certs",
             get(metadata::get_anchor_certs),
         )
+        .route("/mdm/checkin", put(mdm::handle_checkin))
+        .route("/mdm/server", put(mdm::handle_command_report))
+        .route(
+            "/mdm/assets/{identifier}/{expiry}/{signature}",
+            get(mdm::get_asset),
+        )
+        .nest("/acme", acme::create_routes(state.clone()))
+        .route("/admin/fonts", get(admin::list_fonts))
+        .route("/admin/users", get(admin::list_users))
+        .route("/admin/users/{user_name}", put(admin::update_user))
+        .route("/admin/declarations", get(admin::list_declarations))
+        .route(
+            "/admin/declarations/{identifier}",
+            put(admin::update_declaration).delete(admin::remove_declaration),
+        )
+        .route("/admin/assets/{identifier}", put(admin::update_asset))
+        .route(
+            "/admin/user_identities/{identifier}",
+            put(admin::update_user_identity),
+        )
+        .route(
+            "/admin/software_updates/{identifier}",
+            put(admin::update_software_update),
+        )
+        .route(
+            "/admin/software_updates/{identifier}/devices",
+            get(admin::get_software_update_compliance),
+        )
+        .route(
+            "/admin/software_update_settings/{identifier}",
+            put(admin::update_software_update_settings),
+        )
+        .route(
+            "/admin/status_subscriptions/{identifier}",
+            put(admin::update_status_subscriptions),
+        )
+        .route(
+            "/admin/profiles/{identifier}/removal_password",
+            get(admin::get_removal_password),
+        )
+        .route("/admin/devices", get(admin::list_devices))
+        .route("/admin/devices/{udid}", get(admin::get_device))
+        .route(
+            "/admin/devices/{udid}/asset_tag",
+            put(admin::update_asset_tag),
+        )
+        .route(
+            "/admin/devices/{udid}/identity",
+            delete(admin::release_device_identity),
+        )
+        .route(
+            "/admin/devices/{udid}/activation_lock_bypass_codes",
+            get(admin::get_bypass_codes),
+        )
+        .route(
+            "/admin/devices/{udid}/filevault_recovery_key",
+            post(admin::retrieve_recovery_key),
+        )
+        .route("/admin/devices/{udid}/passwords", get(admin::get_passwords))
+        .route(
+            "/admin/devices/{udid}/admin_account",
+            get(admin::get_admin_account),
+        )
+        .route(
+            "/admin/devices/{udid}/access_log",
+            get(admin::get_access_log),
+        )
+        .route(
+            "/admin/devices/{udid}/passwords/{kind}/rotate",
+            post(admin::rotate_password),
+        )
+        .route(
+            "/admin/devices/{udid}/passwords/{kind}/verify",
+            post(admin::verify_password),
+        )
+        .route(
+            "/admin/devices/{udid}/acme_identity",
+            post(admin::install_acme_identity),
+        )
+        .route("/admin/devices/{udid}/fonts", post(admin::install_fonts))
+        .route(
+            "/admin/devices/{udid}/web_clips",
+            post(admin::install_web_clips),
+        )
+        .route(
+            "/admin/devices/{udid}/extension_policy",
+            post(admin::install_extension_policy),
+        )
+        .route(
+            "/admin/devices/{udid}/custom_settings",
+            post(admin::install_custom_settings),
+        )
+        .route(
+            "/admin/devices/{udid}/profiles",
+            get(admin::list_installed_profiles).post(admin::import_profile),
+        )
+        .route(
+            "/admin/devices/{udid}/declarations",
+            get(admin::list_device_declarations).put(admin::assign_declarations),
+        )
+        .route(
+            "/admin/devices/{udid}/status",
+            get(admin::get_device_status),
+        )
+        .route(
+            "/admin/devices/{udid}/status_history",
+            get(admin::get_status_history),
+        )
+        .route("/admin/devices/{udid}/user", put(admin::assign_user))
+        .route(
+            "/admin/devices/{udid}/accounts",
+            post(admin::install_accounts),
+        )
         .with_state(state)
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
 }
@@ -0,0 +1,130 @@
+use crate::app_state::AppState;
+use crate::database::{AcmeAccount, acme_accounts, acme_orders};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{AcmeProblem, JwsRequest, acme_url};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// Our only relevant field within newAccount requests, per section 7.3.
+/// Contact information and terms of service are of no concern.
+pub struct NewAccountRequest {
+    #[serde(default)]
+    pub only_return_existing: bool,
+}
+
+#[derive(Serialize)]
+/// The account object, per section 7.1.2.
+pub struct AccountObject {
+    pub status: &'static str,
+    pub orders: String,
+}
+
+#[derive(Serialize)]
+/// The orders list object, per section 7.1.2.1.
+pub struct OrdersList {
+    pub orders: Vec<String>,
+}
+
+/// Responds with the given account, alongside its URL.
+fn account_response(state: &AppState, status: StatusCode, account_id: &str) -> Response {
+    let account_url = acme_url(state, &format!("/account/{account_id}"));
+    let account = AccountObject {
+        status: "valid",
+        orders: format!("{account_url}/orders"),
+    };
+    (status, [(header::LOCATION, account_url)], Json(account)).into_response()
+}
+
+/// Creates an account for the given key, or finds the account already using it.
+pub async fn new_account(
+    State(state): State<AppState>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    if request.account_id.is_some() {
+        return Err(AcmeProblem::malformed(
+            "newAccount requests must contain their key",
+        ));
+    }
+    let payload: NewAccountRequest = request.parse_payload()?;
+
+    let connection = &mut state.database.connection();
+    let key_thumbprint = request.jwk.thumbprint();
+    let existing_account = acme_accounts::table
+        .filter(acme_accounts::key_thumbprint.eq(&key_thumbprint))
+        .first::<AcmeAccount>(connection)
+        .optional()
+        .expect("can query ACME accounts");
+    if let Some(account) = existing_account {
+        return Ok(account_response(
+            &state,
+            StatusCode::OK,
+            &account.account_id,
+        ));
+    }
+    if payload.only_return_existing {
+        return Err(AcmeProblem::new(
+            StatusCode::BAD_REQUEST,
+            "accountDoesNotExist",
+            "no account exists with this key",
+        ));
+    }
+
+    let account = AcmeAccount {
+        account_id: Uuid::new_v4().to_string(),
+        key_thumbprint,
+        jwk: serde_json::to_string(&request.jwk).expect("should be able to serialize key"),
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(acme_accounts::table)
+        .values(&account)
+        .execute(connection)
+        .expect("error persisting ACME account");
+    Ok(account_response(
+        &state,
+        StatusCode::CREATED,
+        &account.account_id,
+    ))
+}
+
+pub async fn get_account(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    if request.account_id()? != account_id {
+        return Err(AcmeProblem::unauthorized("account does not match"));
+    }
+    Ok(account_response(&state, StatusCode::OK, &account_id))
+}
+
+pub async fn list_orders(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    if request.account_id()? != account_id {
+        return Err(AcmeProblem::unauthorized("account does not match"));
+    }
+
+    let connection = &mut state.database.connection();
+    let order_ids = acme_orders::table
+        .filter(acme_orders::account_id.eq(&account_id))
+        .select(acme_orders::order_id)
+        .load::<String>(connection)
+        .expect("can query ACME orders");
+    let orders = order_ids
+        .iter()
+        .map(|order_id| acme_url(&state, &format!("/order/{order_id}")))
+        .collect();
+    Ok(Json(OrdersList { orders }).into_response())
+}
@@ -0,0 +1,253 @@
+use crate::app_state::AppState;
+use crate::database::{AcmeAccount, acme_accounts, acme_nonces};
+use aws_lc_rs::signature::{ECDSA_P256_SHA256_FIXED, ECDSA_P384_SHA384_FIXED, UnparsedPublicKey};
+use axum::{
+    body::Bytes,
+    extract::{FromRef, FromRequest, OriginalUri, Request},
+    http::StatusCode,
+};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use diesel::prelude::*;
+use rsa::{
+    BigUint, RsaPublicKey,
+    pkcs1v15::{Signature, VerifyingKey},
+    signature::Verifier,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+use super::{AcmeProblem, acme_url};
+
+#[derive(Deserialize)]
+/// A JWS in its flattened JSON serialization, per section 6.2 of RFC 8555.
+struct FlattenedJws {
+    protected: String,
+    payload: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct ProtectedHeader {
+    alg: String,
+    nonce: String,
+    url: String,
+    /// Only present when creating accounts.
+    jwk: Option<Jwk>,
+    /// The URL of the account this request was made on behalf of.
+    kid: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kty")]
+/// An account's public key, per RFC 7517.
+pub enum Jwk {
+    #[serde(rename = "EC")]
+    Ec { crv: String, x: String, y: String },
+    #[serde(rename = "RSA")]
+    Rsa { n: String, e: String },
+}
+
+impl Jwk {
+    /// The thumbprint of this key, per RFC 7638.
+    /// Its members must be ordered lexicographically, without whitespace.
+    pub fn thumbprint(&self) -> String {
+        let canonical_form = match self {
+            Jwk::Ec { crv, x, y } => {
+                format!(r#"{{"crv":"{crv}","kty":"EC","x":"{x}","y":"{y}"}}"#)
+            }
+            Jwk::Rsa { n, e } => format!(r#"{{"e":"{e}","kty":"RSA","n":"{n}"}}"#),
+        };
+        BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(canonical_form))
+    }
+
+    /// Verifies the given signature over the message with this key.
+    /// Returns None if the algorithm is unsupported for this key.
+    fn verify(&self, algorithm: &str, message: &[u8], signature: &[u8]) -> Option<bool> {
+        match self {
+            Jwk::Ec { crv, x, y } => {
+                let verification_algorithm = match (algorithm, crv.as_str()) {
+                    ("ES256", "P-256") => &ECDSA_P256_SHA256_FIXED,
+                    ("ES384", "P-384") => &ECDSA_P384_SHA384_FIXED,
+                    _ => return None,
+                };
+                // Keys are given in their uncompressed form.
+                let mut point = vec![0x04];
+                point.extend(BASE64_URL_SAFE_NO_PAD.decode(x).ok()?);
+                point.extend(BASE64_URL_SAFE_NO_PAD.decode(y).ok()?);
+                let public_key = UnparsedPublicKey::new(verification_algorithm, point);
+                Some(public_key.verify(message, signature).is_ok())
+            }
+            Jwk::Rsa { n, e } => {
+                if algorithm != "RS256" {
+                    return None;
+                }
+                let public_key = RsaPublicKey::new(
+                    BigUint::from_bytes_be(&BASE64_URL_SAFE_NO_PAD.decode(n).ok()?),
+                    BigUint::from_bytes_be(&BASE64_URL_SAFE_NO_PAD.decode(e).ok()?),
+                )
+                .ok()?;
+                let Ok(signature) = Signature::try_from(signature) else {
+                    return Some(false);
+                };
+                let verifying_key = VerifyingKey::<Sha256>::new(public_key);
+                Some(verifying_key.verify(message, &signature).is_ok())
+            }
+        }
+    }
+}
+
+/// An authenticated ACME request, per section 6.2 of RFC 8555.
+///
+/// Its nonce is consumed, and its signature verified against either
+/// the embedded key or the key of the account it was made on behalf of.
+pub struct JwsRequest {
+    /// The request's payload. This is empty for POST-as-GET requests.
+    pub payload: Vec<u8>,
+    /// The key this request was signed with.
+    pub jwk: Jwk,
+    /// The account this request was made on behalf of, if identified by its URL.
+    pub account_id: Option<String>,
+}
+
+impl JwsRequest {
+    /// Whether this is a POST-as-GET request, per section 6.3.
+    pub fn is_post_as_get(&self) -> bool {
+        self.payload.is_empty()
+    }
+
+    /// Parses the request's payload as JSON.
+    pub fn parse_payload<T: DeserializeOwned>(&self) -> Result<T, AcmeProblem> {
+        serde_json::from_slice(&self.payload)
+            .map_err(|_| AcmeProblem::malformed("unable to parse request payload"))
+    }
+
+    /// The account this request was made on behalf of.
+    /// Requests other than account creation must identify their account.
+    pub fn account_id(&self) -> Result<&str, AcmeProblem> {
+        self.account_id
+            .as_deref()
+            .ok_or(AcmeProblem::malformed("request must identify its account"))
+    }
+}
+
+impl<S> FromRequest<S> for JwsRequest
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = AcmeProblem;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+        // As our routes are nested, we need the path prior to it being stripped.
+        let requested_path = match request.extensions().get::<OriginalUri>() {
+            Some(OriginalUri(uri)) => uri.path(),
+            None => request.uri().path(),
+        };
+        let requested_url = format!(
+            "https://{}{requested_path}",
+            state.config.service.base_domain
+        );
+
+        let body = Bytes::from_request(request, &state)
+            .await
+            .map_err(|_| AcmeProblem::malformed("unable to read request"))?;
+        let jws: FlattenedJws = serde_json::from_slice(&body)
+            .map_err(|_| AcmeProblem::malformed("request must be a flattened JWS"))?;
+        let decode = |value: &str| {
+            BASE64_URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|_| AcmeProblem::malformed("request contains invalid base64url"))
+        };
+        let header: ProtectedHeader = serde_json::from_slice(&decode(&jws.protected)?)
+            .map_err(|_| AcmeProblem::malformed("unable to parse protected header"))?;
+
+        // Every nonce may only be used once.
+        let connection = &mut state.database.connection();
+        let consumed_nonces = diesel::delete(acme_nonces::table.find(&header.nonce))
+            .execute(connection)
+            .expect("error consuming nonce");
+        if consumed_nonces != 1 {
+            return Err(AcmeProblem::new(
+                StatusCode::BAD_REQUEST,
+                "badNonce",
+                "nonce is invalid or has already been used",
+            ));
+        }
+
+        if header.url != requested_url {
+            return Err(AcmeProblem::unauthorized("request URL does not match"));
+        }
+
+        let (jwk, account_id) = match (header.jwk, header.kid) {
+            (Some(jwk), None) => (jwk, None),
+            (None, Some(kid)) => {
+                let Some(account_id) = kid.strip_prefix(&acme_url(&state, "/account/")) else {
+                    return Err(AcmeProblem::unauthorized("unknown account"));
+                };
+                let account = acme_accounts::table
+                    .find(account_id)
+                    .first::<AcmeAccount>(connection)
+                    .optional()
+                    .expect("can query ACME accounts")
+                    .ok_or(AcmeProblem::new(
+                        StatusCode::BAD_REQUEST,
+                        "accountDoesNotExist",
+                        "unknown account",
+                    ))?;
+                let jwk = serde_json::from_str(&account.jwk)
+                    .expect("should be able to parse persisted account key");
+                (jwk, Some(account.account_id))
+            }
+            _ => {
+                return Err(AcmeProblem::malformed(
+                    "request must contain exactly one of jwk or kid",
+                ));
+            }
+        };
+
+        let signing_input = format!("{}.{}", jws.protected, jws.payload);
+        let signature = decode(&jws.signature)?;
+        match jwk.verify(&header.alg, signing_input.as_bytes(), &signature) {
+            Some(true) => {}
+            Some(false) => return Err(AcmeProblem::unauthorized("signature is invalid")),
+            None => {
+                return Err(AcmeProblem::new(
+                    StatusCode::BAD_REQUEST,
+                    "badSignatureAlgorithm",
+                    "only ES256, ES384 and RS256 are supported",
+                ));
+            }
+        }
+
+        Ok(JwsRequest {
+            payload: decode(&jws.payload)?,
+            jwk,
+            account_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbprint_matches_rfc_7638() {
+        // The example key from section 3.1 of RFC 7638.
+        let jwk: Jwk = serde_json::from_str(
+            r#"{
+                "kty": "RSA",
+                "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+                "e": "AQAB",
+                "alg": "RS256",
+                "kid": "2011-04-29"
+            }"#,
+        )
+        .expect("example key should parse");
+        assert_eq!(
+            jwk.thumbprint(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::app_state::AppState;
+use crate::database::{AcmeClientIdentifier, AcmeNonce, acme_client_identifiers, acme_nonces};
+use crate::payloads::{
+    AcmeCertificatePayload, AcmeKeyType, BasePayload, Payload, PayloadScope, PayloadType, Profile,
+};
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use diesel::prelude::*;
+use rand::distr::{Alphanumeric, SampleString};
+use serde::Serialize;
+use time::{Duration, OffsetDateTime};
+
+mod accounts;
+mod jws;
+mod orders;
+
+pub use jws::JwsRequest;
+
+/// The length of nonces, client identifiers and challenge tokens we generate.
+const TOKEN_LENGTH: usize = 32;
+
+/// How long nonces remain valid for.
+const NONCE_LIFETIME: Duration = Duration::hours(1);
+
+/// A budget ACME server, issuing device identities from our ACME CA.
+/// This implements RFC 8555: https://datatracker.ietf.org/doc/html/rfc8555
+///
+/// Only the device-attest-01 challenge is supported, per
+/// https://datatracker.ietf.org/doc/html/draft-ietf-acme-device-attest
+/// Orders must identify the device via a client identifier we've issued it.
+pub fn create_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/directory", get(get_directory))
+        .route("/new-nonce", get(new_nonce))
+        .route("/new-account", post(accounts::new_account))
+        .route("/account/{account_id}", post(accounts::get_account))
+        .route("/account/{account_id}/orders", post(accounts::list_orders))
+        .route("/new-order", post(orders::new_order))
+        .route("/order/{order_id}", post(orders::get_order))
+        .route("/order/{order_id}/finalize", post(orders::finalize_order))
+        .route("/authz/{order_id}", post(orders::get_authorization))
+        .route("/challenge/{order_id}", post(orders::respond_to_challenge))
+        .route("/cert/{order_id}", post(orders::get_certificate))
+        .layer(middleware::from_fn_with_state(state, acme_middleware))
+}
+
+/// Disables our ACME server if it is unconfigured, and
+/// otherwise provides a fresh nonce with every response.
+async fn acme_middleware(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if state.certificates.apple_attestation_root.is_none() {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let mut response = next.run(request).await;
+    let nonce = issue_nonce(&mut state.database.connection());
+    let headers = response.headers_mut();
+    headers.insert(
+        "Replay-Nonce",
+        HeaderValue::from_str(&nonce).expect("nonces should be valid header values"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// The absolute URL of the given ACME resource.
+pub fn acme_url(state: &AppState, path: &str) -> String {
+    format!("https://{}/acme{path}", state.config.service.base_domain)
+}
+
+/// Generates a new random token, suitable for nonces and challenges.
+fn generate_token() -> String {
+    Alphanumeric.sample_string(&mut rand::rng(), TOKEN_LENGTH)
+}
+
+/// Persists a new nonce, pruning those which have expired.
+fn issue_nonce(connection: &mut SqliteConnection) -> String {
+    let now = OffsetDateTime::now_utc();
+    diesel::delete(acme_nonces::table.filter(acme_nonces::creation_date.lt(now - NONCE_LIFETIME)))
+        .execute(connection)
+        .expect("error pruning nonces");
+
+    let nonce = AcmeNonce {
+        nonce: generate_token(),
+        creation_date: now,
+    };
+    diesel::insert_into(acme_nonces::table)
+        .values(&nonce)
+        .execute(connection)
+        .expect("error persisting nonce");
+    nonce.nonce
+}
+
+#[derive(Serialize)]
+/// An ACME error, given as a problem document per section 6.7.
+pub struct AcmeProblem {
+    #[serde(skip)]
+    status: StatusCode,
+    #[serde(rename = "type")]
+    kind: String,
+    detail: String,
+}
+
+impl AcmeProblem {
+    pub fn new(status: StatusCode, kind: &str, detail: &str) -> Self {
+        AcmeProblem {
+            status,
+            kind: format!("urn:ietf:params:acme:error:{kind}"),
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn malformed(detail: &str) -> Self {
+        AcmeProblem::new(StatusCode::BAD_REQUEST, "malformed", detail)
+    }
+
+    pub fn unauthorized(detail: &str) -> Self {
+        AcmeProblem::new(StatusCode::UNAUTHORIZED, "unauthorized", detail)
+    }
+}
+
+impl IntoResponse for AcmeProblem {
+    fn into_response(self) -> Response {
+        let headers = [(header::CONTENT_TYPE, "application/problem+json")];
+        (self.status, headers, Json(&self)).into_response()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The directory object, per section 7.1.1.
+pub struct Directory {
+    pub new_nonce: String,
+    pub new_account: String,
+    pub new_order: String,
+}
+
+pub async fn get_directory(State(state): State<AppState>) -> Response {
+    Json(Directory {
+        new_nonce: acme_url(&state, "/new-nonce"),
+        new_account: acme_url(&state, "/new-account"),
+        new_order: acme_url(&state, "/new-order"),
+    })
+    .into_response()
+}
+
+/// Our middleware provides the nonce itself, per section 7.2.
+pub async fn new_nonce(method: Method) -> Response {
+    if method == Method::HEAD {
+        (StatusCode::OK).into_response()
+    } else {
+        (StatusCode::NO_CONTENT).into_response()
+    }
+}
+
+/// Creates a profile for the given device to request an identity certificate
+/// via our ACME server. Its key is bound to the Secure Enclave,
+/// and the device's serial number and UDID are attested to by Apple.
+///
+/// Each profile contains a new one-time client identifier tied to the device.
+pub fn identity_profile(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+) -> Profile<Payload> {
+    let service_config = &state.config.service;
+    let client_identifier = AcmeClientIdentifier {
+        client_identifier: generate_token(),
+        udid: device_udid.to_string(),
+        creation_date: OffsetDateTime::now_utc(),
+        use_date: None,
+    };
+    diesel::insert_into(acme_client_identifiers::table)
+        .values(&client_identifier)
+        .execute(connection)
+        .expect("error persisting client identifier");
+
+    Profile {
+        base: BasePayload {
+            identifier: format!("{}.acme-identity", service_config.base_identifier),
+            display_name: Some("Device Identity".to_string()),
+            organization: Some(service_config.organization_name.clone()),
+            ..Default::default()
+        },
+        scope: Some(PayloadScope::System),
+        contents: vec![Payload::AcmeCertificate(AcmeCertificatePayload {
+            base: BasePayload {
+                identifier: format!("{}.acme-identity.acme", service_config.base_identifier),
+                payload_type: PayloadType::Acme,
+                ..Default::default()
+            },
+            directory_url: acme_url(state, "/directory"),
+            client_identifier: client_identifier.client_identifier,
+            // Hardware bound keys must be P-256 or P-384.
+            key_size: 384,
+            key_type: AcmeKeyType::EcSecPrimeRandom,
+            hardware_bound: true,
+            subject: vec![
+                vec![vec![
+                    "O".to_string(),
+                    service_config.organization_name.clone(),
+                ]],
+                vec![vec!["CN".to_string(), device_udid.to_string()]],
+            ],
+            subject_alt_name: None,
+            // Signing only.
+            usage_flags: Some(1),
+            // Client authentication.
+            extended_key_usage: Some(vec!["1.3.6.1.5.5.7.3.2".to_string()]),
+            attest: Some(true),
+            allow_all_apps_access: None,
+            key_is_extractable: None,
+        })],
+        ..Default::default()
+    }
+}
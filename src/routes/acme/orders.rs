@@ -0,0 +1,454 @@
+use crate::app_state::AppState;
+use crate::certificates::verify_device_attestation;
+use crate::database::{
+    AcmeClientIdentifier, AcmeOrder, Device, acme_client_identifiers, acme_orders, devices,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use der::{
+    Encode,
+    pem::{self, LineEnding},
+};
+use diesel::prelude::*;
+use rcgen::{CertificateSigningRequestParams, PublicKeyData};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use super::{AcmeProblem, JwsRequest, acme_url, generate_token};
+
+/// The lifecycle of an order, per section 7.1.6.
+/// As we only support device-attest-01, orders never become "processing".
+const STATUS_PENDING: &str = "pending";
+const STATUS_READY: &str = "ready";
+const STATUS_VALID: &str = "valid";
+const STATUS_INVALID: &str = "invalid";
+
+/// The identifier type Apple devices present their client identifier as.
+const PERMANENT_IDENTIFIER: &str = "permanent-identifier";
+
+/// How long an order may take to complete.
+const ORDER_LIFETIME: Duration = Duration::days(1);
+
+#[derive(Clone, Serialize, Deserialize)]
+/// An identifier, per section 9.7.7.
+pub struct Identifier {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewOrderRequest {
+    pub identifiers: Vec<Identifier>,
+}
+
+#[derive(Deserialize)]
+pub struct ChallengeRequest {
+    #[serde(rename = "attObj")]
+    /// The attestation object, encoded as base64url.
+    pub attestation_object: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct FinalizeRequest {
+    /// The DER-encoded CSR, encoded as base64url.
+    pub csr: String,
+}
+
+#[derive(Serialize)]
+/// The order object, per section 7.1.3.
+pub struct OrderObject {
+    pub status: &'static str,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires: OffsetDateTime,
+    pub identifiers: Vec<Identifier>,
+    pub authorizations: Vec<String>,
+    pub finalize: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<String>,
+}
+
+#[derive(Serialize)]
+/// The authorization object, per section 7.1.4.
+pub struct AuthorizationObject {
+    pub status: &'static str,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires: OffsetDateTime,
+    pub identifier: Identifier,
+    pub challenges: Vec<ChallengeObject>,
+}
+
+#[derive(Serialize)]
+/// The challenge object, per section 7.1.5.
+pub struct ChallengeObject {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub url: String,
+    pub status: &'static str,
+    pub token: String,
+}
+
+/// The status of an order, accounting for its expiration.
+fn order_status(order: &AcmeOrder) -> &'static str {
+    let has_expired = order.expiration_date < OffsetDateTime::now_utc();
+    match order.status.as_str() {
+        STATUS_PENDING if has_expired => STATUS_INVALID,
+        STATUS_READY if has_expired => STATUS_INVALID,
+        STATUS_PENDING => STATUS_PENDING,
+        STATUS_READY => STATUS_READY,
+        STATUS_VALID => STATUS_VALID,
+        _ => STATUS_INVALID,
+    }
+}
+
+/// The status of an order's sole authorization and challenge.
+/// They're valid once the device's attestation has been accepted.
+fn authorization_status(order: &AcmeOrder) -> &'static str {
+    match order_status(order) {
+        STATUS_READY | STATUS_VALID => STATUS_VALID,
+        status => status,
+    }
+}
+
+fn order_url(state: &AppState, order: &AcmeOrder) -> String {
+    acme_url(state, &format!("/order/{}", order.order_id))
+}
+
+fn order_identifier(order: &AcmeOrder) -> Identifier {
+    Identifier {
+        kind: PERMANENT_IDENTIFIER.to_string(),
+        value: order.client_identifier.clone(),
+    }
+}
+
+/// Responds with the given order, alongside its URL.
+fn order_response(state: &AppState, status: StatusCode, order: &AcmeOrder) -> Response {
+    let order_object = OrderObject {
+        status: order_status(order),
+        expires: order.expiration_date,
+        identifiers: vec![order_identifier(order)],
+        authorizations: vec![acme_url(state, &format!("/authz/{}", order.order_id))],
+        finalize: format!("{}/finalize", order_url(state, order)),
+        certificate: order
+            .certificate
+            .as_ref()
+            .map(|_| acme_url(state, &format!("/cert/{}", order.order_id))),
+    };
+    (
+        status,
+        [(header::LOCATION, order_url(state, order))],
+        Json(order_object),
+    )
+        .into_response()
+}
+
+fn challenge_object(state: &AppState, order: &AcmeOrder) -> ChallengeObject {
+    ChallengeObject {
+        kind: "device-attest-01",
+        url: acme_url(state, &format!("/challenge/{}", order.order_id)),
+        status: authorization_status(order),
+        token: order.challenge_token.clone(),
+    }
+}
+
+/// Loads an order, ensuring it belongs to the requesting account.
+fn load_order(
+    connection: &mut SqliteConnection,
+    order_id: &str,
+    request: &JwsRequest,
+) -> Result<AcmeOrder, AcmeProblem> {
+    let account_id = request.account_id()?;
+    acme_orders::table
+        .find(order_id)
+        .first::<AcmeOrder>(connection)
+        .optional()
+        .expect("can query ACME orders")
+        .filter(|order| order.account_id == account_id)
+        .ok_or(AcmeProblem::new(
+            StatusCode::NOT_FOUND,
+            "malformed",
+            "unknown order",
+        ))
+}
+
+/// Places an order for the device identified by a client identifier we've issued.
+pub async fn new_order(
+    State(state): State<AppState>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let account_id = request.account_id()?.to_string();
+    let payload: NewOrderRequest = request.parse_payload()?;
+    let [identifier] = payload.identifiers.as_slice() else {
+        return Err(AcmeProblem::new(
+            StatusCode::BAD_REQUEST,
+            "rejectedIdentifier",
+            "orders must contain a single identifier",
+        ));
+    };
+    if identifier.kind != PERMANENT_IDENTIFIER {
+        return Err(AcmeProblem::new(
+            StatusCode::BAD_REQUEST,
+            "unsupportedIdentifier",
+            "only permanent-identifier is supported",
+        ));
+    }
+
+    // Client identifiers may only be used once.
+    let connection = &mut state.database.connection();
+    let now = OffsetDateTime::now_utc();
+    let consumed_identifiers = diesel::update(
+        acme_client_identifiers::table
+            .find(&identifier.value)
+            .filter(acme_client_identifiers::use_date.is_null()),
+    )
+    .set(acme_client_identifiers::use_date.eq(now))
+    .execute(connection)
+    .expect("error updating client identifier");
+    if consumed_identifiers != 1 {
+        return Err(AcmeProblem::new(
+            StatusCode::FORBIDDEN,
+            "rejectedIdentifier",
+            "client identifier is unknown or has already been used",
+        ));
+    }
+    let client_identifier = acme_client_identifiers::table
+        .find(&identifier.value)
+        .first::<AcmeClientIdentifier>(connection)
+        .expect("can query client identifiers");
+
+    let order = AcmeOrder {
+        order_id: Uuid::new_v4().to_string(),
+        account_id,
+        client_identifier: client_identifier.client_identifier,
+        udid: client_identifier.udid,
+        status: STATUS_PENDING.to_string(),
+        challenge_token: generate_token(),
+        attested_public_key: None,
+        certificate: None,
+        creation_date: now,
+        expiration_date: now + ORDER_LIFETIME,
+    };
+    diesel::insert_into(acme_orders::table)
+        .values(&order)
+        .execute(connection)
+        .expect("error persisting ACME order");
+    Ok(order_response(&state, StatusCode::CREATED, &order))
+}
+
+pub async fn get_order(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let connection = &mut state.database.connection();
+    let order = load_order(connection, &order_id, &request)?;
+    Ok(order_response(&state, StatusCode::OK, &order))
+}
+
+pub async fn get_authorization(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let connection = &mut state.database.connection();
+    let order = load_order(connection, &order_id, &request)?;
+    Ok(Json(AuthorizationObject {
+        status: authorization_status(&order),
+        expires: order.expiration_date,
+        identifier: order_identifier(&order),
+        challenges: vec![challenge_object(&state, &order)],
+    })
+    .into_response())
+}
+
+/// Validates the attestation given in response to a device-attest-01 challenge.
+/// POST-as-GET requests only provide the challenge's current state.
+pub async fn respond_to_challenge(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let connection = &mut state.database.connection();
+    let mut order = load_order(connection, &order_id, &request)?;
+
+    if !request.is_post_as_get() && order_status(&order) == STATUS_PENDING {
+        let payload: ChallengeRequest = request.parse_payload()?;
+        let attestation_object = payload
+            .attestation_object
+            .and_then(|contents| BASE64_URL_SAFE_NO_PAD.decode(contents).ok())
+            .ok_or(AcmeProblem::malformed(
+                "device-attest-01 responses must contain an attestation object",
+            ))?;
+
+        // The device may have since been removed, such as after checking out.
+        let device = devices::table
+            .find(&order.udid)
+            .first::<Device>(connection)
+            .optional()
+            .expect("can query devices")
+            .ok_or(AcmeProblem::unauthorized(
+                "client identifier was issued to an unknown device",
+            ))?;
+        match validate_attestation(&state, &order, &device, &attestation_object) {
+            Ok(public_key) => {
+                diesel::update(acme_orders::table.find(&order.order_id))
+                    .set((
+                        acme_orders::status.eq(STATUS_READY),
+                        acme_orders::attested_public_key.eq(&public_key),
+                    ))
+                    .execute(connection)
+                    .expect("error updating ACME order");
+                order.status = STATUS_READY.to_string();
+                order.attested_public_key = Some(public_key);
+            }
+            Err(detail) => {
+                println!("rejected device attestation for {}: {detail}", order.udid);
+                diesel::update(acme_orders::table.find(&order.order_id))
+                    .set(acme_orders::status.eq(STATUS_INVALID))
+                    .execute(connection)
+                    .expect("error updating ACME order");
+                return Err(AcmeProblem::new(
+                    StatusCode::FORBIDDEN,
+                    "badAttestationStatement",
+                    &detail,
+                ));
+            }
+        }
+    }
+
+    let authorization_link = format!(
+        "<{}>;rel=\"up\"",
+        acme_url(&state, &format!("/authz/{}", order.order_id))
+    );
+    Ok((
+        [(header::LINK, authorization_link)],
+        Json(challenge_object(&state, &order)),
+    )
+        .into_response())
+}
+
+/// Verifies the attestation is for the device our order's client identifier was issued to.
+/// Returns the attested public key, which our issued certificate must certify.
+fn validate_attestation(
+    state: &AppState,
+    order: &AcmeOrder,
+    device: &Device,
+    attestation_object: &[u8],
+) -> Result<Vec<u8>, String> {
+    let attestation_root = state
+        .certificates
+        .apple_attestation_root
+        .as_ref()
+        .ok_or("device attestation is not configured")?
+        .to_der()
+        .expect("should be able to encode attestation root");
+    let attestation = verify_device_attestation(
+        &attestation_root,
+        attestation_object,
+        &order.challenge_token,
+    )?;
+
+    // Not all platforms attest to both identifiers, so either suffices.
+    let udid_matches = attestation.udid.as_deref() == Some(device.udid.as_str());
+    let serial_matches =
+        attestation.serial_number.as_deref() == Some(device.serial_number.as_str());
+    if !udid_matches && !serial_matches {
+        return Err("attested device does not match client identifier".to_string());
+    }
+
+    Ok(attestation.public_key)
+}
+
+/// Issues a certificate for the attested key from our ACME CA.
+pub async fn finalize_order(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let connection = &mut state.database.connection();
+    let mut order = load_order(connection, &order_id, &request)?;
+    if order_status(&order) != STATUS_READY {
+        return Err(AcmeProblem::new(
+            StatusCode::FORBIDDEN,
+            "orderNotReady",
+            "order is not ready to be finalized",
+        ));
+    }
+
+    let bad_csr = |detail| AcmeProblem::new(StatusCode::BAD_REQUEST, "badCSR", detail);
+    let payload: FinalizeRequest = request.parse_payload()?;
+    let csr = BASE64_URL_SAFE_NO_PAD
+        .decode(payload.csr)
+        .map_err(|_| bad_csr("CSR must be encoded as base64url"))?;
+    // This additionally verifies the CSR's signature.
+    let csr = CertificateSigningRequestParams::from_der(&csr.into())
+        .map_err(|_| bad_csr("unable to parse CSR"))?;
+    if order.attested_public_key.as_deref() != Some(csr.public_key.der_bytes()) {
+        return Err(bad_csr("CSR key does not match attested key"));
+    }
+
+    // Our certificate only identifies the device its attestation matched.
+    let validity_days = state.config.acme.certificate_validity_days.unwrap_or(365);
+    let certificate = state
+        .certificates
+        .issue_acme_certificate(csr, &order.udid, validity_days)
+        .map_err(|err| {
+            println!("error issuing certificate for {}: {err}", order.udid);
+            AcmeProblem::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "serverInternal",
+                "unable to issue certificate",
+            )
+        })?;
+
+    let certificate = certificate.der().to_vec();
+    diesel::update(acme_orders::table.find(&order.order_id))
+        .set((
+            acme_orders::status.eq(STATUS_VALID),
+            acme_orders::certificate.eq(&certificate),
+        ))
+        .execute(connection)
+        .expect("error updating ACME order");
+    order.status = STATUS_VALID.to_string();
+    order.certificate = Some(certificate);
+    Ok(order_response(&state, StatusCode::OK, &order))
+}
+
+/// Provides the issued certificate alongside our ACME CA, per section 7.4.2.
+pub async fn get_certificate(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    request: JwsRequest,
+) -> Result<Response, AcmeProblem> {
+    let connection = &mut state.database.connection();
+    let order = load_order(connection, &order_id, &request)?;
+    let Some(certificate) = order.certificate else {
+        return Err(AcmeProblem::new(
+            StatusCode::NOT_FOUND,
+            "malformed",
+            "no certificate has been issued",
+        ));
+    };
+
+    let acme_ca_cert = state
+        .certificates
+        .acme_ca_cert
+        .to_der()
+        .expect("should be able to encode ACME CA certificate");
+    let certificate_chain = [certificate, acme_ca_cert]
+        .iter()
+        .map(|contents| {
+            pem::encode_string("CERTIFICATE", LineEnding::LF, contents)
+                .expect("should be able to encode certificate")
+        })
+        .collect::<String>();
+
+    let headers = [(header::CONTENT_TYPE, "application/pem-certificate-chain")];
+    Ok((headers, certificate_chain).into_response())
+}
@@ -0,0 +1,46 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::database::{Device, devices};
+use crate::routes::acme;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+/// Installs a profile for the given device to request an attested identity via ACME.
+pub async fn install_acme_identity(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    // Our ACME server is disabled without an attestation root.
+    if state.certificates.apple_attestation_root.is_none() {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    let profile = acme::identity_profile(&state, connection, &device.udid);
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
@@ -0,0 +1,55 @@
+use crate::app_state::AppState;
+use crate::database::ActivationLockBypassCode;
+use crate::database::activation_lock_bypass_codes::dsl::*;
+use crate::escrow;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct BypassCodeResponse {
+    /// Either "server" or "device".
+    pub source: String,
+    /// The bypass code, as entered on the device to clear Activation Lock.
+    pub bypass_code: String,
+    /// The hash of this code, as given to Apple.
+    pub hash: String,
+}
+
+/// Provides all bypass codes escrowed for the given device.
+pub async fn get_bypass_codes(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let records = activation_lock_bypass_codes
+        .filter(udid.eq(&device_udid))
+        .load::<ActivationLockBypassCode>(connection)
+        .expect("can query bypass codes");
+    if records.is_empty() {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    escrow::record_access(connection, &device_udid, "activation_lock_bypass_code");
+    let mut codes = vec![];
+    for record in records {
+        let Some(bypass_code) = escrow::open_code(&state, &record) else {
+            println!("unable to decrypt bypass code for {device_udid}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+        };
+        codes.push(BypassCodeResponse {
+            source: record.source,
+            bypass_code,
+            hash: record.code_hash,
+        });
+    }
+    Json(codes).into_response()
+}
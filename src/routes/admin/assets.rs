@@ -0,0 +1,94 @@
+use crate::app_state::AppState;
+use crate::database::{StoredDeclaration, declarations as declarations_table};
+use crate::declarations::{self, AssetSource, AssetType};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use super::AdminAuth;
+use super::declarations::DeclarationResponse;
+
+#[derive(Deserialize)]
+pub struct AssetRequest {
+    /// For example, "credential.identity" or "data".
+    pub asset_type: AssetType,
+    /// Required for data assets, and otherwise determined by their type.
+    pub content_type: Option<String>,
+    /// A path relative to our assets directory.
+    pub file_name: Option<String>,
+    /// The asset's data, as base64. It is encrypted within our database.
+    pub contents: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UserIdentityRequest {
+    pub full_name: Option<String>,
+    pub email_address: Option<String>,
+}
+
+/// Responds with the given declaration, as now stored.
+fn stored_declaration(connection: &mut SqliteConnection, identifier: &str) -> Response {
+    let stored = declarations_table::table
+        .find(identifier)
+        .first::<StoredDeclaration>(connection)
+        .expect("can query declarations");
+    Json(DeclarationResponse::from(stored)).into_response()
+}
+
+/// Creates or replaces an asset declaration, whose data is served to devices it is assigned to.
+/// Its data may either be within our assets directory, or provided directly.
+pub async fn update_asset(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<AssetRequest>,
+) -> Response {
+    let source = match (request.file_name, request.contents) {
+        (Some(file_name), None) => AssetSource::File(file_name),
+        (None, Some(contents)) => match BASE64_STANDARD.decode(contents) {
+            Ok(contents) => AssetSource::Contents(contents),
+            Err(_) => {
+                return (StatusCode::BAD_REQUEST, "contents must be base64").into_response();
+            }
+        },
+        _ => {
+            let message = "exactly one of file_name or contents must be provided";
+            return (StatusCode::BAD_REQUEST, message).into_response();
+        }
+    };
+
+    let connection = &mut state.database.connection();
+    let stored = declarations::store_asset(
+        &state,
+        connection,
+        identifier,
+        request.asset_type,
+        request.content_type,
+        source,
+    );
+    match stored {
+        Ok(declaration) => stored_declaration(connection, &declaration.identifier),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+/// Creates or replaces an asset describing the user of a device,
+/// such as for use within account configurations.
+pub async fn update_user_identity(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<UserIdentityRequest>,
+) -> Response {
+    let declaration =
+        declarations::user_identity(identifier, request.full_name, request.email_address);
+    let connection = &mut state.database.connection();
+    declarations::store_declaration(&state, connection, &declaration);
+    stored_declaration(connection, &declaration.identifier)
+}
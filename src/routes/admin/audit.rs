@@ -0,0 +1,43 @@
+use crate::app_state::AppState;
+use crate::database::secret_accesses;
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct SecretAccessResponse {
+    pub secret_kind: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub access_date: OffsetDateTime,
+}
+
+/// Lists every retrieval of secrets escrowed for the given device, newest first.
+pub async fn get_access_log(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let records = secret_accesses::table
+        .filter(secret_accesses::udid.eq(&device_udid))
+        .order(secret_accesses::id.desc())
+        .select((secret_accesses::secret_kind, secret_accesses::access_date))
+        .load::<(String, OffsetDateTime)>(connection)
+        .expect("can query secret accesses");
+
+    let accesses: Vec<SecretAccessResponse> = records
+        .into_iter()
+        .map(|(secret_kind, access_date)| SecretAccessResponse {
+            secret_kind,
+            access_date,
+        })
+        .collect();
+    Json(accesses).into_response()
+}
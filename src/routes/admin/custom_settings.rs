@@ -0,0 +1,86 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::database::{Device, devices};
+use crate::payloads::{self, BasePayload, CustomSettingsFormat, PayloadScope, Profile};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+#[derive(Deserialize)]
+pub struct CustomSettingsRequest {
+    /// The preference domain to apply settings to, such as "com.example.app".
+    pub domain: String,
+    pub format: CustomSettingsFormat,
+    /// Arbitrary settings. As these are given as JSON, null values are not permitted.
+    pub settings: plist::Dictionary,
+}
+
+/// Installs a profile applying the given settings to a preference domain on a device.
+pub async fn install_custom_settings(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<CustomSettingsRequest>,
+) -> Response {
+    if request.domain.is_empty() || request.settings.is_empty() {
+        return (StatusCode::BAD_REQUEST).into_response();
+    }
+
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    if let CustomSettingsFormat::Direct = request.format
+        && let Some(reserved_key) = payloads::reserved_setting_keys(&request.settings).first()
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("{reserved_key} may not be used as a setting"),
+        )
+            .into_response();
+    }
+
+    let identifier = format!(
+        "{}.custom-settings.{}",
+        state.config.service.base_identifier, request.domain
+    );
+    let payload = payloads::custom_settings(
+        format!("{identifier}.settings"),
+        &request.domain,
+        request.settings,
+        request.format,
+    );
+    let profile = Profile {
+        base: BasePayload {
+            identifier,
+            display_name: Some(format!("Settings for {}", request.domain)),
+            organization: Some(state.config.service.organization_name.clone()),
+            ..Default::default()
+        },
+        scope: Some(PayloadScope::System),
+        contents: vec![payload],
+        ..Default::default()
+    };
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
@@ -0,0 +1,198 @@
+use crate::app_state::AppState;
+use crate::database::{Device, StoredDeclaration, declarations as declarations_table, devices};
+use crate::declarations::{self, Declaration, DeclarationCategory};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct DeclarationResponse {
+    pub identifier: String,
+    /// For example, "com.apple.configuration.passcode.settings".
+    pub declaration_type: String,
+    pub server_token: String,
+    pub payload: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub update_date: OffsetDateTime,
+}
+
+impl From<StoredDeclaration> for DeclarationResponse {
+    fn from(stored: StoredDeclaration) -> Self {
+        let declaration = Declaration::from_stored(&stored);
+        DeclarationResponse {
+            identifier: declaration.identifier,
+            declaration_type: declaration.declaration_type,
+            server_token: declaration.server_token,
+            payload: declaration.payload,
+            update_date: stored.update_date,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeclarationRequest {
+    pub declaration_type: String,
+    /// The declaration's payload, as a JSON object.
+    pub payload: Value,
+}
+
+#[derive(Deserialize)]
+pub struct AssignDeclarationsRequest {
+    /// The identifiers of every declaration this device should have.
+    pub identifiers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct AssignedDeclarationsResponse {
+    /// The DeclarativeManagement command asking the device to synchronize.
+    pub command_uuid: Uuid,
+    /// References to declarations not assigned to this device.
+    pub warnings: Vec<String>,
+}
+
+/// Lists all declarations.
+pub async fn list_declarations(_: AdminAuth, State(state): State<AppState>) -> Response {
+    let connection = &mut state.database.connection();
+    let results = declarations_table::table
+        .order(declarations_table::identifier.asc())
+        .load::<StoredDeclaration>(connection)
+        .expect("can query declarations");
+
+    let declarations: Vec<DeclarationResponse> =
+        results.into_iter().map(DeclarationResponse::from).collect();
+    Json(declarations).into_response()
+}
+
+/// Creates or replaces a declaration.
+/// Devices it is assigned to are asked to synchronize if its contents have changed.
+pub async fn update_declaration(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<DeclarationRequest>,
+) -> Response {
+    if DeclarationCategory::for_type(&request.declaration_type).is_none() {
+        let message = format!("unknown declaration type {}", request.declaration_type);
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    if !request.payload.is_object() {
+        return (StatusCode::BAD_REQUEST, "payload must be an object").into_response();
+    }
+
+    let connection = &mut state.database.connection();
+    let declaration = Declaration::new(identifier, request.declaration_type, request.payload);
+    declarations::store_declaration(&state, connection, &declaration);
+
+    let stored = declarations_table::table
+        .find(&declaration.identifier)
+        .first::<StoredDeclaration>(connection)
+        .expect("can query declarations");
+    Json(DeclarationResponse::from(stored)).into_response()
+}
+
+/// Removes a declaration from all devices, and then entirely.
+pub async fn remove_declaration(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    if !declarations::remove_declaration(&state, connection, &identifier) {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+    (StatusCode::NO_CONTENT).into_response()
+}
+
+/// Lists the declarations assigned to a device.
+pub async fn list_device_declarations(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let device_count = devices::table
+        .find(&device_udid)
+        .count()
+        .get_result::<i64>(connection)
+        .expect("can query devices");
+    if device_count == 0 {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let declarations: Vec<DeclarationResponse> =
+        declarations::device_declarations(connection, &device_udid)
+            .into_iter()
+            .map(DeclarationResponse::from)
+            .collect();
+    Json(declarations).into_response()
+}
+
+/// Replaces the declarations assigned to a device, enabling
+/// Declarative Device Management on the device if needed.
+pub async fn assign_declarations(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<AssignDeclarationsRequest>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let device = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices");
+    let Some(device) = device else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    let mut identifiers = request.identifiers;
+    identifiers.sort();
+    identifiers.dedup();
+    let stored = declarations_table::table
+        .filter(declarations_table::identifier.eq_any(&identifiers))
+        .load::<StoredDeclaration>(connection)
+        .expect("can query declarations");
+    let unknown: Vec<&str> = identifiers
+        .iter()
+        .filter(|identifier| {
+            !stored
+                .iter()
+                .any(|stored| &stored.identifier == *identifier)
+        })
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        let message = format!("unknown declarations: {}", unknown.join(", "));
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+
+    let assigned: Vec<Declaration> = stored.iter().map(Declaration::from_stored).collect();
+    let warnings = declarations::unresolved_references(&assigned);
+    for warning in &warnings {
+        println!(
+            "device {} has an invalid declaration - {warning}",
+            device.udid
+        );
+    }
+
+    let command_uuid =
+        declarations::assign_declarations(&state, connection, &device.udid, &identifiers);
+    (
+        StatusCode::ACCEPTED,
+        Json(AssignedDeclarationsResponse {
+            command_uuid,
+            warnings,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,131 @@
+use crate::app_state::AppState;
+use crate::database::{Device, bootstrap_tokens, devices};
+use crate::routes::mdm;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+/// An overview of an enrolled device.
+pub struct DeviceInventory {
+    pub udid: String,
+    pub serial_number: String,
+    pub product: String,
+    pub os_version: String,
+    pub imei: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_contact: OffsetDateTime,
+    /// Whether this device has escrowed its bootstrap token. Only applicable to Macs.
+    pub bootstrap_token_escrowed: bool,
+    /// The user this device is assigned to, if any.
+    pub user_name: Option<String>,
+    /// Present once the device has reported its name.
+    pub device_name: Option<String>,
+    pub asset_tag: Option<String>,
+    /// The device's preferred language, once it has checked in.
+    pub language: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AssetTagRequest {
+    /// The asset tag to assign this device, or null to remove it.
+    pub asset_tag: Option<String>,
+}
+
+impl DeviceInventory {
+    fn new(device: Device, bootstrap_token_udid: Option<String>) -> Self {
+        DeviceInventory {
+            udid: device.udid,
+            serial_number: device.serial_number,
+            product: device.product,
+            os_version: device.device_version,
+            imei: device.imei,
+            last_contact: device.last_contact,
+            bootstrap_token_escrowed: bootstrap_token_udid.is_some(),
+            user_name: device.user_name,
+            device_name: device.device_name,
+            asset_tag: device.asset_tag,
+            language: device.language,
+        }
+    }
+}
+
+/// Lists all enrolled devices.
+pub async fn list_devices(_: AdminAuth, State(state): State<AppState>) -> Response {
+    let connection = &mut state.database.connection();
+    let results = devices::table
+        .left_join(bootstrap_tokens::table)
+        .select((Device::as_select(), bootstrap_tokens::udid.nullable()))
+        .order(devices::udid.asc())
+        .load::<(Device, Option<String>)>(connection)
+        .expect("can query devices");
+
+    let inventory: Vec<DeviceInventory> = results
+        .into_iter()
+        .map(|(device, bootstrap_token_udid)| DeviceInventory::new(device, bootstrap_token_udid))
+        .collect();
+    Json(inventory).into_response()
+}
+
+/// Provides an overview of a single device.
+pub async fn get_device(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let result = devices::table
+        .left_join(bootstrap_tokens::table)
+        .filter(devices::udid.eq(&device_udid))
+        .select((Device::as_select(), bootstrap_tokens::udid.nullable()))
+        .first::<(Device, Option<String>)>(connection)
+        .optional()
+        .expect("can query devices");
+
+    match result {
+        Some((device, bootstrap_token_udid)) => {
+            Json(DeviceInventory::new(device, bootstrap_token_udid)).into_response()
+        }
+        None => (StatusCode::NOT_FOUND).into_response(),
+    }
+}
+
+/// Assigns an asset tag to a device, or removes it.
+pub async fn update_asset_tag(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<AssetTagRequest>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let updated_count = diesel::update(devices::table.find(&device_udid))
+        .set(devices::asset_tag.eq(&request.asset_tag))
+        .execute(connection)
+        .expect("error updating device");
+    if updated_count == 0 {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+    (StatusCode::NO_CONTENT).into_response()
+}
+
+/// Releases a device from the identity it enrolled with, such as after it was erased
+/// without checking out. Until then, it may not enroll with a new identity.
+pub async fn release_device_identity(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    if mdm::release_identity(connection, &device_udid) == 0 {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+    (StatusCode::NO_CONTENT).into_response()
+}
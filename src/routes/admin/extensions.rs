@@ -0,0 +1,188 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::database::{Device, devices};
+use crate::payloads::{
+    BasePayload, KernelExtensionPolicyPayload, Payload, PayloadScope, Profile,
+    ServiceManagementPayload, ServiceManagementRule, ServiceManagementRuleType,
+    SystemExtensionPolicyPayload, SystemExtensionType, is_valid_team_identifier,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+#[derive(Deserialize)]
+pub struct ExtensionPolicyRequest {
+    #[serde(default)]
+    pub system_extensions: Vec<SystemExtensionRule>,
+    #[serde(default)]
+    pub kernel_extensions: Vec<KernelExtensionRule>,
+    #[serde(default)]
+    pub login_items: Vec<LoginItemRule>,
+}
+
+#[derive(Deserialize)]
+pub struct SystemExtensionRule {
+    pub team_id: String,
+    /// If not specified, all extensions from this team are allowed.
+    pub bundle_id: Option<String>,
+    /// If not specified, all types are allowed.
+    #[serde(default)]
+    pub types: Vec<SystemExtensionType>,
+    /// Whether this extension may be removed without authentication.
+    #[serde(default)]
+    pub removable: bool,
+}
+
+#[derive(Deserialize)]
+pub struct KernelExtensionRule {
+    /// Empty for unsigned legacy extensions.
+    pub team_id: String,
+    /// If not specified, all extensions from this team are allowed.
+    pub bundle_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginItemRule {
+    pub rule_type: ServiceManagementRuleType,
+    pub rule_value: String,
+    pub team_id: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// Installs a profile allowing the given system extensions, kernel extensions
+/// and login items on a Mac.
+pub async fn install_extension_policy(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<ExtensionPolicyRequest>,
+) -> Response {
+    let profile = match extension_policy_profile(&state, request) {
+        Ok(profile) => profile,
+        Err(reason) => return (StatusCode::BAD_REQUEST, reason).into_response(),
+    };
+
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    if !device.is_mac() {
+        return (StatusCode::BAD_REQUEST, "device is not a Mac").into_response();
+    }
+
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
+
+/// Creates a profile containing a payload for each kind of rule requested.
+/// Returns a description of the first invalid rule as an error.
+fn extension_policy_profile(
+    state: &AppState,
+    request: ExtensionPolicyRequest,
+) -> Result<Profile<Payload>, String> {
+    let base_identifier = format!("{}.extension-policy", state.config.service.base_identifier);
+    let validate_team_id = |team_id: &str| {
+        if is_valid_team_identifier(team_id) {
+            Ok(())
+        } else {
+            Err(format!("invalid team identifier {team_id}"))
+        }
+    };
+
+    let mut payloads = vec![];
+    if !request.system_extensions.is_empty() {
+        let mut payload = SystemExtensionPolicyPayload::default();
+        payload.base.identifier = format!("{base_identifier}.system-extensions");
+        for rule in request.system_extensions {
+            validate_team_id(&rule.team_id)?;
+            match &rule.bundle_id {
+                Some(bundle_id) => payload.allow_extension(&rule.team_id, bundle_id),
+                None => payload.allow_team(&rule.team_id),
+            }
+            payload.allow_types(&rule.team_id, &rule.types);
+            if rule.removable {
+                let Some(bundle_id) = &rule.bundle_id else {
+                    return Err("removable extensions must specify their bundle ID".to_string());
+                };
+                payload.allow_removal(&rule.team_id, bundle_id);
+            }
+        }
+        payloads.push(Payload::SystemExtensionPolicy(payload));
+    }
+
+    if !request.kernel_extensions.is_empty() {
+        let mut payload = KernelExtensionPolicyPayload::default();
+        payload.base.identifier = format!("{base_identifier}.kernel-extensions");
+        for rule in request.kernel_extensions {
+            match &rule.bundle_id {
+                // Unsigned extensions have no team.
+                Some(bundle_id) if rule.team_id.is_empty() => {
+                    payload.allow_extension("", bundle_id)
+                }
+                Some(bundle_id) => {
+                    validate_team_id(&rule.team_id)?;
+                    payload.allow_extension(&rule.team_id, bundle_id)
+                }
+                None => {
+                    validate_team_id(&rule.team_id)?;
+                    payload.allow_team(&rule.team_id)
+                }
+            }
+        }
+        payloads.push(Payload::KernelExtensionPolicy(payload));
+    }
+
+    if !request.login_items.is_empty() {
+        let mut payload = ServiceManagementPayload::default();
+        payload.base.identifier = format!("{base_identifier}.login-items");
+        for rule in request.login_items {
+            if let Some(team_id) = &rule.team_id {
+                validate_team_id(team_id)?;
+            }
+            if let ServiceManagementRuleType::TeamIdentifier = rule.rule_type {
+                validate_team_id(&rule.rule_value)?;
+            }
+            payload.rules.push(ServiceManagementRule {
+                rule_type: rule.rule_type,
+                rule_value: rule.rule_value,
+                team_identifier: rule.team_id,
+                comment: rule.comment,
+            });
+        }
+        payloads.push(Payload::ServiceManagement(payload));
+    }
+
+    if payloads.is_empty() {
+        return Err("no rules were specified".to_string());
+    }
+    Ok(Profile {
+        base: BasePayload {
+            identifier: base_identifier,
+            display_name: Some("Extension Policy".to_string()),
+            organization: Some(state.config.service.organization_name.clone()),
+            ..Default::default()
+        },
+        scope: Some(PayloadScope::System),
+        contents: payloads,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,67 @@
+use crate::app_state::AppState;
+use crate::commands::{self, Command, FileVaultUnlock, RotateFileVaultKey};
+use crate::database::{FilevaultRecoveryKey, filevault_recovery_keys};
+use crate::escrow;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use der::Encode;
+use diesel::prelude::*;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct RecoveryKeyResponse {
+    pub recovery_key: String,
+}
+
+/// Provides the FileVault personal recovery key escrowed for the given device.
+///
+/// As this key has now been seen, we'll ask the device to rotate it.
+/// Its replacement is escrowed once the device responds.
+/// Given these side effects, this is only available via POST.
+pub async fn retrieve_recovery_key(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let record = filevault_recovery_keys::table
+        .find(&device_udid)
+        .first::<FilevaultRecoveryKey>(connection)
+        .optional()
+        .expect("can query recovery keys");
+    let Some(record) = record else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    let Some(recovery_key) = escrow::open_recovery_key(&state, &record) else {
+        println!("unable to decrypt recovery key for {device_udid}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+
+    escrow::record_access(connection, &device_udid, "filevault_recovery_key");
+    diesel::update(filevault_recovery_keys::table.find(&device_udid))
+        .set(filevault_recovery_keys::viewed_date.eq(OffsetDateTime::now_utc()))
+        .execute(connection)
+        .expect("error updating recovery key");
+
+    if !commands::is_pending(connection, &device_udid, "RotateFileVaultKey") {
+        let escrow_cert_contents = state.certificates.escrow_cert.to_der().unwrap();
+        Command::RotateFileVaultKey(RotateFileVaultKey {
+            key_type: "personal".to_string(),
+            // The current recovery key is able to unlock FileVault.
+            unlock: FileVaultUnlock {
+                password: Some(recovery_key.clone()),
+            },
+            reply_encryption_certificate: escrow_cert_contents,
+        })
+        .enqueue(&state, connection, &device_udid);
+    }
+
+    Json(RecoveryKeyResponse { recovery_key }).into_response()
+}
@@ -0,0 +1,67 @@
+use crate::app_state::AppState;
+use crate::assets;
+use crate::commands;
+use crate::database::{Device, devices};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+#[derive(Deserialize)]
+pub struct InstallFontsRequest {
+    /// The PostScript names of fonts to install, as listed by `list_fonts`.
+    pub fonts: Vec<String>,
+}
+
+/// Lists all fonts available within our assets directory.
+pub async fn list_fonts(_: AdminAuth, State(state): State<AppState>) -> Response {
+    Json(assets::index_fonts(&state.config)).into_response()
+}
+
+/// Installs a profile containing the given fonts on a device.
+pub async fn install_fonts(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<InstallFontsRequest>,
+) -> Response {
+    if request.fonts.is_empty() {
+        return (StatusCode::BAD_REQUEST).into_response();
+    }
+
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    let profile = match assets::font_profile(&state.config, &request.fonts) {
+        Ok(profile) => profile,
+        Err(missing_font) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("unknown font {missing_font}"),
+            )
+                .into_response();
+        }
+    };
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
@@ -0,0 +1,91 @@
+use crate::app_state::AppState;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{StatusCode, header, request::Parts},
+};
+
+mod acme;
+mod activation_lock;
+mod assets;
+mod audit;
+mod custom_settings;
+mod declarations;
+mod devices;
+mod extensions;
+mod filevault;
+mod fonts;
+mod passwords;
+mod profiles;
+mod software_updates;
+mod statuses;
+mod users;
+mod web_clips;
+
+pub use acme::install_acme_identity;
+pub use activation_lock::get_bypass_codes;
+pub use assets::{update_asset, update_user_identity};
+pub use audit::get_access_log;
+pub use custom_settings::install_custom_settings;
+pub use declarations::{
+    assign_declarations, list_declarations, list_device_declarations, remove_declaration,
+    update_declaration,
+};
+pub use devices::{get_device, list_devices, release_device_identity, update_asset_tag};
+pub use extensions::install_extension_policy;
+pub use filevault::retrieve_recovery_key;
+pub use fonts::{install_fonts, list_fonts};
+pub use passwords::{get_admin_account, get_passwords, rotate_password, verify_password};
+pub use profiles::{get_removal_password, import_profile, list_installed_profiles};
+pub use software_updates::{
+    get_software_update_compliance, update_software_update, update_software_update_settings,
+};
+pub use statuses::{get_device_status, get_status_history, update_status_subscriptions};
+pub use users::{assign_user, install_accounts, list_users, update_user};
+pub use web_clips::install_web_clips;
+
+/// Guards administrative endpoints, such as those exposing escrowed secrets.
+///
+/// Requests must provide the configured API token via
+/// the `Authorization: Bearer <token>` header.
+/// If no token is configured, all administrative requests are rejected.
+pub struct AdminAuth;
+
+impl<S> FromRequestParts<S> for AdminAuth
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+        let Some(api_token) = state.config.admin.api_token else {
+            return Err(StatusCode::FORBIDDEN);
+        };
+
+        let Some(given_token) = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+
+        if !constant_time_eq(given_token.as_bytes(), api_token.as_bytes()) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(AdminAuth)
+    }
+}
+
+/// Compares two values without exiting early, avoiding timing attacks.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0, |difference, (l, r)| difference | (l ^ r))
+        == 0
+}
@@ -0,0 +1,188 @@
+use crate::app_state::AppState;
+use crate::database::{Device, DevicePassword, device_passwords, devices};
+use crate::escrow::{self, PasswordKind};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+/// A Recovery Lock or firmware password we've generated for a device.
+pub struct PasswordResponse {
+    /// Either "recovery_lock" or "firmware".
+    pub kind: String,
+    /// One of "pending", "active", "superseded" or "failed".
+    pub status: String,
+    pub password: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub creation_date: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub activation_date: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub verification_date: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct QueuedCommandResponse {
+    pub command_uuid: Uuid,
+}
+
+/// Provides the history of pre-boot passwords generated for the given device, newest first.
+///
+/// Our administrator account's password is excluded,
+/// as retrieving it via `get_admin_account` rotates it.
+pub async fn get_passwords(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let records = device_passwords::table
+        .filter(device_passwords::udid.eq(&device_udid))
+        .filter(device_passwords::kind.ne(PasswordKind::AdminAccount.as_str()))
+        .order(device_passwords::id.desc())
+        .load::<DevicePassword>(connection)
+        .expect("can query device passwords");
+    if records.is_empty() {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    escrow::record_access(connection, &device_udid, "device_passwords");
+    let mut passwords = vec![];
+    for record in records {
+        let Some(password) = escrow::open_password(&state, &record) else {
+            println!("unable to decrypt password for {device_udid}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+        };
+        passwords.push(PasswordResponse {
+            kind: record.kind,
+            status: record.status,
+            password,
+            creation_date: record.creation_date,
+            activation_date: record.activation_date,
+            verification_date: record.verification_date,
+        });
+    }
+    Json(passwords).into_response()
+}
+
+/// Generates a new password of the given kind and queues it for the device.
+pub async fn rotate_password(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path((device_udid, password_kind)): Path<(String, PasswordKind)>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let device = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices");
+    match device {
+        Some(device) if device.is_mac() => {}
+        _ => return (StatusCode::NOT_FOUND).into_response(),
+    }
+
+    // Only one password change may be in flight at a time,
+    // as each authorizes itself with the password currently active.
+    if escrow::has_pending_password(connection, &device_udid, password_kind) {
+        return (StatusCode::CONFLICT).into_response();
+    }
+
+    // We may not yet be able to change this password,
+    // such as if the device has not reported its administrator account.
+    let Some(command_uuid) =
+        escrow::rotate_password(&state, connection, &device_udid, password_kind)
+    else {
+        escrow::request_admin_account_guid(&state, connection, &device_udid);
+        return (StatusCode::CONFLICT).into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
+
+/// Queues a command for the device to verify its active password of the given kind.
+pub async fn verify_password(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path((device_udid, password_kind)): Path<(String, PasswordKind)>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let Some(command_uuid) =
+        escrow::verify_password(&state, connection, &device_udid, password_kind)
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
+
+#[derive(Serialize)]
+pub struct AdminAccountResponse {
+    pub short_name: Option<String>,
+    pub password: String,
+    /// The command rotating this password, now that it has been seen.
+    /// This is absent if a rotation was already pending.
+    pub rotation_command_uuid: Option<Uuid>,
+}
+
+/// Provides the password of our managed administrator account on the given device.
+///
+/// As this password has now been seen, we'll immediately rotate it.
+/// It remains valid until the device acknowledges its replacement.
+/// If it cannot yet be rotated, as the device has not reported the account's GUID,
+/// the password is withheld and we'll ask the device for it again.
+pub async fn get_admin_account(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let password_kind = PasswordKind::AdminAccount;
+    let Some(record) = escrow::active_password(connection, &device_udid, password_kind) else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    let Some(password) = escrow::open_password(&state, &record) else {
+        println!("unable to decrypt administrator password for {device_udid}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+
+    let rotation_command_uuid = if escrow::has_pending_password(
+        connection,
+        &device_udid,
+        password_kind,
+    ) {
+        None
+    } else {
+        let Some(command_uuid) =
+            escrow::rotate_password(&state, connection, &device_udid, password_kind)
+        else {
+            escrow::request_admin_account_guid(&state, connection, &device_udid);
+            let message = "the device has not yet reported its administrator account, so its password cannot be rotated";
+            return (StatusCode::CONFLICT, message).into_response();
+        };
+        Some(command_uuid)
+    };
+    escrow::record_access(connection, &device_udid, password_kind.as_str());
+
+    Json(AdminAccountResponse {
+        short_name: state.config.passwords.admin_account_name.clone(),
+        password,
+        rotation_command_uuid,
+    })
+    .into_response()
+}
@@ -0,0 +1,161 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::database::{
+    Device, InstalledProfile, ProfileRemovalPassword, commands as commands_table, devices,
+    installed_profiles, profile_removal_passwords,
+};
+use crate::escrow;
+use crate::payloads::{self, TemplateVariables};
+use crate::users;
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct ImportedProfileResponse {
+    pub command_uuid: Uuid,
+    /// The subject of the certificate the profile was originally signed with.
+    pub signer: Option<String>,
+    /// Issues within the profile, such as duplicate PayloadUUIDs or unknown keys.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+/// The latest revision of a profile installed on a device.
+pub struct InstalledProfileResponse {
+    pub identifier: String,
+    pub version: i32,
+    pub content_hash: String,
+    pub command_uuid: String,
+    /// The status of the command installing this revision, such as "Acknowledged".
+    pub status: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub update_date: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+pub struct RemovalPasswordResponse {
+    pub identifier: String,
+    /// The password a user must enter to remove this profile.
+    pub removal_password: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub creation_date: OffsetDateTime,
+}
+
+/// Provides the password required to remove the profile with the given identifier.
+/// Passwords are shared by all devices, so their access is not attributed to a device.
+pub async fn get_removal_password(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let record = profile_removal_passwords::table
+        .find(&identifier)
+        .first::<ProfileRemovalPassword>(connection)
+        .optional()
+        .expect("can query removal passwords");
+    let Some(record) = record else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    let Some(removal_password) = escrow::open_removal_password(&state, &record) else {
+        println!("unable to decrypt removal password for profile {identifier}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+
+    println!("removal password for profile {identifier} was retrieved");
+    Json(RemovalPasswordResponse {
+        identifier: record.identifier,
+        removal_password,
+        creation_date: record.creation_date,
+    })
+    .into_response()
+}
+
+/// Lists the profiles we have installed on a device, alongside their versions.
+pub async fn list_installed_profiles(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let results = installed_profiles::table
+        .inner_join(commands_table::table)
+        .filter(installed_profiles::udid.eq(&device_udid))
+        .select((InstalledProfile::as_select(), commands_table::status))
+        .order(installed_profiles::identifier.asc())
+        .load::<(InstalledProfile, String)>(connection)
+        .expect("can query installed profiles");
+
+    let profiles: Vec<InstalledProfileResponse> = results
+        .into_iter()
+        .map(|(profile, status)| InstalledProfileResponse {
+            identifier: profile.identifier,
+            version: profile.version,
+            content_hash: profile.content_hash,
+            command_uuid: profile.command_uuid,
+            status,
+            update_date: profile.update_date,
+        })
+        .collect();
+    Json(profiles).into_response()
+}
+
+/// Installs an existing .mobileconfig file, signed or unsigned, on a device.
+/// The profile is re-signed by us, regardless of its original signer.
+///
+/// The profile is rendered as a template for the device, expanding placeholders
+/// such as `$SERIALNUMBER` or `{{user.email}}` from its inventory and assigned user.
+///
+/// If the profile cannot be imported, its warnings are returned alongside 400 Bad Request.
+/// If it refers to variables without a value for this device, 409 Conflict is returned.
+pub async fn import_profile(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    body: Bytes,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    let imported = match payloads::import_profile(body.to_vec()) {
+        Ok(imported) => imported,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let Some(profile) = imported.profile else {
+        return (StatusCode::BAD_REQUEST, imported.warnings.join("\n")).into_response();
+    };
+    let user = users::assigned_user(connection, &device);
+    let variables = TemplateVariables::for_device(&device, user.as_ref());
+    let profile = match profile.render(&variables) {
+        Ok(profile) => profile,
+        Err(reason) => return (StatusCode::CONFLICT, reason).into_response(),
+    };
+
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    let response = ImportedProfileResponse {
+        command_uuid,
+        signer: imported.signer.map(|signer| signer.describe()),
+        warnings: imported.warnings,
+    };
+    (StatusCode::ACCEPTED, Json(response)).into_response()
+}
@@ -0,0 +1,135 @@
+use crate::app_state::AppState;
+use crate::declarations::{
+    self, Declaration, SoftwareUpdateDeferrals, SoftwareUpdateEnforcement, SoftwareUpdateSettings,
+    UPDATE_STATUS_ITEMS,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use super::AdminAuth;
+
+#[derive(Deserialize)]
+pub struct SoftwareUpdateRequest {
+    /// For example, "18.1".
+    pub target_os_version: String,
+    pub target_build_version: Option<String>,
+    /// The deadline within each device's local time zone, such as "2026-11-01T18:00:00".
+    pub target_local_date_time: String,
+    pub details_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SoftwareUpdateSettingsRequest {
+    pub notifications: Option<bool>,
+    pub combined_deferral_days: Option<u8>,
+    pub major_deferral_days: Option<u8>,
+    pub minor_deferral_days: Option<u8>,
+    pub system_deferral_days: Option<u8>,
+}
+
+#[derive(Serialize)]
+pub struct SoftwareUpdateDeclarationsResponse {
+    /// The identifiers of every declaration created,
+    /// all of which should be assigned to devices.
+    pub identifiers: Vec<String>,
+}
+
+/// Stores the given configurations alongside an activation applying them.
+fn store_with_activation(state: &AppState, configurations: Vec<Declaration>) -> Response {
+    let activation = Declaration::simple_activation(
+        format!("{}.activation", configurations[0].identifier),
+        configurations
+            .iter()
+            .map(|configuration| configuration.identifier.clone())
+            .collect(),
+    );
+
+    let connection = &mut state.database.connection();
+    let mut identifiers = vec![];
+    for declaration in configurations.iter().chain([&activation]) {
+        declarations::store_declaration(state, connection, declaration);
+        identifiers.push(declaration.identifier.clone());
+    }
+    Json(SoftwareUpdateDeclarationsResponse { identifiers }).into_response()
+}
+
+/// Creates or replaces declarations enforcing an update by a deadline.
+/// Devices are additionally subscribed to their update status, so that
+/// their progress is known.
+pub async fn update_software_update(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<SoftwareUpdateRequest>,
+) -> Response {
+    let enforcement = SoftwareUpdateEnforcement {
+        target_os_version: request.target_os_version,
+        target_build_version: request.target_build_version,
+        target_local_date_time: request.target_local_date_time,
+        details_url: request.details_url,
+    };
+    let enforcement = match enforcement.into_declaration(identifier) {
+        Ok(enforcement) => enforcement,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let status_items = UPDATE_STATUS_ITEMS.map(str::to_string);
+    let subscriptions = declarations::status_subscriptions(
+        format!("{}.status", enforcement.identifier),
+        &status_items,
+    )
+    .expect("update status items should be known");
+
+    store_with_activation(&state, vec![enforcement, subscriptions])
+}
+
+/// Creates or replaces declarations configuring update deferrals and notifications.
+pub async fn update_software_update_settings(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<SoftwareUpdateSettingsRequest>,
+) -> Response {
+    let deferrals = SoftwareUpdateDeferrals {
+        combined_period_in_days: request.combined_deferral_days,
+        major_period_in_days: request.major_deferral_days,
+        minor_period_in_days: request.minor_deferral_days,
+        system_period_in_days: request.system_deferral_days,
+    };
+    let has_deferrals = [
+        deferrals.combined_period_in_days,
+        deferrals.major_period_in_days,
+        deferrals.minor_period_in_days,
+        deferrals.system_period_in_days,
+    ]
+    .iter()
+    .any(Option::is_some);
+    let settings = SoftwareUpdateSettings {
+        notifications: request.notifications,
+        deferrals: has_deferrals.then_some(deferrals),
+    };
+    let settings = match settings.into_declaration(identifier) {
+        Ok(settings) => settings,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    store_with_activation(&state, vec![settings])
+}
+
+/// Lists the update progress of every device assigned the given enforcement declaration,
+/// including whether they have missed its deadline.
+pub async fn get_software_update_compliance(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    match declarations::update_compliance(connection, &identifier) {
+        Some(compliance) => Json(compliance).into_response(),
+        None => (StatusCode::NOT_FOUND).into_response(),
+    }
+}
@@ -0,0 +1,163 @@
+use crate::app_state::AppState;
+use crate::database::{DeviceStatus, device_status_changes, device_statuses, devices};
+use crate::declarations::{self, Declaration};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use super::AdminAuth;
+
+#[derive(Serialize)]
+pub struct DeviceStatusResponse {
+    /// For example, "softwareupdate.install-state".
+    pub status_item: String,
+    pub value: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub update_date: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+pub struct StatusChangeResponse {
+    pub status_item: String,
+    /// Absent if the device no longer reports this item.
+    pub value: Option<Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub report_date: OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct StatusHistoryQuery {
+    /// Limits history to a single status item.
+    pub status_item: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct StatusSubscriptionsRequest {
+    /// The status items devices should report, such as "device.operating-system.version".
+    pub status_items: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct StatusSubscriptionsResponse {
+    /// The identifiers of the configuration and its activation,
+    /// both of which should be assigned to devices.
+    pub identifiers: Vec<String>,
+}
+
+/// Parses a value we've stored as JSON.
+fn parse_value(value: &str) -> Value {
+    serde_json::from_str(value).expect("stored status values should be valid JSON")
+}
+
+/// Whether the given device exists.
+fn device_exists(connection: &mut SqliteConnection, device_udid: &str) -> bool {
+    let device_count = devices::table
+        .find(device_udid)
+        .count()
+        .get_result::<i64>(connection)
+        .expect("can query devices");
+    device_count > 0
+}
+
+/// Provides the latest value of every status item the device has reported.
+pub async fn get_device_status(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    if !device_exists(connection, &device_udid) {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let results = device_statuses::table
+        .filter(device_statuses::udid.eq(&device_udid))
+        .order(device_statuses::status_item.asc())
+        .load::<DeviceStatus>(connection)
+        .expect("can query device statuses");
+    let statuses: Vec<DeviceStatusResponse> = results
+        .into_iter()
+        .map(|status| DeviceStatusResponse {
+            value: parse_value(&status.value),
+            status_item: status.status_item,
+            update_date: status.update_date,
+        })
+        .collect();
+    Json(statuses).into_response()
+}
+
+/// Lists every change to the device's status items, newest first.
+pub async fn get_status_history(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Query(query): Query<StatusHistoryQuery>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    if !device_exists(connection, &device_udid) {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let mut changes = device_status_changes::table
+        .filter(device_status_changes::udid.eq(&device_udid))
+        .order(device_status_changes::id.desc())
+        .select((
+            device_status_changes::status_item,
+            device_status_changes::value,
+            device_status_changes::report_date,
+        ))
+        .into_boxed();
+    if let Some(status_item) = query.status_item {
+        changes = changes.filter(device_status_changes::status_item.eq(status_item));
+    }
+    let results = changes
+        .load::<(String, Option<String>, OffsetDateTime)>(connection)
+        .expect("can query device status changes");
+
+    let changes: Vec<StatusChangeResponse> = results
+        .into_iter()
+        .map(|(status_item, value, report_date)| StatusChangeResponse {
+            status_item,
+            value: value.as_deref().map(parse_value),
+            report_date,
+        })
+        .collect();
+    Json(changes).into_response()
+}
+
+/// Creates or replaces declarations subscribing devices to the given status items,
+/// alongside an activation applying them.
+pub async fn update_status_subscriptions(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Json(request): Json<StatusSubscriptionsRequest>,
+) -> Response {
+    let configuration = match declarations::status_subscriptions(identifier, &request.status_items)
+    {
+        Ok(configuration) => configuration,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let activation = Declaration::simple_activation(
+        format!("{}.activation", configuration.identifier),
+        vec![configuration.identifier.clone()],
+    );
+    let subscriptions = [configuration, activation];
+
+    let connection = &mut state.database.connection();
+    for declaration in &subscriptions {
+        declarations::store_declaration(&state, connection, declaration);
+    }
+    let identifiers = subscriptions
+        .into_iter()
+        .map(|declaration| declaration.identifier)
+        .collect();
+    Json(StatusSubscriptionsResponse { identifiers }).into_response()
+}
@@ -0,0 +1,154 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::database::{Device, User, devices, users};
+use crate::users as user_records;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+#[derive(Serialize)]
+pub struct UserResponse {
+    pub user_name: String,
+    pub full_name: Option<String>,
+    pub email_address: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub creation_date: OffsetDateTime,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        UserResponse {
+            user_name: user.user_name,
+            full_name: user.full_name,
+            email_address: user.email_address,
+            creation_date: user.creation_date,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateUserRequest {
+    pub full_name: Option<String>,
+    pub email_address: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AssignUserRequest {
+    /// The user to assign this device to, or null to unassign it.
+    pub user_name: Option<String>,
+}
+
+/// Lists all users.
+pub async fn list_users(_: AdminAuth, State(state): State<AppState>) -> Response {
+    let connection = &mut state.database.connection();
+    let results = users::table
+        .order(users::user_name.asc())
+        .load::<User>(connection)
+        .expect("can query users");
+
+    let users: Vec<UserResponse> = results.into_iter().map(UserResponse::from).collect();
+    Json(users).into_response()
+}
+
+/// Creates a user, or replaces the attributes of an existing user.
+pub async fn update_user(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(user_name): Path<String>,
+    Json(request): Json<UpdateUserRequest>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let user = User {
+        user_name,
+        full_name: request.full_name,
+        email_address: request.email_address,
+        creation_date: OffsetDateTime::now_utc(),
+    };
+    diesel::insert_into(users::table)
+        .values(&user)
+        .on_conflict(users::user_name)
+        .do_update()
+        .set((
+            users::full_name.eq(&user.full_name),
+            users::email_address.eq(&user.email_address),
+        ))
+        .execute(connection)
+        .expect("error persisting user");
+    (StatusCode::NO_CONTENT).into_response()
+}
+
+/// Assigns a device to a user, or unassigns it.
+pub async fn assign_user(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<AssignUserRequest>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    if let Some(user_name) = &request.user_name {
+        let user_count = users::table
+            .find(user_name)
+            .count()
+            .get_result::<i64>(connection)
+            .expect("can query users");
+        if user_count == 0 {
+            return (StatusCode::NOT_FOUND, format!("unknown user {user_name}")).into_response();
+        }
+    }
+
+    let updated_count = diesel::update(devices::table.find(&device_udid))
+        .set(devices::user_name.eq(&request.user_name))
+        .execute(connection)
+        .expect("error updating device");
+    if updated_count == 0 {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+    (StatusCode::NO_CONTENT).into_response()
+}
+
+/// Installs a profile configuring our accounts for the device's assigned user.
+pub async fn install_accounts(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    let Some(user) = user_records::assigned_user(connection, &device) else {
+        return (StatusCode::CONFLICT, "device has no assigned user").into_response();
+    };
+
+    let profile = match user_records::account_profile(&state.config, &user) {
+        Ok(profile) => profile,
+        Err(reason) => return (StatusCode::CONFLICT, reason).into_response(),
+    };
+    // There's nothing to install if no accounts are configured.
+    if profile.contents.is_empty() {
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
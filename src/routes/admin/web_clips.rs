@@ -0,0 +1,61 @@
+use crate::app_state::AppState;
+use crate::assets::{self, WebClipAsset};
+use crate::commands;
+use crate::database::{Device, devices};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use super::AdminAuth;
+use super::passwords::QueuedCommandResponse;
+
+#[derive(Deserialize)]
+pub struct InstallWebClipsRequest {
+    pub web_clips: Vec<WebClipAsset>,
+}
+
+/// Installs a profile containing the given web clips on a device.
+pub async fn install_web_clips(
+    _: AdminAuth,
+    State(state): State<AppState>,
+    Path(device_udid): Path<String>,
+    Json(request): Json<InstallWebClipsRequest>,
+) -> Response {
+    if request.web_clips.is_empty() {
+        return (StatusCode::BAD_REQUEST).into_response();
+    }
+
+    let connection = &mut state.database.connection();
+    let Some(device) = devices::table
+        .find(&device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+
+    let profile = match assets::web_clip_profile(&state.config, request.web_clips) {
+        Ok(profile) => profile,
+        Err(missing_icon) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("unknown icon {missing_icon}"),
+            )
+                .into_response();
+        }
+    };
+    let Ok(command_uuid) = commands::install_profile(&state, connection, &device, profile) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+    };
+    (
+        StatusCode::ACCEPTED,
+        Json(QueuedCommandResponse { command_uuid }),
+    )
+        .into_response()
+}
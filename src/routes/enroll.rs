@@ -1,8 +1,13 @@
 use crate::app_state::AppState;
+use crate::assets::AcceptLanguage;
 use crate::certificates::{Pkcs7Body, Pkcs7Signer};
+use crate::config::ServiceConfig;
 use crate::database::pending_enrollments::dsl::*;
-use crate::database::{PendingEnrollment, pending_enrollments};
-use crate::payloads::{BasePayload, PayloadType, Profile, ScepPayload, ScepPayloadContents};
+use crate::database::{PendingEnrollment, devices, pending_enrollments};
+use crate::payloads::{
+    ALL_ACCESS_RIGHTS, BOOTSTRAP_TOKEN_CAPABILITY, BasePayload, MdmPayload, Payload, PayloadType,
+    Profile, ProfileOptions, ScepPayload, ScepPayloadContents, name_based_uuid,
+};
 use crate::plist::Plist;
 use axum::{
     extract::State,
@@ -10,6 +15,7 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use diesel::ExpressionMethods;
+use diesel::OptionalExtension;
 use diesel::query_dsl::*;
 use rand::distr::{Alphanumeric, SampleString};
 use serde::{Deserialize, Serialize};
@@ -35,6 +41,8 @@ pub struct EnrollProfile {
     pub version: isize,
     #[serde(rename = "PayloadContent")]
     pub contents: EnrollPayload,
+    #[serde(flatten)]
+    pub options: ProfileOptions,
 }
 
 /// Our custom payload format for enroll.
@@ -50,9 +58,16 @@ pub struct EnrollPayload {
 
 /// Handles the enrollment profile payload.
 /// https://developer.apple.com/library/archive/documentation/NetworkingInternet/Conceptual/iPhoneOTAConfiguration/profile-service/profile-service.html#//apple_ref/doc/uid/TP40009505-CH2-SW17
-pub async fn generate_enroll_payload(State(state): State<AppState>) -> Response {
+/// Its strings are localized per the requesting browser's Accept-Language header.
+pub async fn generate_enroll_payload(
+    State(state): State<AppState>,
+    languages: AcceptLanguage,
+) -> Response {
     let connection = &mut state.database.connection();
     let service_config = &state.config.service;
+    let messages = state.messages.localizer(&languages.0);
+    let organization = [("organization", service_config.organization_name.as_str())];
+    let profile_config = &state.config.profiles.enrollment;
 
     // We'll persist this challenge to identify enrollment later.
     // TODO: Have proper authentication for challenge creation
@@ -66,28 +81,25 @@ pub async fn generate_enroll_payload(State(state): State<AppState>) -> Response
         .execute(connection)
         .expect("error persiting challenge");
 
-    // TODO: Allow for description configuration
-    //
     // This profile is a special case: The enrollment payload must be the top-level value,
     // i.e. there is no array of PayloadContent.
     // We encode this ourselves and return the signed response manually.
+    // Its UUID remains stable, so that devices treat it as the same profile.
+    let profile_identifier = format!("{}.profile-service", service_config.base_identifier);
     let enroll_profile = EnrollProfile {
         payload_type: PayloadType::ProfileService,
-        identifier: format!("{}.profile-service", service_config.base_identifier),
+        uuid: name_based_uuid(&service_config.uuid_namespace(), &profile_identifier),
+        identifier: profile_identifier,
         organization: service_config.organization_name.to_owned(),
-        display_name: format!("{} Enrollment", service_config.organization_name),
-        description: format!(
-            "Install this profile to enroll into the MDM service for \"{}\".
-If you do not recognize this name, please remove this profile.",
-            service_config.organization_name
-        ),
-        uuid: Uuid::new_v4(),
+        display_name: messages.get("enrollment.display_name", &organization),
+        description: messages.get("enrollment.description", &organization),
         version: 1,
         contents: EnrollPayload {
             url: format!("https://{}/profile", service_config.base_domain),
             device_attributes: ["UDID", "VERSION", "PRODUCT", "SERIAL", "IMEI"],
             challenge: random_challenge,
         },
+        options: profile_config.options(),
     };
 
     state.serve_profile(enroll_profile)
@@ -95,24 +107,26 @@ If you do not recognize this name, please remove this profile.",
 
 #[derive(Deserialize, Debug)]
 /// A property list given via its POST body within an PKCS#7 envelope.
+/// Devices additionally provide the other attributes we request (e.g. PRODUCT),
+/// but they are recorded upon Authenticate instead. Notably, IMEI is absent
+/// on devices without cellular connectivity.
 pub struct EnrollRequest {
     #[serde(rename = "CHALLENGE")]
     pub challenge: String,
-    #[serde(rename = "IMEI")]
-    pub imei: String,
-    #[serde(rename = "PRODUCT")]
-    pub product: String,
-    #[serde(rename = "SERIAL")]
-    pub serial: String,
     #[serde(rename = "UDID")]
     pub udid: String,
-    #[serde(rename = "VERSION")]
-    pub device_version: String,
 }
 
 /// Responds to a request to begin enrollment.
 /// https://developer.apple.com/library/archive/documentation/NetworkingInternet/Conceptual/iPhoneOTAConfiguration/profile-service/profile-service.html#//apple_ref/doc/uid/TP40009505-CH2-SW17
-pub async fn begin_enrollment(State(state): State<AppState>, envelope: Pkcs7Body) -> Response {
+///
+/// Our response is localized in the device's stored language if it was previously enrolled,
+/// or per its Accept-Language header otherwise.
+pub async fn begin_enrollment(
+    State(state): State<AppState>,
+    languages: AcceptLanguage,
+    envelope: Pkcs7Body,
+) -> Response {
     let service_config = &state.config.service;
 
     // Now that Pkcs7Body has determined the issuer and have extracted
@@ -132,6 +146,19 @@ pub async fn begin_enrollment(State(state): State<AppState>, envelope: Pkcs7Body
         return (StatusCode::UNAUTHORIZED).into_response();
     }
 
+    let stored_language = devices::table
+        .find(&contents.udid)
+        .select(devices::language)
+        .first::<Option<String>>(connection)
+        .optional()
+        .expect("can query devices")
+        .flatten();
+    let languages = match stored_language {
+        Some(language) => vec![language],
+        None => languages.0,
+    };
+    let messages = state.messages.localizer(&languages);
+
     // To give an overview, enrollment can go one of two ways:
     //  - If the supplied PKCS#7 body is signed by Apple, we need to supply SCEP information.
     //    (This occurs initially, immediately after profile installation.)
@@ -143,44 +170,89 @@ pub async fn begin_enrollment(State(state): State<AppState>, envelope: Pkcs7Body
             let profile = Profile {
                 base: BasePayload {
                     identifier: format!("{}.scep", service_config.base_identifier),
-                    display_name: Some("SCEP Payload".to_string()),
+                    display_name: Some(messages.get("enrollment.scep.display_name", &[])),
                     ..Default::default()
                 },
-                contents: vec![ScepPayload {
-                    base: BasePayload {
-                        identifier: format!("{}.scep.config", service_config.base_identifier),
-                        payload_type: PayloadType::Scep,
-                        ..Default::default()
-                    },
-                    contents: ScepPayloadContents {
-                        // We'll reuse the challenge from MDM.
-                        challenge: contents.challenge,
-                        key_type: "RSA".to_string(),
-                        // 5 is digital signature (1) + key encipherment (5)
-                        key_usage: 5,
-                        key_size: 2048,
-                        name: service_config.device_ca_name.clone(),
-                        subject: vec![
-                            vec![vec![
-                                "O".to_string(),
-                                service_config.organization_name.clone(),
-                            ]],
-                            vec![vec!["CN".to_string(), service_config.base_domain.clone()]],
-                        ],
-                        // /cgi-bin/pkiclient.exe seems to be standard.
-                        url: format!(
-                            "https://{}/cgi-bin/pkiclient.exe",
-                            service_config.base_domain
-                        ),
-                    },
-                }],
+                contents: vec![scep_payload(service_config, contents.challenge)],
                 ..Default::default()
             };
             state.serve_profile(profile)
         }
         Pkcs7Signer::Ourselves => {
-            println!("Issued by our root CA");
-            "TODO".to_string().into_response()
+            // We'll now supply the MDM payload, alongside the identity it authenticates with.
+            let Some(topic) = &state.config.push.topic else {
+                println!(
+                    "unable to enroll {}: no push topic is configured",
+                    contents.udid
+                );
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                    .into_response();
+            };
+            let identity = scep_payload(service_config, contents.challenge);
+            let mdm = MdmPayload {
+                base: BasePayload {
+                    identifier: format!("{}.mdm.config", service_config.base_identifier),
+                    payload_type: PayloadType::Mdm,
+                    ..Default::default()
+                },
+                identity_certificate_uuid: identity.base.uuid,
+                topic: topic.clone(),
+                server_url: format!("https://{}/mdm/server", service_config.base_domain),
+                check_in_url: Some(format!(
+                    "https://{}/mdm/checkin",
+                    service_config.base_domain
+                )),
+                // We authenticate devices via the signature of their messages.
+                sign_message: Some(true),
+                check_out_when_removed: Some(true),
+                access_rights: ALL_ACCESS_RIGHTS,
+                server_capabilities: Some(vec![BOOTSTRAP_TOKEN_CAPABILITY.to_string()]),
+            };
+
+            let organization = [("organization", service_config.organization_name.as_str())];
+            let profile = Profile {
+                base: BasePayload {
+                    identifier: format!("{}.mdm", service_config.base_identifier),
+                    display_name: Some(messages.get("enrollment.mdm.display_name", &organization)),
+                    organization: Some(service_config.organization_name.clone()),
+                    ..Default::default()
+                },
+                contents: vec![Payload::Scep(identity), Payload::Mdm(mdm)],
+                ..Default::default()
+            };
+            state.serve_profile(profile)
         }
     }
 }
+
+/// The SCEP payload devices obtain their identity certificate with.
+fn scep_payload(service_config: &ServiceConfig, scep_challenge: String) -> ScepPayload {
+    ScepPayload {
+        base: BasePayload {
+            identifier: format!("{}.scep.config", service_config.base_identifier),
+            payload_type: PayloadType::Scep,
+            ..Default::default()
+        },
+        contents: ScepPayloadContents {
+            // We'll reuse the challenge from MDM.
+            challenge: scep_challenge,
+            key_type: "RSA".to_string(),
+            // 5 is digital signature (1) + key encipherment (5)
+            key_usage: 5,
+            key_size: 2048,
+            name: service_config.device_ca_name.clone(),
+            subject: vec![
+                vec![vec![
+                    "O".to_string(),
+                    service_config.organization_name.clone(),
+                ]],
+                vec![vec!["CN".to_string(), service_config.base_domain.clone()]],
+            ],
+            // /cgi-bin/pkiclient.exe seems to be standard.
+            url: format!(
+                "https://{}/cgi-bin/pkiclient.exe",
+                service_config.base_domain
+            ),
+        },
+    }
+}
@@ -0,0 +1,30 @@
+use crate::app_state::AppState;
+use crate::declarations;
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+/// Serves the data of an asset declaration, via the signed URL within its reference.
+///
+/// Assets use the "None" authentication type: the signature within their URL,
+/// which expires, is what keeps an asset's data private.
+pub async fn get_asset(
+    State(state): State<AppState>,
+    Path((identifier, expiry, signature)): Path<(String, i64, String)>,
+) -> Response {
+    let connection = &mut state.database.connection();
+    let Some((content_type, contents)) = declarations::asset_data(&state, connection, &identifier)
+    else {
+        return (StatusCode::NOT_FOUND).into_response();
+    };
+    let vault = &state.vault;
+    if !declarations::verify_asset_signature(vault, &identifier, &contents, expiry, &signature) {
+        // This may also occur if an asset within our assets directory has been modified.
+        println!("asset {identifier} was requested with an invalid or expired URL");
+        return (StatusCode::NOT_FOUND).into_response();
+    }
+
+    ([(header::CONTENT_TYPE, content_type)], contents).into_response()
+}
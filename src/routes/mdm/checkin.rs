@@ -0,0 +1,342 @@
+use crate::app_state::AppState;
+use crate::assets::AcceptLanguage;
+use crate::certificates::MdmRequest;
+use crate::commands::{self, Command, DeviceConfigured, DeviceInformation, SecurityInfo};
+use crate::database::{Device, devices, filevault_recovery_keys};
+use crate::escrow;
+use crate::plist::Plist;
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use time::OffsetDateTime;
+
+use super::{authenticate_device, declarative_management};
+
+#[derive(Deserialize)]
+#[serde(tag = "MessageType")]
+/// Messages sent by devices to our check-in endpoint.
+/// https://developer.apple.com/documentation/devicemanagement/check-in
+pub enum CheckinMessage {
+    Authenticate(AuthenticateMessage),
+    TokenUpdate(TokenUpdateMessage),
+    CheckOut(CheckOutMessage),
+    SetBootstrapToken(SetBootstrapTokenMessage),
+    GetBootstrapToken(GetBootstrapTokenMessage),
+    DeclarativeManagement(DeclarativeManagementMessage),
+}
+
+#[derive(Deserialize)]
+/// Sent upon installation of the MDM payload.
+/// https://developer.apple.com/documentation/devicemanagement/authenticaterequest
+pub struct AuthenticateMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+    #[serde(rename = "OSVersion")]
+    pub os_version: Option<String>,
+    #[serde(rename = "ProductName")]
+    pub product_name: Option<String>,
+    #[serde(rename = "SerialNumber")]
+    pub serial_number: Option<String>,
+    #[serde(rename = "IMEI")]
+    pub imei: Option<String>,
+}
+
+#[derive(Deserialize)]
+/// Sent whenever the device's push token changes, including immediately after enrollment.
+/// https://developer.apple.com/documentation/devicemanagement/tokenupdaterequest
+pub struct TokenUpdateMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+    #[serde(rename = "AwaitingConfiguration")]
+    /// Whether Setup Assistant is waiting for a DeviceConfigured command.
+    /// Only sent by devices enrolled via Automated Device Enrollment.
+    pub awaiting_configuration: Option<bool>,
+}
+
+#[derive(Deserialize)]
+/// Sent when the MDM profile is removed, given CheckOutWhenRemoved.
+/// https://developer.apple.com/documentation/devicemanagement/checkoutrequest
+pub struct CheckOutMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+}
+
+#[derive(Deserialize)]
+/// Sent by Macs to escrow their bootstrap token.
+/// https://developer.apple.com/documentation/devicemanagement/setbootstraptokenrequest
+pub struct SetBootstrapTokenMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+    #[serde(rename = "BootstrapToken")]
+    /// If absent, the device is asking us to remove its escrowed token.
+    pub bootstrap_token: Option<ByteBuf>,
+}
+
+#[derive(Deserialize)]
+/// Sent by Macs to retrieve their escrowed bootstrap token.
+/// https://developer.apple.com/documentation/devicemanagement/getbootstraptokenrequest
+pub struct GetBootstrapTokenMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+}
+
+#[derive(Deserialize)]
+/// Sent to reach a Declarative Device Management endpoint, such as to synchronize declarations.
+/// https://developer.apple.com/documentation/devicemanagement/declarativemanagementrequest
+pub struct DeclarativeManagementMessage {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+    #[serde(rename = "Endpoint")]
+    /// For example, "tokens", "declaration-items" or "status".
+    pub endpoint: String,
+    #[serde(rename = "Data")]
+    /// The JSON body of this request, such as a status report.
+    pub data: Option<ByteBuf>,
+}
+
+#[derive(Serialize)]
+/// Our response to GetBootstrapToken.
+/// https://developer.apple.com/documentation/devicemanagement/getbootstraptokenresponse
+pub struct GetBootstrapTokenResponse {
+    #[serde(rename = "BootstrapToken", skip_serializing_if = "Option::is_none")]
+    pub bootstrap_token: Option<ByteBuf>,
+}
+
+/// Handles check-in messages from enrolled (or enrolling) devices.
+///
+/// Devices send their preferred languages alongside, which we keep to localize
+/// what we later send them.
+pub async fn handle_checkin(
+    State(state): State<AppState>,
+    languages: AcceptLanguage,
+    request: MdmRequest,
+) -> Response {
+    let Ok(message) = Plist::<CheckinMessage>::from_xml(request.contents) else {
+        return (StatusCode::BAD_REQUEST).into_response();
+    };
+
+    let connection = &mut state.database.connection();
+    match message {
+        CheckinMessage::Authenticate(message) => {
+            authenticate(&state, connection, message, request.fingerprint, languages)
+        }
+        CheckinMessage::TokenUpdate(message) => {
+            let Some(device) = authenticate_device(connection, &message.udid, &request.fingerprint)
+            else {
+                return (StatusCode::UNAUTHORIZED).into_response();
+            };
+            // TODO(spotlightishere): Persist push tokens once APNs is implemented
+
+            if let Some(language) = languages.preferred()
+                && device.language.as_deref() != Some(language)
+            {
+                diesel::update(devices::table.find(&device.udid))
+                    .set(devices::language.eq(language))
+                    .execute(connection)
+                    .expect("error updating device");
+            }
+
+            // Macs only report their recovery key once FileVault is enabled,
+            // and may not have reported our administrator account's GUID.
+            // Until we have them, check again whenever the device updates its token.
+            if device.is_mac() {
+                request_recovery_key(&state, connection, &device);
+                escrow::request_admin_account_guid(&state, connection, &device.udid);
+            }
+
+            // Keep our inventory of applications reasonably current.
+            commands::refresh_applications(&state, connection, &device);
+
+            // Devices enrolled via Automated Device Enrollment wait within
+            // Setup Assistant until we're done configuring them.
+            if message.awaiting_configuration == Some(true)
+                && !commands::is_pending(connection, &device.udid, "DeviceConfigured")
+            {
+                if device.is_mac() {
+                    escrow::configure_admin_account(&state, connection, &device.udid);
+                } else {
+                    Command::DeviceConfigured(DeviceConfigured::default()).enqueue(
+                        &state,
+                        connection,
+                        &device.udid,
+                    );
+                }
+            }
+            (StatusCode::OK).into_response()
+        }
+        CheckinMessage::CheckOut(message) => {
+            if authenticate_device(connection, &message.udid, &request.fingerprint).is_none() {
+                return (StatusCode::UNAUTHORIZED).into_response();
+            }
+            // TODO(spotlightishere): Mark this device as unenrolled
+            // Its identity is no longer in use, so it may now enroll with another.
+            release_identity(connection, &message.udid);
+            (StatusCode::OK).into_response()
+        }
+        CheckinMessage::SetBootstrapToken(message) => {
+            if authenticate_device(connection, &message.udid, &request.fingerprint).is_none() {
+                return (StatusCode::UNAUTHORIZED).into_response();
+            }
+
+            match message.bootstrap_token {
+                Some(token) if !token.is_empty() => escrow::escrow_bootstrap_token(
+                    &state,
+                    connection,
+                    &message.udid,
+                    &request.fingerprint,
+                    &token,
+                ),
+                _ => escrow::remove_bootstrap_token(connection, &message.udid),
+            }
+            (StatusCode::OK).into_response()
+        }
+        CheckinMessage::GetBootstrapToken(message) => {
+            if authenticate_device(connection, &message.udid, &request.fingerprint).is_none() {
+                return (StatusCode::UNAUTHORIZED).into_response();
+            }
+
+            // We'll only ever provide the token escrowed by this same identity.
+            let bootstrap_token = escrow::open_bootstrap_token(
+                &state,
+                connection,
+                &message.udid,
+                &request.fingerprint,
+            )
+            .map(ByteBuf::from);
+            let response = GetBootstrapTokenResponse { bootstrap_token };
+            match Plist(response).to_xml() {
+                Ok(body) => ([(header::CONTENT_TYPE, "application/xml")], body).into_response(),
+                Err(err) => {
+                    println!("error within xml plist serialization: {err}");
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+                }
+            }
+        }
+        CheckinMessage::DeclarativeManagement(message) => {
+            let Some(device) = authenticate_device(connection, &message.udid, &request.fingerprint)
+            else {
+                return (StatusCode::UNAUTHORIZED).into_response();
+            };
+            declarative_management::handle_request(
+                connection,
+                &device,
+                &message.endpoint,
+                message.data.as_deref().map(Vec::as_slice),
+            )
+        }
+    }
+}
+
+/// Registers this device, binding it to the identity certificate it authenticated with.
+///
+/// Once bound, a device may only authenticate again with that same identity.
+/// Otherwise, anyone holding an identity issued by our CA could claim its UDID.
+/// Its binding is released upon CheckOut, or by an administrator.
+fn authenticate(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    message: AuthenticateMessage,
+    fingerprint: String,
+    languages: AcceptLanguage,
+) -> Response {
+    let bound_fingerprint = devices::table
+        .find(&message.udid)
+        .select(devices::identity_fingerprint)
+        .first::<Option<String>>(connection)
+        .optional()
+        .expect("can query devices")
+        .flatten();
+    if let Some(bound_fingerprint) = bound_fingerprint
+        && bound_fingerprint != fingerprint
+    {
+        println!(
+            "device {} authenticated with a different identity",
+            message.udid
+        );
+        return (StatusCode::UNAUTHORIZED).into_response();
+    }
+
+    let device = Device {
+        udid: message.udid.clone(),
+        device_version: message.os_version.unwrap_or_default(),
+        product: message.product_name.unwrap_or_default(),
+        serial_number: message.serial_number.unwrap_or_default(),
+        imei: message.imei,
+        last_contact: OffsetDateTime::now_utc(),
+        identity_fingerprint: Some(fingerprint),
+        is_apple_silicon: None,
+        admin_account_guid: None,
+        is_supervised: None,
+        applications_report_date: None,
+        user_name: None,
+        device_name: None,
+        asset_tag: None,
+        language: languages.preferred().map(str::to_string),
+        declarations_update_date: None,
+    };
+    diesel::insert_into(devices::table)
+        .values(&device)
+        .on_conflict(devices::udid)
+        .do_update()
+        .set(&device)
+        .execute(connection)
+        .expect("error persisting device");
+
+    // Every enrolled device is given a bypass code should it become Activation Locked.
+    // Its hash can be given to Apple for devices enrolled via Automated Device Enrollment
+    // (see the activation-lock tool). We'll additionally ask whether the device has generated its own.
+    escrow::escrow_server_code(state, connection, &device.udid);
+    let mut queries = vec![
+        "ActivationLockBypassCode".to_string(),
+        "IsSupervised".to_string(),
+        "DeviceName".to_string(),
+    ];
+    // A Mac's architecture determines which pre-boot password it supports.
+    if device.is_mac() {
+        queries.push("IsAppleSilicon".to_string());
+    }
+    Command::DeviceInformation(DeviceInformation { queries }).enqueue(
+        state,
+        connection,
+        &device.udid,
+    );
+    commands::refresh_applications(state, connection, &device);
+
+    // Macs should escrow their FileVault personal recovery key to us.
+    if device.is_mac() {
+        let escrow_profile = escrow::escrow_profile(state);
+        if commands::install_profile(state, connection, &device, escrow_profile).is_err() {
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
+        }
+        request_recovery_key(state, connection, &device);
+    }
+
+    (StatusCode::OK).into_response()
+}
+
+/// Unbinds a device from its identity certificate, allowing it to enroll again.
+pub fn release_identity(connection: &mut SqliteConnection, device_udid: &str) -> usize {
+    diesel::update(devices::table.find(device_udid))
+        .set(devices::identity_fingerprint.eq(None::<String>))
+        .execute(connection)
+        .expect("error updating device")
+}
+
+/// Queries a Mac's security information if we do not yet have its recovery key.
+fn request_recovery_key(state: &AppState, connection: &mut SqliteConnection, device: &Device) {
+    let escrowed_count = filevault_recovery_keys::table
+        .find(&device.udid)
+        .count()
+        .get_result::<i64>(connection)
+        .expect("can query recovery keys");
+    if escrowed_count > 0 || commands::is_pending(connection, &device.udid, "SecurityInfo") {
+        return;
+    }
+
+    Command::SecurityInfo(SecurityInfo::default()).enqueue(state, connection, &device.udid);
+}
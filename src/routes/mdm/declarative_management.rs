@@ -0,0 +1,63 @@
+use crate::database::Device;
+use crate::declarations::{self, Declaration, DeclarationCategory, StatusReport, TokensResponse};
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::SqliteConnection;
+
+/// Handles a request from a device to a Declarative Device Management endpoint,
+/// such as "tokens" or "declaration/configuration/com.example.passcode".
+/// https://developer.apple.com/documentation/devicemanagement/declarativemanagementrequest
+pub fn handle_request(
+    connection: &mut SqliteConnection,
+    device: &Device,
+    endpoint: &str,
+    data: Option<&[u8]>,
+) -> Response {
+    match endpoint {
+        "tokens" => Json(TokensResponse {
+            sync_tokens: declarations::sync_tokens(connection, device),
+        })
+        .into_response(),
+        "declaration-items" => {
+            Json(declarations::declaration_items(connection, &device.udid)).into_response()
+        }
+        "status" => {
+            let report = data.and_then(|data| serde_json::from_slice::<StatusReport>(data).ok());
+            let Some(report) = report else {
+                println!("device {} sent an invalid status report", device.udid);
+                return (StatusCode::BAD_REQUEST).into_response();
+            };
+            declarations::record_status_report(connection, &device.udid, report);
+            (StatusCode::OK).into_response()
+        }
+        _ => {
+            let Some((category, identifier)) = endpoint
+                .strip_prefix("declaration/")
+                .and_then(|path| path.split_once('/'))
+            else {
+                println!(
+                    "device {} requested unknown endpoint {endpoint}",
+                    device.udid
+                );
+                return (StatusCode::NOT_FOUND).into_response();
+            };
+
+            // Devices may only retrieve declarations assigned to them.
+            let declaration = declarations::device_declarations(connection, &device.udid)
+                .into_iter()
+                .find(|declaration| declaration.identifier == identifier)
+                .map(|declaration| Declaration::from_stored(&declaration));
+            match declaration {
+                Some(declaration)
+                    if declaration.category() == DeclarationCategory::parse(category) =>
+                {
+                    Json(declaration).into_response()
+                }
+                _ => (StatusCode::NOT_FOUND).into_response(),
+            }
+        }
+    }
+}
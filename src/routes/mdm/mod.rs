@@ -0,0 +1,38 @@
+use crate::database::Device;
+use crate::database::devices::dsl::*;
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+mod assets;
+mod checkin;
+mod declarative_management;
+mod server;
+
+pub use assets::get_asset;
+pub use checkin::{handle_checkin, release_identity};
+pub use server::handle_command_report;
+
+/// Loads an enrolled device, ensuring that the request came from
+/// the identity certificate it enrolled with.
+/// If so, we additionally record that the device has contacted us.
+pub fn authenticate_device(
+    connection: &mut SqliteConnection,
+    device_udid: &str,
+    fingerprint: &str,
+) -> Option<Device> {
+    let mut device = devices
+        .find(device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")?;
+    if device.identity_fingerprint.as_deref() != Some(fingerprint) {
+        return None;
+    }
+
+    device.last_contact = OffsetDateTime::now_utc();
+    diesel::update(devices.find(device_udid))
+        .set(last_contact.eq(device.last_contact))
+        .execute(connection)
+        .expect("error updating device");
+    Some(device)
+}
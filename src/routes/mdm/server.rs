@@ -0,0 +1,163 @@
+use crate::app_state::AppState;
+use crate::certificates::MdmRequest;
+use crate::commands::{self, CommandStatus};
+use crate::database::commands::dsl::*;
+use crate::database::{QueuedCommand, commands as commands_table};
+use crate::escrow;
+use crate::plist::Plist;
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+
+use super::authenticate_device;
+
+/// How long to wait before resending a command a device responded NotNow to.
+const NOT_NOW_RETRY_DELAY: Duration = Duration::minutes(5);
+
+#[derive(Deserialize)]
+/// Common keys within every request to our server URL.
+/// https://developer.apple.com/documentation/devicemanagement/implementing_device_management/sending_mdm_commands_to_a_device
+pub struct CommandReport {
+    #[serde(rename = "UDID")]
+    pub udid: String,
+    #[serde(rename = "Status")]
+    pub status: CommandStatus,
+    #[serde(rename = "CommandUUID")]
+    pub command_uuid: Option<String>,
+}
+
+/// Handles the result of the previous command, and provides the next queued command.
+pub async fn handle_command_report(State(state): State<AppState>, request: MdmRequest) -> Response {
+    let Ok(report) = Plist::<CommandReport>::from_xml(request.contents.clone()) else {
+        return (StatusCode::BAD_REQUEST).into_response();
+    };
+
+    let connection = &mut state.database.connection();
+    let Some(device) = authenticate_device(connection, &report.udid, &request.fingerprint) else {
+        return (StatusCode::UNAUTHORIZED).into_response();
+    };
+
+    // If this is a response to a previous command, update its state.
+    if let Some(reported_uuid) = &report.command_uuid
+        && report.status != CommandStatus::Idle
+    {
+        let queued_command = commands
+            .find(reported_uuid)
+            .filter(udid.eq(&device.udid))
+            .first::<QueuedCommand>(connection)
+            .optional()
+            .expect("can query commands");
+        let Some(queued_command) = queued_command else {
+            return (StatusCode::BAD_REQUEST).into_response();
+        };
+
+        diesel::update(commands.find(reported_uuid))
+            .set((
+                status.eq(report.status.as_str()),
+                last_update.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(connection)
+            .expect("error updating command");
+
+        match report.status {
+            CommandStatus::Acknowledged => {
+                handle_result(&state, connection, &queued_command, request.contents)
+            }
+            CommandStatus::Error | CommandStatus::CommandFormatError => {
+                handle_failure(connection, &queued_command)
+            }
+            _ => {}
+        }
+    }
+
+    // Lastly, send our next command, if any.
+    let retry_cutoff = OffsetDateTime::now_utc() - NOT_NOW_RETRY_DELAY;
+    let next_command = commands_table::table
+        .filter(udid.eq(&device.udid))
+        .filter(
+            status.eq(CommandStatus::Queued.as_str()).or(status
+                .eq(CommandStatus::NotNow.as_str())
+                .and(last_update.lt(retry_cutoff))),
+        )
+        .order(creation_date.asc())
+        .first::<QueuedCommand>(connection)
+        .optional()
+        .expect("can query commands");
+
+    match next_command {
+        Some(next_command) => {
+            let Some(command_payload) = commands::open_payload(&state, &next_command) else {
+                println!("unable to decrypt command {}", next_command.command_uuid);
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                    .into_response();
+            };
+            let headers = [(header::CONTENT_TYPE, "application/xml")];
+            (headers, command_payload).into_response()
+        }
+        // An empty response indicates we have no further commands.
+        None => (StatusCode::OK).into_response(),
+    }
+}
+
+/// Handles the response for an acknowledged command.
+fn handle_result(
+    state: &AppState,
+    connection: &mut SqliteConnection,
+    queued_command: &QueuedCommand,
+    contents: Vec<u8>,
+) {
+    let device_udid = &queued_command.udid;
+    match queued_command.request_type.as_str() {
+        "AccountConfiguration" => {
+            commands::accounts::handle_account_configuration(state, connection, queued_command)
+        }
+        "DeviceInformation" => {
+            commands::device_information::handle_response(state, connection, device_udid, contents)
+        }
+        "InstalledApplicationList" => {
+            commands::installed_applications::handle_response(connection, device_udid, contents)
+        }
+        "RotateFileVaultKey" => {
+            commands::filevault::handle_response(state, connection, device_udid, contents)
+        }
+        "SecurityInfo" => {
+            commands::security_info::handle_response(state, connection, device_udid, contents)
+        }
+        "SetAutoAdminPassword" => {
+            commands::accounts::handle_set_auto_admin_password(connection, queued_command)
+        }
+        "SetFirmwarePassword" => {
+            commands::passwords::handle_set_firmware_password(connection, queued_command, contents)
+        }
+        "SetRecoveryLock" => {
+            commands::passwords::handle_set_recovery_lock(connection, queued_command)
+        }
+        "VerifyFirmwarePassword" => commands::passwords::handle_verify_firmware_password(
+            connection,
+            queued_command,
+            contents,
+        ),
+        "VerifyRecoveryLock" => {
+            commands::passwords::handle_verify_recovery_lock(connection, queued_command, contents)
+        }
+        // Most commands have no response beyond their status.
+        _ => {}
+    }
+}
+
+/// Handles a command the device was unable to process.
+fn handle_failure(connection: &mut SqliteConnection, queued_command: &QueuedCommand) {
+    match queued_command.request_type.as_str() {
+        // The password we generated never took effect.
+        "AccountConfiguration"
+        | "SetAutoAdminPassword"
+        | "SetFirmwarePassword"
+        | "SetRecoveryLock" => escrow::password_failed(connection, &queued_command.command_uuid),
+        _ => {}
+    }
+}
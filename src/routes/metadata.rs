@@ -1,5 +1,8 @@
 use crate::app_state::AppState;
-use crate::payloads::{BasePayload, PayloadScope, PayloadType, Profile, RootCertificatePayload};
+use crate::assets::AcceptLanguage;
+use crate::payloads::{
+    BasePayload, Payload, PayloadScope, PayloadType, Profile, RootCertificatePayload,
+};
 use axum::Json;
 use axum::extract::State;
 use axum::response::Response;
@@ -36,39 +39,38 @@ pub async fn get_anchor_certs() -> Json<Vec<String>> {
     Json(certificates)
 }
 
-pub async fn create_trust_profile(State(state): State<AppState>) -> Response {
+pub async fn create_trust_profile(
+    State(state): State<AppState>,
+    languages: AcceptLanguage,
+) -> Response {
     // Provides the root CA certificate necessary to continue a connection to this server.
     // https://developer.apple.com/documentation/devicemanagement/implementing_device_management/simplifying_mdm_server_administration_for_ios_devices
+    // Its strings are localized per the requesting browser's Accept-Language header.
     let service_config = &state.config.service;
+    let messages = state.messages.localizer(&languages.0);
+    let organization = [("organization", service_config.organization_name.as_str())];
+    let profile_config = &state.config.profiles.trust;
     let root_ca_contents = state.certificates.root_ca_cert.to_der().unwrap();
 
     let trust_profile = Profile {
         base: BasePayload {
             identifier: format!("{}.trust-profile", service_config.base_identifier),
-            display_name: Some(format!(
-                "Trust Profile for {}",
-                service_config.organization_name
-            )),
-            description: Some(format!(
-                "Configures your device to securely connect to the MDM service for \"{}\".",
-                service_config.organization_name
-            )),
+            display_name: Some(messages.get("trust.display_name", &organization)),
+            description: Some(messages.get("trust.description", &organization)),
             ..Default::default()
         },
         scope: Some(PayloadScope::System),
-        contents: vec![RootCertificatePayload {
+        contents: vec![Payload::RootCertificate(RootCertificatePayload {
             base: BasePayload {
                 identifier: format!("{}.trust-profile.root", service_config.base_identifier),
-                display_name: Some(format!(
-                    "Root Certificate for {}",
-                    service_config.organization_name
-                )),
+                display_name: Some(messages.get("trust.root.display_name", &organization)),
                 payload_type: PayloadType::CertificateRoot,
                 ..Default::default()
             },
             file_name: "root_ca.pem".to_string(),
             certificate: root_ca_contents,
-        }],
+        })],
+        options: profile_config.options(),
     };
 
     state.serve_profile(trust_profile)
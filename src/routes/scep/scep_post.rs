@@ -1,11 +1,11 @@
 use axum::{
-    extract::{Query, State},
+    extract::Query,
     http::StatusCode,
     response::{IntoResponse, Response},
 };
 use serde::Deserialize;
 
-use crate::{app_state::AppState, certificates::Pkcs7Body};
+use crate::certificates::Pkcs7Body;
 
 #[derive(Deserialize)]
 /// Standard values passed within query parameters.
@@ -15,6 +15,8 @@ pub struct ClientParams {
 }
 
 /// messageTypes per section 3.2.1.2.
+// These will be used once PKIOperation requests are handled.
+#[allow(dead_code)]
 enum PKIMessageType {
     CertRep = 3,
     RenewalReq = 17,
@@ -30,11 +32,7 @@ enum PKIMessageType {
 /// This component handles POSTed PKIOperation requests.
 /// Per section 4.3: "Note that when used with HTTP POST, the only OPERATION possible
 /// is "PKIOperation", so many CAs don't check this value or even notice its absence."
-pub async fn post_op_handler(
-    State(state): State<AppState>,
-    Query(params): Query<ClientParams>,
-    envelope: Pkcs7Body,
-) -> Response {
+pub async fn post_op_handler(Query(params): Query<ClientParams>, _envelope: Pkcs7Body) -> Response {
     // We are checking the operation out of spite.
     if params.operation != "PKIOperation" {
         return (StatusCode::BAD_REQUEST).into_response();
@@ -0,0 +1,57 @@
+use crate::database::activation_lock_bypass_codes::dsl::*;
+use crate::database::{ActivationLockBypassCode, Device, devices};
+use crate::escrow::SOURCE_SERVER;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::{load_state, usage};
+
+#[derive(Serialize)]
+/// The body for Apple's Activation Lock endpoint within the device enrollment service.
+/// https://developer.apple.com/documentation/devicemanagement/activation_lock_a_device
+pub struct ActivationLockRequest {
+    /// The serial number of the device.
+    pub device: String,
+    /// The hash of our bypass code.
+    pub escrow_key: String,
+    /// Displayed on the device's Activation Lock screen.
+    pub lost_message: String,
+}
+
+/// Prepares the request necessary to escrow a device's bypass code with Apple.
+///
+/// TODO(spotlightishere): Submit this ourselves once we have
+/// support for authenticating with Apple's device enrollment service.
+pub fn run(args: &[String]) {
+    let Some(device_udid) = args.first() else {
+        usage("activation-lock <UDID> [config path]");
+    };
+    let state = load_state(args, 1);
+    let connection = &mut state.database.connection();
+
+    let Some(device) = devices::table
+        .find(device_udid)
+        .first::<Device>(connection)
+        .optional()
+        .expect("can query devices")
+    else {
+        usage("activation-lock <UDID> [config path] (no device has this UDID)");
+    };
+    let Some(record) = activation_lock_bypass_codes
+        .find((device_udid, SOURCE_SERVER))
+        .first::<ActivationLockBypassCode>(connection)
+        .optional()
+        .expect("can query bypass codes")
+    else {
+        usage("activation-lock <UDID> [config path] (no bypass code is escrowed)");
+    };
+
+    let request = ActivationLockRequest {
+        device: device.serial_number,
+        escrow_key: record.code_hash,
+        lost_message: "".to_string(),
+    };
+    let body = serde_json::to_string_pretty(&request).expect("should be able to encode request");
+    println!("POST https://mdmenrollment.apple.com/device/activationlock");
+    println!("{body}");
+}
@@ -0,0 +1,32 @@
+use crate::payloads::import_profile;
+use std::{fs, process};
+
+use super::usage;
+
+/// Lints an existing .mobileconfig file, reporting its signer and any issues within.
+/// Exits unsuccessfully if the profile could not be imported.
+pub fn run(args: &[String]) {
+    let Some(profile_path) = args.first() else {
+        usage("lint-profile <path>");
+    };
+    let contents = fs::read(profile_path).expect("should be able to read profile");
+
+    let imported = match import_profile(contents) {
+        Ok(imported) => imported,
+        Err(e) => {
+            eprintln!("{profile_path}: {e}");
+            process::exit(1);
+        }
+    };
+    match imported.signer {
+        Some(signer) => println!("signed by {}", signer.describe()),
+        None => println!("unsigned"),
+    }
+    for warning in &imported.warnings {
+        println!("{warning}");
+    }
+    if imported.profile.is_none() {
+        eprintln!("{profile_path}: unable to import profile");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::app_state::AppState;
+use crate::config::Config;
+use std::process;
+
+mod activation_lock;
+mod lint_profile;
+
+/// Administrative tools, run as `mdm-server <tool> [arguments...] [config path]`.
+const TOOLS: [&str; 2] = ["activation-lock", "lint-profile"];
+
+/// Whether the given argument names a tool.
+pub fn is_tool(name: &str) -> bool {
+    TOOLS.contains(&name)
+}
+
+/// Runs the tool named by the first argument.
+pub fn run(args: &[String]) {
+    match args[0].as_str() {
+        "activation-lock" => activation_lock::run(&args[1..]),
+        "lint-profile" => lint_profile::run(&args[1..]),
+        _ => unreachable!("is_tool should be checked prior"),
+    }
+}
+
+/// Loads our state from the configuration path at the given position, if present.
+fn load_state(args: &[String], position: usize) -> AppState {
+    let config_path = match args.get(position) {
+        Some(s) => s.clone(),
+        None => "./config.toml".to_string(),
+    };
+    let config = Config::load_from(config_path);
+    AppState::with_config(config)
+}
+
+/// Prints the given usage and exits.
+fn usage(usage: &str) -> ! {
+    eprintln!("usage: mdm-server {usage}");
+    process::exit(1)
+}
@@ -0,0 +1,109 @@
+use crate::config::Config;
+use crate::database::User;
+use crate::payloads::{
+    BasePayload, CalDavPayload, CardDavPayload, ExchangeActiveSyncPayload, MailPayload, Payload,
+    PayloadScope, Profile,
+};
+
+/// Creates a profile configuring our mail, contacts and calendar accounts for a user.
+/// Their name, email address and user name are filled in from their user record.
+/// Passwords are never included, so the user is prompted for them upon installation.
+///
+/// Mail and Exchange accounts require an email address.
+/// If one is configured but the user has none, an error describing this is returned.
+pub fn account_profile(config: &Config, user: &User) -> Result<Profile<Payload>, String> {
+    let service_config = &config.service;
+    let accounts_config = &config.accounts;
+    let identifier = |suffix: &str| format!("{}.accounts.{suffix}", service_config.base_identifier);
+    let account_description = Some(service_config.organization_name.clone());
+
+    let requires_email_address = accounts_config.sign_in_with_email_address
+        || accounts_config.mail.is_some()
+        || accounts_config.exchange.is_some();
+    if requires_email_address && user.email_address.is_none() {
+        return Err(format!("user {} has no email address", user.user_name));
+    }
+    let sign_in_name = if accounts_config.sign_in_with_email_address {
+        user.email_address.clone()
+    } else {
+        Some(user.user_name.clone())
+    };
+
+    let mut payloads = vec![];
+    if let Some(mail_config) = &accounts_config.mail {
+        payloads.push(Payload::Mail(MailPayload {
+            base: BasePayload {
+                identifier: identifier("mail"),
+                ..MailPayload::default().base
+            },
+            email_account_description: account_description.clone(),
+            email_account_name: user.full_name.clone(),
+            email_address: user.email_address.clone(),
+            incoming_mail_server_host_name: mail_config.incoming_host.clone(),
+            incoming_mail_server_port_number: mail_config.incoming_port,
+            incoming_mail_server_use_ssl: Some(true),
+            incoming_mail_server_username: sign_in_name.clone(),
+            outgoing_mail_server_host_name: mail_config.outgoing_host.clone(),
+            outgoing_mail_server_port_number: mail_config.outgoing_port,
+            outgoing_mail_server_use_ssl: Some(true),
+            outgoing_mail_server_username: sign_in_name.clone(),
+            outgoing_password_same_as_incoming_password: Some(true),
+            ..Default::default()
+        }));
+    }
+    if let Some(exchange_config) = &accounts_config.exchange {
+        payloads.push(Payload::ExchangeActiveSync(ExchangeActiveSyncPayload {
+            base: BasePayload {
+                identifier: identifier("exchange"),
+                ..ExchangeActiveSyncPayload::default().base
+            },
+            account_name: account_description.clone(),
+            email_address: user.email_address.clone(),
+            host: exchange_config.host.clone(),
+            ssl: Some(true),
+            user_name: sign_in_name.clone(),
+            ..Default::default()
+        }));
+    }
+    if let Some(caldav_config) = &accounts_config.caldav {
+        payloads.push(Payload::CalDav(CalDavPayload {
+            base: BasePayload {
+                identifier: identifier("caldav"),
+                ..CalDavPayload::default().base
+            },
+            account_description: account_description.clone(),
+            host_name: caldav_config.host.clone(),
+            port: caldav_config.port,
+            principal_url: caldav_config.principal_url.clone(),
+            use_ssl: Some(true),
+            username: sign_in_name.clone(),
+        }));
+    }
+    if let Some(carddav_config) = &accounts_config.carddav {
+        payloads.push(Payload::CardDav(CardDavPayload {
+            base: BasePayload {
+                identifier: identifier("carddav"),
+                ..CardDavPayload::default().base
+            },
+            account_description: account_description.clone(),
+            host_name: carddav_config.host.clone(),
+            port: carddav_config.port,
+            principal_url: carddav_config.principal_url.clone(),
+            use_ssl: Some(true),
+            username: sign_in_name.clone(),
+        }));
+    }
+
+    Ok(Profile {
+        base: BasePayload {
+            identifier: format!("{}.accounts", service_config.base_identifier),
+            display_name: Some("Accounts".to_string()),
+            organization: Some(service_config.organization_name.clone()),
+            ..Default::default()
+        },
+        // Accounts belong to the user, rather than the device as a whole.
+        scope: Some(PayloadScope::User),
+        contents: payloads,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,16 @@
+use crate::database::{Device, User, users};
+use diesel::prelude::*;
+
+mod accounts;
+
+pub use accounts::account_profile;
+
+/// The user this device is assigned to, if any.
+pub fn assigned_user(connection: &mut SqliteConnection, device: &Device) -> Option<User> {
+    let user_name = device.user_name.as_ref()?;
+    users::table
+        .find(user_name)
+        .first::<User>(connection)
+        .optional()
+        .expect("can query users")
+}
@@ -0,0 +1,154 @@
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload},
+};
+use aws_lc_rs::hmac;
+use std::fs;
+
+use crate::config::Config;
+
+/// The length of the nonce prepended to every sealed value.
+const NONCE_LENGTH: usize = 12;
+
+/// Encrypts secrets (bypass codes, recovery keys, passwords, etc.)
+/// before they are persisted within our database.
+///
+/// Values are sealed with AES-256-GCM under a key stored alongside
+/// our certificates. Every value is additionally bound to a context
+/// (typically a device's UDID and the kind of secret), so that a sealed
+/// value cannot be copied between rows and opened successfully.
+///
+/// The same key additionally derives a separate key for signing values,
+/// such as URLs we provide to devices.
+#[derive(Clone)]
+pub struct Vault {
+    cipher: Aes256Gcm,
+    signing_key: hmac::Key,
+}
+
+impl Vault {
+    /// Loads our vault key, creating one if necessary.
+    pub fn load_key(config: &Config) -> Self {
+        let key_path = config.certificate_path("vault_key.bin");
+        if !key_path.exists() {
+            let key = Aes256Gcm::generate_key(OsRng);
+            fs::write(&key_path, key).expect("should be able to write vault key");
+        }
+
+        let key_contents = fs::read(&key_path).expect("should be able to read vault key");
+        if key_contents.len() != 32 {
+            panic!("vault key should be exactly 32 bytes in length");
+        }
+        Vault::from_key(&key_contents)
+    }
+
+    /// Creates a vault using the given 32-byte key.
+    pub(crate) fn from_key(key_contents: &[u8]) -> Self {
+        let key = Key::<Aes256Gcm>::from_slice(key_contents);
+        let derivation_key = hmac::Key::new(hmac::HMAC_SHA256, key_contents);
+        let signing_key = hmac::Key::new(
+            hmac::HMAC_SHA256,
+            hmac::sign(&derivation_key, b"signing").as_ref(),
+        );
+
+        Vault {
+            cipher: Aes256Gcm::new(key),
+            signing_key,
+        }
+    }
+
+    /// Encrypts the given secret, returning its nonce and ciphertext.
+    pub fn seal(&self, context: &str, secret: &[u8]) -> Vec<u8> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: secret,
+            aad: context.as_bytes(),
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .expect("should be able to encrypt secret");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    /// Decrypts a secret previously sealed with the same context.
+    /// If it has been tampered with (or the context differs), this returns None.
+    pub fn open(&self, context: &str, sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LENGTH {
+            return None;
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: context.as_bytes(),
+        };
+        self.cipher.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+
+    /// Convenience wrapper to seal a string.
+    pub fn seal_string(&self, context: &str, secret: &str) -> Vec<u8> {
+        self.seal(context, secret.as_bytes())
+    }
+
+    /// Convenience wrapper to open a sealed string.
+    pub fn open_string(&self, context: &str, sealed: &[u8]) -> Option<String> {
+        String::from_utf8(self.open(context, sealed)?).ok()
+    }
+
+    /// Signs the given value, returning its signature in hexadecimal form.
+    pub fn sign(&self, value: &str) -> String {
+        hex::encode(hmac::sign(&self.signing_key, value.as_bytes()))
+    }
+
+    /// Whether the given signature was created by `sign` for this value.
+    pub fn verify(&self, value: &str, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        hmac::verify(&self.signing_key, value.as_bytes(), &signature).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_returns_sealed_secret() {
+        let vault = Vault::from_key(&[7; 32]);
+        let sealed = vault.seal_string("device:passcode", "hunter2");
+        assert_eq!(
+            vault.open_string("device:passcode", &sealed).as_deref(),
+            Some("hunter2")
+        );
+    }
+
+    #[test]
+    fn open_rejects_mismatched_context() {
+        let vault = Vault::from_key(&[7; 32]);
+        let sealed = vault.seal_string("first-device:passcode", "hunter2");
+        assert!(vault.open("second-device:passcode", &sealed).is_none());
+    }
+
+    #[test]
+    fn open_rejects_tampered_or_truncated_values() {
+        let vault = Vault::from_key(&[7; 32]);
+        let mut sealed = vault.seal_string("device:passcode", "hunter2");
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(vault.open("device:passcode", &sealed).is_none());
+        assert!(
+            vault
+                .open("device:passcode", &sealed[..NONCE_LENGTH - 1])
+                .is_none()
+        );
+
+        let other_vault = Vault::from_key(&[8; 32]);
+        let sealed = vault.seal_string("device:passcode", "hunter2");
+        assert!(other_vault.open("device:passcode", &sealed).is_none());
+    }
+}